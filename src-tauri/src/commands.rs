@@ -5,13 +5,22 @@
 
 use crate::error::AppError;
 use crate::segy::{
+    analysis::{self, HistogramResult, SpectrumResult},
+    geometry::{self, SpacingStats, TraceOffset},
     rendering::{
-        self, AmplitudeScaling, ColormapType, RenderMode, RenderedImage, ViewportConfig,
-        WiggleConfig,
+        self, AmplitudeScaling, ColormapType, PngCompression, RenderCancelToken, RenderConfig,
+        RenderState, RenderedImage, ViewportConfig, WiggleConfig,
     },
-    HeaderFieldSpec, SegyData, SegyFormatSpec, SegyReaderState, TraceBlock,
+    validation, ConsistencyWarning, DecodedField, FieldDiff, FileSummary, HeaderFieldSpec,
+    RawBinaryHeader, RawTextualHeader, SampleFormatSuggestion, SamplesPayload, ScanProgress,
+    SegyData, SegyFileConfig, SegyFormatSpec, SegyReader, SegyReaderState, TraceBlock, TraceHeader,
+    ValidationFinding,
 };
-use tauri::State;
+use std::path::{Path, PathBuf};
+use tauri::{AppHandle, Emitter, State};
+
+/// Event carrying `ScanProgress` while long trace scans run.
+const SCAN_PROGRESS_EVENT: &str = "segy://scan-progress";
 
 /// Standard command result type for Tauri invokes.
 ///
@@ -26,6 +35,8 @@ type CommandResult<T> = Result<T, String>;
 ///
 /// # Arguments
 /// * `file_path` - Absolute path to the SEG-Y file
+/// * `override_config` - Optional geometry (samples per trace, sample format,
+///   byte order) replacing the values derived from a corrupt binary header
 ///
 /// # Returns
 /// A Result containing the SegyData structure with headers only
@@ -54,30 +65,198 @@ type CommandResult<T> = Result<T, String>;
 #[tauri::command]
 pub async fn load_segy_file(
     file_path: String,
+    override_config: Option<SegyFileConfig>,
     state: State<'_, SegyReaderState>,
 ) -> CommandResult<SegyData> {
-    let reader = state.open(file_path).await.map_err(String::from)?;
+    let reader = state
+        .open(file_path, override_config)
+        .await
+        .map_err(String::from)?;
     Ok(reader.data())
 }
 
+/// Count the traces in a SEG-Y file without opening a reader
+///
+/// Reads only the textual and binary headers, skipping the memory map and
+/// reader cache used by `load_segy_file`, so file list previews stay cheap.
+/// Returns `None` when the trace block size can't be determined.
+#[tauri::command]
+pub async fn get_trace_count(file_path: String) -> CommandResult<Option<usize>> {
+    run_blocking(move || SegyReader::count_traces(&file_path)).await
+}
+
+/// Summarize an open file for the file-info sidebar
+///
+/// Gathers revision, byte order, sample format, geometry, and sorting in one
+/// call, with codes turned into display names.
+#[tauri::command]
+pub async fn get_file_summary(
+    file_path: String,
+    state: State<'_, SegyReaderState>,
+) -> CommandResult<FileSummary> {
+    let reader = state.get_or_open(file_path).await.map_err(String::from)?;
+    run_blocking(move || reader.summary()).await
+}
+
+/// Get the original textual header bytes before any encoding conversion
+///
+/// Includes the detected encoding so callers can decode exotic code pages
+/// or compare against the converted lines in `SegyData`.
+#[tauri::command]
+pub async fn get_raw_textual_header(
+    file_path: String,
+    state: State<'_, SegyReaderState>,
+) -> CommandResult<RawTextualHeader> {
+    let reader = state.get_or_open(file_path).await.map_err(String::from)?;
+    Ok(RawTextualHeader::from(reader.textual_header()))
+}
+
+/// Get the original 400 binary header bytes and the detected byte order
+///
+/// Lets advanced users apply their own field interpretation or confirm the
+/// endianness detection when parsed values look wrong.
+#[tauri::command]
+pub async fn get_binary_header_raw(
+    file_path: String,
+    state: State<'_, SegyReaderState>,
+) -> CommandResult<RawBinaryHeader> {
+    let reader = state.get_or_open(file_path).await.map_err(String::from)?;
+    reader.raw_binary_header().map_err(String::from)
+}
+
+/// Read raw bytes at any file offset for hex inspection
+///
+/// `length` is capped at 64 KiB; larger requests and ranges past the end of
+/// the file return a `ValidationError`.
+#[tauri::command]
+pub async fn read_bytes(
+    file_path: String,
+    offset: u64,
+    length: usize,
+    state: State<'_, SegyReaderState>,
+) -> CommandResult<Vec<u8>> {
+    let reader = state.get_or_open(file_path).await.map_err(String::from)?;
+    run_blocking(move || reader.read_bytes(offset, length)).await
+}
+
+/// Fingerprint a file for caching rendered tiles across sessions
+///
+/// Derived from the file size, modification time, and binary header bytes;
+/// a change-detection heuristic, not a cryptographic hash. Returned as 16
+/// hex digits because JavaScript numbers cannot hold every `u64`.
+#[tauri::command]
+pub async fn get_content_hash(
+    file_path: String,
+    state: State<'_, SegyReaderState>,
+) -> CommandResult<String> {
+    let reader = state.get_or_open(file_path).await.map_err(String::from)?;
+    run_blocking(move || Ok(format!("{:016x}", reader.content_hash()?))).await
+}
+
+/// Suggest whether a file's 4-byte samples are really IBM or IEEE float
+///
+/// Decodes a few traces both ways and reports the more plausible format, so
+/// the UI can offer an override (via `load_segy_file`'s `override_config`)
+/// when it differs from the declared code. Nothing is applied here.
+#[tauri::command]
+pub async fn suggest_sample_format(
+    file_path: String,
+    state: State<'_, SegyReaderState>,
+) -> CommandResult<SampleFormatSuggestion> {
+    let reader = state.get_or_open(file_path).await.map_err(String::from)?;
+    run_blocking(move || reader.detect_sample_format()).await
+}
+
 /// Get binary header field specifications
 ///
-/// Returns metadata dynamically loaded from canonical SEG-Y Rev 0 spec
+/// When `file_path` is provided, the spec matching the file's declared SEG-Y
+/// revision is returned; otherwise the canonical Rev 0 spec is used.
 #[tauri::command]
-pub fn get_binary_header_spec() -> CommandResult<Vec<HeaderFieldSpec>> {
-    let spec = SegyFormatSpec::load_rev0()?;
+pub async fn get_binary_header_spec(
+    file_path: Option<String>,
+    state: State<'_, SegyReaderState>,
+) -> CommandResult<Vec<HeaderFieldSpec>> {
+    let spec = load_spec_for_file(file_path, &state).await?;
     Ok(spec.get_binary_header_fields())
 }
 
 /// Get trace header field specifications
 ///
-/// Returns metadata dynamically loaded from canonical SEG-Y Rev 0 spec
+/// When `file_path` is provided, the spec matching the file's declared SEG-Y
+/// revision is returned; otherwise the canonical Rev 0 spec is used.
 #[tauri::command]
-pub fn get_trace_header_spec() -> CommandResult<Vec<HeaderFieldSpec>> {
-    let spec = SegyFormatSpec::load_rev0()?;
+pub async fn get_trace_header_spec(
+    file_path: Option<String>,
+    state: State<'_, SegyReaderState>,
+) -> CommandResult<Vec<HeaderFieldSpec>> {
+    let spec = load_spec_for_file(file_path, &state).await?;
     Ok(spec.get_trace_header_fields())
 }
 
+/// Remap trace header fields for the rest of the session
+///
+/// `overrides` are merged onto the spec for the file's revision, replacing
+/// fields by key (e.g. inlines at byte 9) and dropping fields whose bytes they
+/// take over. They apply to every later spec-driven header read of this file
+/// until replaced; pass an empty list to restore the standard layout.
+#[tauri::command]
+pub async fn set_trace_header_overrides(
+    file_path: String,
+    overrides: Vec<HeaderFieldSpec>,
+    state: State<'_, SegyReaderState>,
+) -> CommandResult<()> {
+    state
+        .set_header_overrides(file_path, overrides)
+        .await
+        .map_err(String::from)
+}
+
+/// Get the trace header fields in effect for a file, including overrides
+#[tauri::command]
+pub async fn get_effective_trace_spec(
+    file_path: String,
+    state: State<'_, SegyReaderState>,
+) -> CommandResult<Vec<HeaderFieldSpec>> {
+    let reader = state.get_or_open(file_path).await.map_err(String::from)?;
+    reader
+        .effective_trace_spec()
+        .map(|spec| spec.get_trace_header_fields())
+        .map_err(String::from)
+}
+
+/// Load a custom header specification from a JSON file
+///
+/// The spec may `extends` a bundled spec (`rev0`, `rev1`, `rev2`, `rev21`) and
+/// list only added `fields` and partial `overrides`, e.g. to relocate
+/// non-standard inline/crossline bytes without recompiling.
+#[tauri::command]
+pub async fn get_custom_header_spec(path: String) -> CommandResult<SegyFormatSpec> {
+    run_blocking(move || SegyFormatSpec::load_from_path(&path).map_err(AppError::config)).await
+}
+
+/// Validate a SEG-Y file against the spec for its declared revision
+///
+/// Checks required binary header fields, coded values, the sample format, and
+/// whether the data region holds a whole number of traces. Each deviation is
+/// returned as a finding so the UI can list warnings separately from errors.
+#[tauri::command]
+pub async fn validate_segy(file_path: String) -> CommandResult<Vec<ValidationFinding>> {
+    run_blocking(move || validation::validate_file(&file_path)).await
+}
+
+/// Compare binary header sample count and interval against trace headers
+///
+/// Checks the first trace headers and returns one warning per mismatching
+/// field and trace. Diagnostic only; parsing is unchanged.
+#[tauri::command]
+pub async fn check_consistency(
+    file_path: String,
+    state: State<'_, SegyReaderState>,
+) -> CommandResult<Vec<ConsistencyWarning>> {
+    let reader = state.get_or_open(file_path).await.map_err(String::from)?;
+    run_blocking(move || Ok(reader.check_header_consistency())).await
+}
+
 /// Load a single trace by index from a SEG-Y file
 ///
 /// # Arguments
@@ -102,43 +281,612 @@ pub async fn load_single_trace(
 ///
 /// Uses memory-mapped I/O for fast random access at any file offset.
 /// More efficient than loading traces one-by-one via load_single_trace.
+/// `skip_aux_traces` drops auxiliary traces, assuming they lead each record.
 #[tauri::command]
 pub async fn load_trace_range(
     file_path: String,
     start_index: usize,
     count: usize,
     max_samples: Option<usize>,
+    skip_aux_traces: Option<bool>,
     state: State<'_, SegyReaderState>,
 ) -> CommandResult<Vec<TraceBlock>> {
     let reader = state.get_or_open(file_path).await.map_err(String::from)?;
-    run_blocking(move || reader.load_trace_range(start_index, count, max_samples)).await
+    run_blocking(move || {
+        reader.load_trace_range(
+            start_index,
+            count,
+            max_samples,
+            skip_aux_traces.unwrap_or(false),
+        )
+    })
+    .await
+}
+
+/// Load trace samples as a flat row-major f32 buffer
+///
+/// Every sample format is converted to f32, avoiding the much larger nested
+/// `TraceData` JSON. Traces shorter than the longest are right-padded with
+/// NaN (sent as `null`).
+#[tauri::command]
+pub async fn load_trace_samples_f32(
+    file_path: String,
+    start_index: usize,
+    count: usize,
+    max_samples: Option<usize>,
+    state: State<'_, SegyReaderState>,
+) -> CommandResult<SamplesPayload> {
+    let reader = state.get_or_open(file_path).await.map_err(String::from)?;
+    run_blocking(move || {
+        let traces = reader.load_trace_data_range(start_index, count, max_samples, None)?;
+        Ok(SamplesPayload::from_traces(&traces))
+    })
+    .await
+}
+
+/// Load one trace's samples as f32 for a 1D plot
+///
+/// Lighter than `load_single_trace`: the header is skipped and every sample
+/// format is converted to f32. `max_samples` downsamples the trace like the
+/// other loaders. `apply_weighting` opts into scaling by the header's trace
+/// weighting factor (`2^-N`), which needs the header to be read.
+#[tauri::command]
+pub async fn get_trace_samples(
+    file_path: String,
+    trace_index: usize,
+    max_samples: Option<usize>,
+    apply_weighting: Option<bool>,
+    state: State<'_, SegyReaderState>,
+) -> CommandResult<Vec<f32>> {
+    let reader = state.get_or_open(file_path).await.map_err(String::from)?;
+    run_blocking(move || {
+        if apply_weighting.unwrap_or(false) {
+            let trace = reader.load_single_trace(trace_index, max_samples)?;
+            return Ok(trace.data.to_f32_vec_weighted(Some(&trace.header)));
+        }
+        let traces = reader.load_trace_data_range(trace_index, 1, max_samples, None)?;
+        Ok(traces[0].to_f32_vec())
+    })
+    .await
+}
+
+/// Load trace headers only, skipping sample data
+///
+/// Returns `count` headers starting at `start_index`, taking every
+/// `stride`-th trace (defaults to 1). Intended for plotting header
+/// attributes such as fold or elevation across a whole line.
+#[tauri::command]
+pub async fn load_trace_headers(
+    file_path: String,
+    start_index: usize,
+    count: usize,
+    stride: Option<usize>,
+    state: State<'_, SegyReaderState>,
+) -> CommandResult<Vec<TraceHeader>> {
+    let reader = state.get_or_open(file_path).await.map_err(String::from)?;
+    let stride = stride.unwrap_or(1);
+    run_blocking(move || reader.load_trace_headers_range(start_index, count, stride)).await
+}
+
+/// Get the minimum and maximum of an integer trace header field
+///
+/// Reads only the field's bytes from every trace header, for example to
+/// auto-range an attribute color scale. Returns `[min, max]`. Emits
+/// `segy://scan-progress` events while scanning.
+#[tauri::command]
+pub async fn header_field_range(
+    app: AppHandle,
+    file_path: String,
+    field_key: String,
+    state: State<'_, SegyReaderState>,
+) -> CommandResult<(i64, i64)> {
+    let reader = state.get_or_open(file_path).await.map_err(String::from)?;
+    run_blocking(move || reader.header_field_range(&field_key, &scan_progress_emitter(app))).await
+}
+
+/// Extract one integer trace header field for a range of traces
+///
+/// Returns the values in trace order without decoding samples; the building
+/// block for attribute plots such as elevation or statics.
+#[tauri::command]
+pub async fn extract_header_field(
+    file_path: String,
+    field_key: String,
+    start_index: usize,
+    count: usize,
+    state: State<'_, SegyReaderState>,
+) -> CommandResult<Vec<i64>> {
+    let reader = state.get_or_open(file_path).await.map_err(String::from)?;
+    run_blocking(move || reader.extract_header_field(&field_key, start_index, count)).await
+}
+
+/// Find the first trace whose integer header field equals `target`
+///
+/// Reads only the field's bytes from each header, e.g. to jump to
+/// `cdp_ensemble_number == 4200`. Pass `sorted` for fields known to be
+/// monotonic across the file to binary search instead of scanning. Returns
+/// `None` when no trace matches. Emits `segy://scan-progress` events while
+/// scanning.
+#[tauri::command]
+pub async fn find_trace_by_field(
+    app: AppHandle,
+    file_path: String,
+    field_key: String,
+    target: i64,
+    sorted: Option<bool>,
+    state: State<'_, SegyReaderState>,
+) -> CommandResult<Option<usize>> {
+    let reader = state.get_or_open(file_path).await.map_err(String::from)?;
+    let sorted = sorted.unwrap_or(false);
+    run_blocking(move || {
+        reader.find_trace_by_field(&field_key, target, sorted, &scan_progress_emitter(app))
+    })
+    .await
+}
+
+/// Export integer trace header fields for a range of traces as CSV
+///
+/// Writes one row per trace with the `field_keys` columns, under a header row
+/// of the spec field names. Only header bytes are read. Emits
+/// `segy://scan-progress` events while writing.
+#[tauri::command]
+pub async fn export_headers_csv(
+    app: AppHandle,
+    file_path: String,
+    output_path: String,
+    field_keys: Vec<String>,
+    start: usize,
+    count: usize,
+    state: State<'_, SegyReaderState>,
+) -> CommandResult<()> {
+    let reader = state.get_or_open(file_path).await.map_err(String::from)?;
+    run_blocking(move || {
+        reader.export_headers_csv(
+            Path::new(&output_path),
+            &field_keys,
+            start,
+            count,
+            &scan_progress_emitter(app),
+        )
+    })
+    .await
+}
+
+/// Export trace samples as a NumPy `.npy` float32 array
+///
+/// The array is traces × samples, so `np.load(output_path)[i]` is trace
+/// `start + i`. Fails if the traces differ in length. Emits
+/// `segy://scan-progress` events while writing.
+#[tauri::command]
+pub async fn export_samples_npy(
+    app: AppHandle,
+    file_path: String,
+    output_path: String,
+    start: usize,
+    count: usize,
+    state: State<'_, SegyReaderState>,
+) -> CommandResult<()> {
+    let reader = state.get_or_open(file_path).await.map_err(String::from)?;
+    run_blocking(move || {
+        reader.export_samples_npy(
+            Path::new(&output_path),
+            start,
+            count,
+            &scan_progress_emitter(app),
+        )
+    })
+    .await
+}
+
+/// List the trace header fields whose values differ between two traces
+///
+/// Handy for spotting where a sort key changes. Returns
+/// `{ field_key, value_a, value_b }` for each differing integer field.
+#[tauri::command]
+pub async fn diff_trace_headers(
+    file_path: String,
+    index_a: usize,
+    index_b: usize,
+    state: State<'_, SegyReaderState>,
+) -> CommandResult<Vec<FieldDiff>> {
+    let reader = state.get_or_open(file_path).await.map_err(String::from)?;
+    run_blocking(move || reader.diff_trace_headers(index_a, index_b)).await
+}
+
+/// Decode one trace header into labeled fields for the header inspector
+///
+/// Each entry carries the field spec (name, description, byte range), the
+/// integer value (`null` for unsupported types), and the code label when the
+/// spec maps that value. Fields follow the file's revision and any header
+/// overrides.
+#[tauri::command]
+pub async fn get_trace_header_decoded(
+    file_path: String,
+    trace_index: usize,
+    state: State<'_, SegyReaderState>,
+) -> CommandResult<Vec<DecodedField>> {
+    let reader = state.get_or_open(file_path).await.map_err(String::from)?;
+    run_blocking(move || reader.decode_trace_header(trace_index)).await
+}
+
+/// Compute the amplitude spectrum of a single trace
+///
+/// The trace mean is removed before the FFT. Frequencies are derived from
+/// the binary header sample interval.
+#[tauri::command]
+pub async fn compute_spectrum(
+    file_path: String,
+    trace_index: usize,
+    state: State<'_, SegyReaderState>,
+) -> CommandResult<SpectrumResult> {
+    let reader = state.get_or_open(file_path).await.map_err(String::from)?;
+    run_blocking(move || {
+        let traces = reader.load_trace_data_range(trace_index, 1, None, None)?;
+        let interval = reader.binary_header().sample_interval_us;
+        Ok(analysis::amplitude_spectrum(&traces[0], interval))
+    })
+    .await
+}
+
+/// Compute the amplitude spectrum averaged over a range of traces
+#[tauri::command]
+pub async fn compute_average_spectrum(
+    file_path: String,
+    start_index: usize,
+    count: usize,
+    state: State<'_, SegyReaderState>,
+) -> CommandResult<SpectrumResult> {
+    let reader = state.get_or_open(file_path).await.map_err(String::from)?;
+    run_blocking(move || {
+        let traces = reader.load_trace_data_range(start_index, count, None, None)?;
+        let interval = reader.binary_header().sample_interval_us;
+        Ok(analysis::average_spectrum(&traces, interval))
+    })
+    .await
+}
+
+/// Histogram the sample amplitudes of a range of traces
+///
+/// `bins` equal-width bins span the smallest to largest finite sample, so the
+/// UI can pick a clip visually. All-constant data returns a single bin.
+/// Emits `segy://scan-progress` events over both passes through the traces.
+#[tauri::command]
+pub async fn amplitude_histogram(
+    app: AppHandle,
+    file_path: String,
+    start: usize,
+    count: usize,
+    bins: usize,
+    state: State<'_, SegyReaderState>,
+) -> CommandResult<HistogramResult> {
+    let reader = state.get_or_open(file_path).await.map_err(String::from)?;
+    run_blocking(move || {
+        reader.amplitude_histogram(start, count, bins, &scan_progress_emitter(app))
+    })
+    .await
+}
+
+/// Compute receiver spacing statistics over a range of traces
+///
+/// Uses the scaler-corrected `group_x`/`group_y` of each trace, skipping
+/// traces whose coordinates are unset. Distances are in survey units, or in
+/// meters along the great circle when coordinates are arcseconds; the result
+/// reports `coordinate_units` so the map view can pick a geographic or
+/// projected plot.
+#[tauri::command]
+pub async fn compute_trace_spacing(
+    file_path: String,
+    start_index: usize,
+    count: usize,
+    state: State<'_, SegyReaderState>,
+) -> CommandResult<SpacingStats> {
+    let reader = state.get_or_open(file_path).await.map_err(String::from)?;
+    run_blocking(move || {
+        let headers = reader.load_trace_headers_range(start_index, count, 1)?;
+        geometry::trace_spacing(&headers)
+    })
+    .await
+}
+
+/// Compare stored and computed source-to-group offsets over a range of traces
+///
+/// Each entry pairs the `source_to_group_distance` field with the distance
+/// between the scaler-corrected source and group coordinates, for geometry QC.
+#[tauri::command]
+pub async fn compute_trace_offsets(
+    file_path: String,
+    start_index: usize,
+    count: usize,
+    state: State<'_, SegyReaderState>,
+) -> CommandResult<Vec<TraceOffset>> {
+    let reader = state.get_or_open(file_path).await.map_err(String::from)?;
+    run_blocking(move || {
+        let headers = reader.load_trace_headers_range(start_index, count, 1)?;
+        Ok(geometry::trace_offsets(&headers))
+    })
+    .await
+}
+
+/// Close a SEG-Y file, dropping its reader and unmapping the file
+///
+/// Once commands still using the reader finish, the OS can reclaim the
+/// mapping and the file can be deleted (notably on Windows). Closing a file
+/// that is not open succeeds, and a later command on the path reopens it.
+#[tauri::command]
+pub async fn close_segy_file(
+    file_path: String,
+    state: State<'_, SegyReaderState>,
+) -> CommandResult<()> {
+    state.close(&file_path).await;
+    Ok(())
+}
+
+/// Drop all decoded traces cached by every open reader.
+///
+/// Subsequent trace loads re-read and re-decode from the memory map.
+#[tauri::command]
+pub async fn clear_trace_cache(state: State<'_, SegyReaderState>) -> CommandResult<()> {
+    state.clear_trace_cache().await;
+    Ok(())
 }
 
 /// Render Variable Density view from SEG-Y traces
+///
+/// `render_config` carries the viewport, colormap, scaling, render mode, and
+/// every optional processing step (see `RenderConfig`); it is the same
+/// structure `save_render_to_file` takes. `compression` defaults to fast PNG
+/// encoding for interactive renders; pass `best` for exports where output
+/// size matters more than latency. Rendering runs on the blocking thread pool
+/// and can be aborted with `cancel_render`, in which case a `RenderError`
+/// with message `cancelled` is returned.
+///
+/// `skip_aux_traces` leaves out auxiliary traces (assumed to lead each record
+/// per the binary header counts). It and `excluded_trace_ids` both report the
+/// remaining `data_trace_count`.
 #[tauri::command]
 pub async fn render_variable_density(
     file_path: String,
-    viewport: ViewportConfig,
+    render_config: RenderConfig,
+    compression: Option<PngCompression>,
+    skip_aux_traces: Option<bool>,
+    state: State<'_, SegyReaderState>,
+    render_state: State<'_, RenderState>,
+) -> CommandResult<RenderedImage> {
+    let cancel = render_state.token();
+    let reader = state.get_or_open(file_path).await.map_err(String::from)?;
+    let compression = compression.unwrap_or_default();
+    let skip_aux_traces = skip_aux_traces.unwrap_or(false);
+    run_blocking(move || {
+        render_section(
+            &reader,
+            render_config,
+            compression,
+            skip_aux_traces,
+            &cancel,
+        )
+    })
+    .await
+}
+
+/// Render a low-resolution variable density thumbnail of the whole file
+///
+/// Reads only about `width` evenly strided traces, each mean-downsampled to
+/// `height` samples, so previews of multi-GB files stay fast. Amplitudes use
+/// the automatic percentile clip. Unlike `render_variable_density` there is
+/// no viewport, and the render is not cancellable.
+#[tauri::command]
+pub async fn render_overview(
+    file_path: String,
+    width: u32,
+    height: u32,
     colormap_type: ColormapType,
-    scaling: AmplitudeScaling,
-    render_mode: RenderMode,
-    wiggle_config: Option<WiggleConfig>,
     state: State<'_, SegyReaderState>,
 ) -> CommandResult<RenderedImage> {
     let reader = state.get_or_open(file_path).await.map_err(String::from)?;
-    let trace_data = run_blocking(move || {
-        reader.load_trace_data_range(viewport.start_trace, viewport.trace_count, None)
+    run_blocking(move || {
+        let traces = reader.load_overview_traces(width as usize, height as usize)?;
+        let config = RenderConfig::variable_density(
+            ViewportConfig::new(traces.len(), width, height),
+            colormap_type,
+            AmplitudeScaling::Auto,
+        );
+        rendering::render_traces(
+            traces,
+            None,
+            reader.binary_header().sample_interval_us,
+            &config,
+            PngCompression::Fast,
+            &RenderCancelToken::none(),
+        )
     })
-    .await?;
+    .await
+}
 
-    rendering::render_traces(
+/// Write a section's amplitudes to a 16-bit grayscale PNG for analysis
+///
+/// Unlike `save_render_to_file`, no colormap or resizing is applied: every
+/// trace in the viewport's range is one pixel column and every sample in its
+/// sample window one row, so `viewport.width` and `height` are ignored.
+/// Amplitudes are normalized with `scaling`, then mapped from -1..1 onto the
+/// full 0..65535 gray range.
+#[tauri::command]
+pub async fn save_amplitude_png16(
+    file_path: String,
+    output_path: String,
+    viewport: ViewportConfig,
+    scaling: AmplitudeScaling,
+    state: State<'_, SegyReaderState>,
+) -> CommandResult<()> {
+    let reader = state.get_or_open(file_path).await.map_err(String::from)?;
+    run_blocking(move || {
+        let window = viewport.sample_window(usize::from(reader.config().samples_per_trace))?;
+        let scaling = scaling.for_sample_window(window.as_ref().map_or(0, |w| w.start));
+        let traces = reader.load_trace_data_range(
+            viewport.start_trace,
+            viewport.trace_count,
+            None,
+            window,
+        )?;
+        rendering::save_amplitude_png16(&traces, &scaling, Path::new(&output_path))
+    })
+    .await
+}
+
+/// Render a section and write it straight to an image file
+///
+/// The encoder is chosen from the extension of `output_path` (`.png`,
+/// `.jpg`/`.jpeg`, or `.webp`), so large exports never cross the bridge.
+/// Unsupported extensions return a `ValidationError` before rendering.
+#[tauri::command]
+pub async fn save_render_to_file(
+    render_config: RenderConfig,
+    file_path: String,
+    output_path: String,
+    state: State<'_, SegyReaderState>,
+    render_state: State<'_, RenderState>,
+) -> CommandResult<()> {
+    let output_path = PathBuf::from(output_path);
+    rendering::export_format(&output_path).map_err(String::from)?;
+    let cancel = render_state.token();
+    let reader = state.get_or_open(file_path).await.map_err(String::from)?;
+    run_blocking(move || {
+        let image = render_section(&reader, render_config, PngCompression::Best, false, &cancel)?;
+        rendering::save_rendered_image(&image, &output_path)
+    })
+    .await
+}
+
+/// Render a horizontal trace axis strip with tick labels
+///
+/// Ticks are labeled with trace numbers, or with `label_field` values (one
+/// per trace, e.g. CDPs from `extract_header_field`). Returns the image and
+/// the `[label, x]` pixel position of every tick.
+#[tauri::command]
+pub async fn render_trace_axis(
+    width: u32,
+    start_trace: usize,
+    trace_count: usize,
+    label_field: Option<Vec<i64>>,
+) -> CommandResult<(RenderedImage, Vec<(i64, u32)>)> {
+    run_blocking(move || rendering::render_trace_axis(width, start_trace, trace_count, label_field))
+        .await
+}
+
+/// Render a wiggle settings swatch from synthetic Ricker wavelets
+///
+/// Needs no open file, so the wiggle settings panel can preview line and
+/// fill styles before they are applied to real data.
+#[tauri::command]
+pub async fn render_wiggle_preview(
+    wiggle_config: WiggleConfig,
+    width: u32,
+    height: u32,
+) -> CommandResult<RenderedImage> {
+    run_blocking(move || rendering::render_wiggle_preview(&wiggle_config, width, height)).await
+}
+
+/// Cancel every render that started before this call.
+///
+/// In-flight renders stop at their next checkpoint and return a
+/// `RenderError` with message `cancelled`; later renders are unaffected.
+#[tauri::command]
+pub fn cancel_render(render_state: State<'_, RenderState>) {
+    render_state.cancel();
+}
+
+/// Build a progress callback forwarding `ScanProgress` to the frontend as
+/// `SCAN_PROGRESS_EVENT` events.
+fn scan_progress_emitter(app: AppHandle) -> impl Fn(ScanProgress) + Sync {
+    move |progress| {
+        if let Err(e) = app.emit(SCAN_PROGRESS_EVENT, progress) {
+            log::warn!("Failed to emit scan progress: {}", e);
+        }
+    }
+}
+
+/// Load the traces a render needs and run them through the render pipeline.
+///
+/// Shared by `render_variable_density` and `save_render_to_file`.
+fn render_section(
+    reader: &SegyReader,
+    mut config: RenderConfig,
+    compression: PngCompression,
+    skip_aux_traces: bool,
+    cancel: &RenderCancelToken,
+) -> Result<RenderedImage, AppError> {
+    let start = config.viewport.start_trace;
+    let count = config.viewport.trace_count;
+    let window = config
+        .viewport
+        .sample_window(usize::from(reader.config().samples_per_trace))?;
+    let mut trace_data = reader.load_trace_data_range(start, count, None, window)?;
+    let excluded = &config.excluded_trace_ids;
+    let mut headers = if config.gather_gap.is_some()
+        || config.hide_dead_traces
+        || config.align_delays
+        || config.missing_traces.is_some()
+        || !excluded.is_empty()
+    {
+        Some(reader.load_trace_headers_range(start, count, 1)?)
+    } else {
+        None
+    };
+    let filter_traces = skip_aux_traces || !excluded.is_empty();
+    if filter_traces {
+        let keep: Vec<bool> = (0..trace_data.len())
+            .map(|i| {
+                let aux = skip_aux_traces && reader.is_aux_trace(start + i);
+                let excluded_id = headers
+                    .as_ref()
+                    .is_some_and(|headers| excluded.contains(&headers[i].trace_id_code.code()));
+                !aux && !excluded_id
+            })
+            .collect();
+        trace_data = trace_data
+            .into_iter()
+            .zip(&keep)
+            .filter_map(|(trace, &keep)| keep.then_some(trace))
+            .collect();
+        headers = headers.map(|headers| {
+            headers
+                .into_iter()
+                .zip(&keep)
+                .filter_map(|(header, &keep)| keep.then_some(header))
+                .collect()
+        });
+    }
+    let data_trace_count = trace_data.len();
+    // Columns are laid out per rendered trace, so drop the skipped ones.
+    config.viewport.trace_count = data_trace_count;
+    cancel.check()?;
+    let mut image = rendering::render_traces(
         trace_data,
-        &viewport,
-        colormap_type,
-        &scaling,
-        render_mode,
-        wiggle_config,
-    )
+        headers.as_deref(),
+        reader.binary_header().sample_interval_us,
+        &config,
+        compression,
+        cancel,
+    )?;
+    if filter_traces {
+        image.data_trace_count = Some(data_trace_count);
+    }
+    Ok(image)
+}
+
+/// Load the header spec for a file's revision, or Rev 0 when no file is given.
+async fn load_spec_for_file(
+    file_path: Option<String>,
+    state: &SegyReaderState,
+) -> CommandResult<SegyFormatSpec> {
+    match file_path {
+        Some(file_path) => {
+            let reader = state.get_or_open(file_path).await.map_err(String::from)?;
+            SegyFormatSpec::load_for_revision(reader.binary_header().segy_revision)
+        }
+        None => SegyFormatSpec::load_rev0(),
+    }
+    .map_err(AppError::config)
+    .map_err(String::from)
 }
 
 /// Run a blocking SEG-Y task on the dedicated blocking thread pool.
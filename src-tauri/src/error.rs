@@ -41,6 +41,30 @@ pub enum AppError {
     /// SEG-Y specific parsing errors
     #[error("SEG-Y error: {message}")]
     SegyError { message: String },
+
+    /// Image rendering or encoding failed
+    #[error("Render error: {message}")]
+    RenderError { message: String },
+
+    /// Header spec or other configuration could not be loaded
+    #[error("Config error: {message}")]
+    ConfigError { message: String },
+}
+
+impl AppError {
+    /// Build a `RenderError` from a message.
+    pub fn render(message: impl Into<String>) -> Self {
+        AppError::RenderError {
+            message: message.into(),
+        }
+    }
+
+    /// Build a `ConfigError` from a message.
+    pub fn config(message: impl Into<String>) -> Self {
+        AppError::ConfigError {
+            message: message.into(),
+        }
+    }
 }
 
 /// Convert standard IO errors into the app error type.
@@ -85,6 +109,18 @@ mod tests {
         assert!(json.contains(r#""message":"test error"#));
     }
 
+    #[test]
+    fn test_render_and_config_error_serialization() {
+        let json = serde_json::to_string(&AppError::render("encode failed")).unwrap();
+        assert_eq!(json, r#"{"name":"RenderError","message":"encode failed"}"#);
+
+        let json = serde_json::to_string(&AppError::config("bad spec")).unwrap();
+        assert_eq!(json, r#"{"name":"ConfigError","message":"bad spec"}"#);
+
+        let error: AppError = serde_json::from_str(&json).unwrap();
+        assert!(matches!(error, AppError::ConfigError { message } if message == "bad spec"));
+    }
+
     #[test]
     fn test_error_deserialization() {
         let json = r#"{"name":"ParseError","message":"invalid format"}"#;
@@ -25,13 +25,49 @@ pub fn run() {
         .plugin(tauri_plugin_updater::Builder::new().build())
         .plugin(tauri_plugin_process::init())
         .manage(segy::SegyReaderState::new())
+        .manage(segy::rendering::RenderState::new())
         .invoke_handler(tauri::generate_handler![
             commands::load_segy_file,
+            commands::get_trace_count,
+            commands::get_raw_textual_header,
+            commands::get_binary_header_raw,
+            commands::read_bytes,
             commands::get_binary_header_spec,
+            commands::get_content_hash,
+            commands::get_file_summary,
+            commands::suggest_sample_format,
             commands::get_trace_header_spec,
+            commands::set_trace_header_overrides,
+            commands::get_effective_trace_spec,
+            commands::get_custom_header_spec,
+            commands::validate_segy,
+            commands::check_consistency,
             commands::load_single_trace,
             commands::load_trace_range,
-            commands::render_variable_density
+            commands::load_trace_samples_f32,
+            commands::get_trace_samples,
+            commands::load_trace_headers,
+            commands::header_field_range,
+            commands::extract_header_field,
+            commands::find_trace_by_field,
+            commands::diff_trace_headers,
+            commands::get_trace_header_decoded,
+            commands::export_headers_csv,
+            commands::export_samples_npy,
+            commands::compute_spectrum,
+            commands::compute_average_spectrum,
+            commands::amplitude_histogram,
+            commands::compute_trace_spacing,
+            commands::compute_trace_offsets,
+            commands::close_segy_file,
+            commands::clear_trace_cache,
+            commands::render_variable_density,
+            commands::render_overview,
+            commands::save_render_to_file,
+            commands::save_amplitude_png16,
+            commands::render_trace_axis,
+            commands::render_wiggle_preview,
+            commands::cancel_render
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
@@ -0,0 +1,367 @@
+//! Frequency-domain analysis of trace data.
+//!
+//! Spectra are computed with `rustfft` on f32-converted samples. The trace
+//! mean is removed before the transform so the DC bin does not dwarf the
+//! signal, and arbitrary (non-power-of-two) trace lengths are supported.
+//! The same machinery provides the trace envelope (instantaneous amplitude)
+//! and constant phase rotation via an FFT-based Hilbert transform. Band-pass
+//! filtering runs in the time domain with Butterworth biquads. Amplitude
+//! histograms are accumulated trace by trace so large ranges never have to
+//! be held in memory.
+
+use crate::segy::TraceData;
+use rustfft::num_complex::Complex;
+use rustfft::FftPlanner;
+use serde::{Deserialize, Serialize};
+
+/// Single-sided amplitude spectrum.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SpectrumResult {
+    /// Bin center frequencies in Hz, from 0 up to Nyquist.
+    pub freqs_hz: Vec<f32>,
+    /// Amplitude per bin, scaled so a unit sine has magnitude 1.
+    pub magnitude: Vec<f32>,
+}
+
+/// Amplitude histogram with equal-width bins.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HistogramResult {
+    /// Bin edges from the smallest to the largest sample; one more than `counts`.
+    pub edges: Vec<f32>,
+    /// Number of finite samples in each bin.
+    pub counts: Vec<u64>,
+}
+
+impl HistogramResult {
+    /// Create empty bins spanning `min..=max`.
+    ///
+    /// A zero-width range (e.g. all-zero data) gets a single bin regardless
+    /// of `bins`.
+    pub fn with_range(min: f32, max: f32, bins: usize) -> Self {
+        let bins = if max > min { bins.max(1) } else { 1 };
+        let width = (f64::from(max) - f64::from(min)) / bins as f64;
+        let mut edges: Vec<f32> = (0..bins)
+            .map(|bin| (f64::from(min) + bin as f64 * width) as f32)
+            .collect();
+        edges.push(max);
+        Self {
+            edges,
+            counts: vec![0; bins],
+        }
+    }
+
+    /// Count finite samples; values outside the range land in the end bins.
+    pub fn add(&mut self, samples: &[f32]) {
+        let bins = self.counts.len();
+        let min = f64::from(self.edges[0]);
+        let span = f64::from(self.edges[bins]) - min;
+        for &value in samples.iter().filter(|v| v.is_finite()) {
+            let bin = if span > 0.0 {
+                ((f64::from(value) - min) / span * bins as f64).max(0.0) as usize
+            } else {
+                0
+            };
+            self.counts[bin.min(bins - 1)] += 1;
+        }
+    }
+}
+
+/// Compute the single-sided amplitude spectrum of one trace.
+///
+/// Returns an empty result for empty traces or a non-positive sample interval.
+pub fn amplitude_spectrum(data: &TraceData, sample_interval_us: i16) -> SpectrumResult {
+    average_spectrum(std::slice::from_ref(data), sample_interval_us)
+}
+
+/// Average the amplitude spectra of several traces.
+///
+/// Traces shorter than the longest one are zero-padded (after mean removal)
+/// so every spectrum shares the same frequency bins.
+pub fn average_spectrum(traces: &[TraceData], sample_interval_us: i16) -> SpectrumResult {
+    let fft_len = traces.iter().map(TraceData::len).max().unwrap_or(0);
+    if fft_len == 0 || sample_interval_us <= 0 || traces.is_empty() {
+        return SpectrumResult::default();
+    }
+
+    let bins = fft_len / 2 + 1;
+    let fft = FftPlanner::<f32>::new().plan_fft_forward(fft_len);
+    let mut magnitude = vec![0.0f32; bins];
+    let mut buffer = Vec::with_capacity(fft_len);
+
+    for trace in traces {
+        let samples = trace.to_f32_vec();
+        let mean = if samples.is_empty() {
+            0.0
+        } else {
+            samples.iter().sum::<f32>() / samples.len() as f32
+        };
+
+        buffer.clear();
+        buffer.extend(samples.iter().map(|&v| Complex::new(v - mean, 0.0)));
+        buffer.resize(fft_len, Complex::new(0.0, 0.0));
+        fft.process(&mut buffer);
+
+        for (bin, value) in buffer[..bins].iter().enumerate() {
+            magnitude[bin] += value.norm() * bin_scale(bin, fft_len);
+        }
+    }
+
+    let trace_count = traces.len() as f32;
+    magnitude.iter_mut().for_each(|m| *m /= trace_count);
+
+    let sample_interval_s = sample_interval_us as f32 * 1e-6;
+    let bin_width_hz = 1.0 / (fft_len as f32 * sample_interval_s);
+    let freqs_hz = (0..bins).map(|bin| bin as f32 * bin_width_hz).collect();
+
+    SpectrumResult {
+        freqs_hz,
+        magnitude,
+    }
+}
+
+/// Compute the trace envelope (instantaneous amplitude).
+///
+/// Builds the analytic signal with an FFT-based Hilbert transform and returns
+/// its magnitude. The envelope is always non-negative, so a diverging
+/// colormap such as seismic only uses its positive half.
+pub fn envelope(samples: &[f32]) -> Vec<f32> {
+    analytic_signal(samples)
+        .iter()
+        .map(|value| value.norm())
+        .collect()
+}
+
+/// Rotate the phase of every frequency component by `degrees`.
+///
+/// Computes `cos(θ)·x − sin(θ)·hilbert(x)` from the analytic signal used by
+/// `envelope`. A 90° rotation turns a zero-phase wavelet into a quadrature
+/// one, and 180° reverses polarity.
+pub fn phase_rotate(samples: &[f32], degrees: f32) -> Vec<f32> {
+    let (sin, cos) = degrees.to_radians().sin_cos();
+    analytic_signal(samples)
+        .iter()
+        .map(|value| cos * value.re - sin * value.im)
+        .collect()
+}
+
+/// Analytic signal `x + i·hilbert(x)` of a trace, one value per sample.
+///
+/// The input is zero-padded to twice its length so circular wrap-around does
+/// not bleed the trace end into its start.
+fn analytic_signal(samples: &[f32]) -> Vec<Complex<f32>> {
+    let len = samples.len();
+    if len == 0 {
+        return Vec::new();
+    }
+
+    let fft_len = len * 2;
+    let mut planner = FftPlanner::<f32>::new();
+    let forward = planner.plan_fft_forward(fft_len);
+    let inverse = planner.plan_fft_inverse(fft_len);
+
+    let mut buffer: Vec<Complex<f32>> = samples.iter().map(|&v| Complex::new(v, 0.0)).collect();
+    buffer.resize(fft_len, Complex::new(0.0, 0.0));
+    forward.process(&mut buffer);
+
+    // Analytic signal: keep DC and Nyquist, double positive frequencies,
+    // zero negative frequencies. `fft_len` is always even here.
+    let half = fft_len / 2;
+    for value in &mut buffer[1..half] {
+        *value *= 2.0;
+    }
+    for value in &mut buffer[half + 1..] {
+        *value = Complex::new(0.0, 0.0);
+    }
+
+    inverse.process(&mut buffer);
+    buffer.truncate(len);
+    let scale = 1.0 / fft_len as f32;
+    for value in &mut buffer {
+        *value *= scale;
+    }
+    buffer
+}
+
+/// Apply a zero-phase Butterworth band-pass filter.
+///
+/// A second-order high-pass at `low_hz` and low-pass at `high_hz` are run
+/// forward and then backward, cancelling phase shift and doubling the
+/// effective order. A corner at or below 0 Hz skips the high-pass, and a
+/// corner at or above Nyquist skips the low-pass. Returns the input unchanged
+/// for a non-positive sample interval.
+pub fn bandpass(samples: &[f32], sample_interval_us: i16, low_hz: f32, high_hz: f32) -> Vec<f32> {
+    if sample_interval_us <= 0 {
+        return samples.to_vec();
+    }
+
+    let sample_rate = 1e6 / sample_interval_us as f64;
+    let nyquist = sample_rate / 2.0;
+    let mut stages = Vec::with_capacity(2);
+    if low_hz > 0.0 && (low_hz as f64) < nyquist {
+        stages.push(Biquad::highpass(low_hz as f64, sample_rate));
+    }
+    if high_hz > 0.0 && (high_hz as f64) < nyquist {
+        stages.push(Biquad::lowpass(high_hz as f64, sample_rate));
+    }
+
+    let mut signal: Vec<f64> = samples.iter().map(|&v| v as f64).collect();
+    for stage in &stages {
+        stage.filter(&mut signal);
+        signal.reverse();
+        stage.filter(&mut signal);
+        signal.reverse();
+    }
+    signal.into_iter().map(|v| v as f32).collect()
+}
+
+/// Second-order IIR section with normalized coefficients (`a0 == 1`).
+struct Biquad {
+    b: [f64; 3],
+    a: [f64; 2],
+}
+
+impl Biquad {
+    /// Butterworth quality factor for a second-order section.
+    const Q: f64 = std::f64::consts::FRAC_1_SQRT_2;
+
+    fn lowpass(corner_hz: f64, sample_rate: f64) -> Self {
+        let (cos_w0, alpha) = Self::prewarp(corner_hz, sample_rate);
+        Self::normalized(
+            [(1.0 - cos_w0) / 2.0, 1.0 - cos_w0, (1.0 - cos_w0) / 2.0],
+            [1.0 + alpha, -2.0 * cos_w0, 1.0 - alpha],
+        )
+    }
+
+    fn highpass(corner_hz: f64, sample_rate: f64) -> Self {
+        let (cos_w0, alpha) = Self::prewarp(corner_hz, sample_rate);
+        Self::normalized(
+            [(1.0 + cos_w0) / 2.0, -(1.0 + cos_w0), (1.0 + cos_w0) / 2.0],
+            [1.0 + alpha, -2.0 * cos_w0, 1.0 - alpha],
+        )
+    }
+
+    fn prewarp(corner_hz: f64, sample_rate: f64) -> (f64, f64) {
+        let w0 = 2.0 * std::f64::consts::PI * corner_hz / sample_rate;
+        (w0.cos(), w0.sin() / (2.0 * Self::Q))
+    }
+
+    fn normalized(b: [f64; 3], a: [f64; 3]) -> Self {
+        Self {
+            b: [b[0] / a[0], b[1] / a[0], b[2] / a[0]],
+            a: [a[1] / a[0], a[2] / a[0]],
+        }
+    }
+
+    /// Filter in place (direct form I).
+    fn filter(&self, signal: &mut [f64]) {
+        let (mut x1, mut x2, mut y1, mut y2) = (0.0, 0.0, 0.0, 0.0);
+        for value in signal.iter_mut() {
+            let x0 = *value;
+            let y0 =
+                self.b[0] * x0 + self.b[1] * x1 + self.b[2] * x2 - self.a[0] * y1 - self.a[1] * y2;
+            x2 = x1;
+            x1 = x0;
+            y2 = y1;
+            y1 = y0;
+            *value = y0;
+        }
+    }
+}
+
+/// Scale factor converting an FFT bin magnitude to single-sided amplitude.
+///
+/// DC and (for even lengths) Nyquist appear once in the full spectrum; every
+/// other bin's energy is split between positive and negative frequencies.
+fn bin_scale(bin: usize, fft_len: usize) -> f32 {
+    let is_nyquist = fft_len % 2 == 0 && bin == fft_len / 2;
+    if bin == 0 || is_nyquist {
+        1.0 / fft_len as f32
+    } else {
+        2.0 / fft_len as f32
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_histogram_bins_and_degenerate_range() {
+        let mut histogram = HistogramResult::with_range(-1.0, 1.0, 4);
+        histogram.add(&[-1.0, -0.6, -0.1, 0.0, 0.4, 1.0, f32::NAN]);
+        assert_eq!(histogram.edges, vec![-1.0, -0.5, 0.0, 0.5, 1.0]);
+        assert_eq!(histogram.counts, vec![2, 1, 2, 1]);
+
+        let mut zeros = HistogramResult::with_range(0.0, 0.0, 16);
+        zeros.add(&[0.0; 5]);
+        assert_eq!(zeros.edges, vec![0.0, 0.0]);
+        assert_eq!(zeros.counts, vec![5]);
+    }
+
+    #[test]
+    fn test_envelope_of_sine_is_flat() {
+        let samples: Vec<f32> = (0..200)
+            .map(|i| 2.0 * (2.0 * std::f32::consts::PI * i as f32 / 20.0).sin())
+            .collect();
+        let env = envelope(&samples);
+
+        assert_eq!(env.len(), samples.len());
+        assert!(env.iter().all(|&v| v >= 0.0));
+        // Away from the edges the envelope tracks the sine amplitude.
+        assert!(env[50..150].iter().all(|&v| (v - 2.0).abs() < 0.1));
+    }
+
+    #[test]
+    fn test_phase_rotation_by_180_degrees_reverses_polarity() {
+        let samples = crate::segy::synthetic::ricker_trace(25.0, 4000, 101).to_f32_vec();
+        let reversed = phase_rotate(&samples, 180.0);
+        let unchanged = phase_rotate(&samples, 0.0);
+
+        assert_eq!(reversed.len(), samples.len());
+        for ((&x, &r), &u) in samples.iter().zip(&reversed).zip(&unchanged) {
+            assert!((r + x).abs() < 1e-5, "{} vs {}", r, -x);
+            assert!((u - x).abs() < 1e-5);
+        }
+    }
+
+    #[test]
+    fn test_bandpass_attenuates_out_of_band_tone() {
+        // 10 Hz and 100 Hz tones at 2 ms sampling; pass 5-30 Hz.
+        let samples: Vec<f32> = (0..1000)
+            .map(|i| {
+                let t = i as f32 * 0.002;
+                (2.0 * std::f32::consts::PI * 10.0 * t).sin()
+                    + (2.0 * std::f32::consts::PI * 100.0 * t).sin()
+            })
+            .collect();
+        let filtered = bandpass(&samples, 2000, 5.0, 30.0);
+        let spectrum = amplitude_spectrum(&TraceData::IeeeFloat32(filtered), 2000);
+
+        // Bins are 0.5 Hz wide: 10 Hz is bin 20, 100 Hz is bin 200.
+        assert!(spectrum.magnitude[20] > 0.8);
+        assert!(spectrum.magnitude[200] < 0.05);
+    }
+
+    #[test]
+    fn test_sine_peak_at_expected_frequency() {
+        // 50 Hz sine sampled at 2 ms over 250 samples (non-power-of-two), with a DC offset.
+        let samples: Vec<f32> = (0..250)
+            .map(|i| 3.0 + (2.0 * std::f32::consts::PI * 50.0 * i as f32 * 0.002).sin())
+            .collect();
+        let spectrum = amplitude_spectrum(&TraceData::IeeeFloat32(samples), 2000);
+
+        assert_eq!(spectrum.freqs_hz.len(), 126);
+        assert!((spectrum.freqs_hz[125] - 250.0).abs() < 1e-3);
+
+        let peak = spectrum
+            .magnitude
+            .iter()
+            .enumerate()
+            .max_by(|a, b| a.1.total_cmp(b.1))
+            .map(|(bin, _)| bin)
+            .unwrap();
+        assert!((spectrum.freqs_hz[peak] - 50.0).abs() < 1e-3);
+        assert!((spectrum.magnitude[peak] - 1.0).abs() < 0.01);
+        assert!(spectrum.magnitude[0] < 1e-3);
+    }
+}
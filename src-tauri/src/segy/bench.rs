@@ -0,0 +1,83 @@
+//! Measurement helpers for the ignored benchmark tests.
+//!
+//! Benchmarks are `#[ignore]`d unit tests next to the code they measure, so
+//! they can reach private functions. Run them in release mode, one at a
+//! time so allocations from other tests do not skew peak memory:
+//!
+//! ```text
+//! cargo test --release -- --ignored --test-threads=1 --nocapture
+//! ```
+//!
+//! Every test binary allocates through `CountingAllocator`, which forwards to
+//! the system allocator and keeps live and peak byte counts. Test-only;
+//! nothing here is shipped.
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// System allocator that tracks live and peak heap bytes.
+pub struct CountingAllocator {
+    live: AtomicUsize,
+    peak: AtomicUsize,
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator {
+    live: AtomicUsize::new(0),
+    peak: AtomicUsize::new(0),
+};
+
+// SAFETY: every call is forwarded unchanged to `System`; only counters are added.
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc(layout);
+        if !ptr.is_null() {
+            self.grow(layout.size());
+        }
+        ptr
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc_zeroed(layout);
+        if !ptr.is_null() {
+            self.grow(layout.size());
+        }
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout);
+        self.live.fetch_sub(layout.size(), Ordering::Relaxed);
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let new_ptr = System.realloc(ptr, layout, new_size);
+        if !new_ptr.is_null() {
+            self.live.fetch_sub(layout.size(), Ordering::Relaxed);
+            self.grow(new_size);
+        }
+        new_ptr
+    }
+}
+
+impl CountingAllocator {
+    fn grow(&self, bytes: usize) {
+        let live = self.live.fetch_add(bytes, Ordering::Relaxed) + bytes;
+        self.peak.fetch_max(live, Ordering::Relaxed);
+    }
+}
+
+/// Run `f` and return its result with the most heap it held at once, in
+/// bytes above what was live when it started.
+pub fn peak_bytes<T>(f: impl FnOnce() -> T) -> (T, usize) {
+    let baseline = ALLOCATOR.live.load(Ordering::Relaxed);
+    ALLOCATOR.peak.store(baseline, Ordering::Relaxed);
+    let result = f();
+    let peak = ALLOCATOR.peak.load(Ordering::Relaxed);
+    (result, peak.saturating_sub(baseline))
+}
+
+/// Format a byte count in MiB for benchmark output.
+pub fn mib(bytes: usize) -> String {
+    format!("{:.1} MiB", bytes as f64 / (1024.0 * 1024.0))
+}
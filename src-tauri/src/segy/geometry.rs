@@ -0,0 +1,270 @@
+//! Survey geometry derived from trace header coordinates.
+//!
+//! Coordinates are stored as integers with a shared scaler (bytes 71-72):
+//! positive values multiply, negative values divide, and zero means no
+//! scaling. Spacing statistics feed the distance annotation of the trace axis.
+//!
+//! Files with `SecondsOfArc` units store longitude/latitude in arcseconds
+//! rather than lengths, so their distances are measured along the great
+//! circle and reported in meters.
+
+use crate::error::AppError;
+use crate::segy::{CoordinateUnits, TraceHeader};
+use serde::{Deserialize, Serialize};
+
+/// Mean Earth radius in meters, used for great-circle distances.
+const EARTH_RADIUS_M: f64 = 6_371_008.8;
+
+/// Distance between consecutive traces, in survey units.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct SpacingStats {
+    /// Median spacing, robust to gaps and repeated positions.
+    pub median: f64,
+    /// Mean spacing.
+    pub mean: f64,
+    /// Smallest spacing.
+    pub min: f64,
+    /// Largest spacing.
+    pub max: f64,
+    /// Units of the header coordinates. Spacings are survey lengths for
+    /// `Length`/`Unknown`, and meters for `SecondsOfArc`.
+    pub coordinate_units: CoordinateUnits,
+}
+
+/// Stored and coordinate-derived source-to-group offset of one trace.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct TraceOffset {
+    /// The `source_to_group_distance` header field as recorded.
+    pub stored: i32,
+    /// `computed_offset` of the trace; `None` when all coordinates are zero.
+    pub computed: Option<f64>,
+}
+
+/// Apply a SEG-Y coordinate scaler to a raw coordinate value.
+pub fn apply_coordinate_scaler(value: i32, scaler: i16) -> f64 {
+    match scaler {
+        0 => value as f64,
+        s if s > 0 => value as f64 * s as f64,
+        s => value as f64 / -(s as f64),
+    }
+}
+
+/// Convert scaled arcseconds to decimal degrees.
+pub fn arcseconds_to_degrees(arcseconds: f64) -> f64 {
+    arcseconds / 3600.0
+}
+
+/// Great-circle distance in meters between two `(longitude, latitude)`
+/// positions given in degrees.
+fn great_circle_distance(a: (f64, f64), b: (f64, f64)) -> f64 {
+    let (lon1, lat1) = (a.0.to_radians(), a.1.to_radians());
+    let (lon2, lat2) = (b.0.to_radians(), b.1.to_radians());
+    let h = ((lat2 - lat1) / 2.0).sin().powi(2)
+        + lat1.cos() * lat2.cos() * ((lon2 - lon1) / 2.0).sin().powi(2);
+    2.0 * EARTH_RADIUS_M * h.sqrt().min(1.0).asin()
+}
+
+/// Distance between two scaled positions: planar in survey units, or great
+/// circle meters when the positions are arcseconds.
+pub fn position_distance(a: (f64, f64), b: (f64, f64), units: CoordinateUnits) -> f64 {
+    match units {
+        CoordinateUnits::SecondsOfArc => great_circle_distance(
+            (arcseconds_to_degrees(a.0), arcseconds_to_degrees(a.1)),
+            (arcseconds_to_degrees(b.0), arcseconds_to_degrees(b.1)),
+        ),
+        CoordinateUnits::Length | CoordinateUnits::Unknown => (b.0 - a.0).hypot(b.1 - a.1),
+    }
+}
+
+/// Scaled source position, or `None` when both coordinates are zero.
+pub fn source_position(header: &TraceHeader) -> Option<(f64, f64)> {
+    if header.source_x == 0 && header.source_y == 0 {
+        return None;
+    }
+    Some((
+        apply_coordinate_scaler(header.source_x, header.coordinate_scaler),
+        apply_coordinate_scaler(header.source_y, header.coordinate_scaler),
+    ))
+}
+
+/// Scaled receiver group position, or `None` when both coordinates are zero.
+pub fn group_position(header: &TraceHeader) -> Option<(f64, f64)> {
+    if header.group_x == 0 && header.group_y == 0 {
+        return None;
+    }
+    Some((
+        apply_coordinate_scaler(header.group_x, header.coordinate_scaler),
+        apply_coordinate_scaler(header.group_y, header.coordinate_scaler),
+    ))
+}
+
+/// Source position of `header` in decimal degrees as `(longitude, latitude)`.
+///
+/// Only `SecondsOfArc` files store geographic coordinates; their
+/// scaler-corrected X/Y arcseconds are divided by 3600. Returns `None` for
+/// length or unknown units, whose coordinates are projected.
+pub fn geographic_coords(header: &TraceHeader) -> Option<(f64, f64)> {
+    if header.coordinate_units != CoordinateUnits::SecondsOfArc {
+        return None;
+    }
+    let degrees =
+        |value| arcseconds_to_degrees(apply_coordinate_scaler(value, header.coordinate_scaler));
+    Some((degrees(header.source_x), degrees(header.source_y)))
+}
+
+/// Source-to-group distance computed from the coordinates of `header`.
+///
+/// Uses the scaler-corrected source and group positions when both are set
+/// (great-circle meters for arcsecond coordinates), and falls back to the
+/// stored `source_to_group_distance` when only one is. Returns `None` when
+/// all four coordinates are zero.
+pub fn computed_offset(header: &TraceHeader) -> Option<f64> {
+    match (source_position(header), group_position(header)) {
+        (Some(source), Some(group)) => {
+            Some(position_distance(source, group, header.coordinate_units))
+        }
+        (None, None) => None,
+        _ => Some(header.source_to_group_distance as f64),
+    }
+}
+
+/// Summarize distances between consecutive receiver group positions.
+///
+/// Traces without coordinates are skipped, so distances are measured between
+/// neighbouring traces that have them. Fails when fewer than two traces do.
+/// The units of the first positioned trace decide how distances are measured.
+pub fn trace_spacing(headers: &[TraceHeader]) -> Result<SpacingStats, AppError> {
+    let positioned: Vec<_> = headers
+        .iter()
+        .filter_map(|header| Some((header.coordinate_units, group_position(header)?)))
+        .collect();
+    let coordinate_units = positioned
+        .first()
+        .map_or(CoordinateUnits::Unknown, |&(units, _)| units);
+    let mut distances: Vec<f64> = positioned
+        .windows(2)
+        .map(|pair| position_distance(pair[0].1, pair[1].1, coordinate_units))
+        .collect();
+    if distances.is_empty() {
+        return Err(AppError::ValidationError {
+            message: "Trace spacing needs at least two traces with group coordinates".to_string(),
+        });
+    }
+
+    distances.sort_by(f64::total_cmp);
+    let count = distances.len();
+    let median = if count % 2 == 0 {
+        (distances[count / 2 - 1] + distances[count / 2]) / 2.0
+    } else {
+        distances[count / 2]
+    };
+
+    Ok(SpacingStats {
+        median,
+        mean: distances.iter().sum::<f64>() / count as f64,
+        min: distances[0],
+        max: distances[count - 1],
+        coordinate_units,
+    })
+}
+
+/// Stored and computed offset of every trace, in order, so geometry QC can
+/// flag traces whose offset field disagrees with their coordinates.
+pub fn trace_offsets(headers: &[TraceHeader]) -> Vec<TraceOffset> {
+    headers
+        .iter()
+        .map(|header| TraceOffset {
+            stored: header.source_to_group_distance,
+            computed: computed_offset(header),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::segy::synthetic::make_trace_header;
+
+    fn header_at(x: i32, y: i32, scaler: i16) -> TraceHeader {
+        header_in_units(x, y, scaler, CoordinateUnits::Length)
+    }
+
+    fn header_in_units(x: i32, y: i32, scaler: i16, units: CoordinateUnits) -> TraceHeader {
+        make_trace_header(|bytes| {
+            bytes[70..72].copy_from_slice(&scaler.to_be_bytes());
+            // Source and group coordinates share the same position.
+            for offset in [72, 80] {
+                bytes[offset..offset + 4].copy_from_slice(&x.to_be_bytes());
+                bytes[offset + 4..offset + 8].copy_from_slice(&y.to_be_bytes());
+            }
+            bytes[88..90].copy_from_slice(&(units as i16).to_be_bytes());
+        })
+    }
+
+    #[test]
+    fn test_spacing_applies_scaler_and_skips_unset_coordinates() {
+        // Centimetres with a -100 scaler: 25 m, (unset), 25 m, then 30 m diagonally.
+        let headers = vec![
+            header_at(100_000, 0, -100),
+            header_at(102_500, 0, -100),
+            header_at(0, 0, -100),
+            header_at(105_000, 0, -100),
+            header_at(106_800, 2_400, -100),
+        ];
+        let stats = trace_spacing(&headers).unwrap();
+
+        assert!((stats.median - 25.0).abs() < 1e-9);
+        assert!((stats.min - 25.0).abs() < 1e-9);
+        assert!((stats.max - 30.0).abs() < 1e-9);
+        assert!((stats.mean - 80.0 / 3.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_arcsecond_coordinates_use_great_circle_meters() {
+        // Longitude -95.5 deg, latitude 0 deg, then 1 arcsecond north (scaler /10).
+        let first = header_in_units(-3_438_000, 0, -10, CoordinateUnits::SecondsOfArc);
+        let second = header_in_units(-3_438_000, 10, -10, CoordinateUnits::SecondsOfArc);
+
+        let (longitude, latitude) = geographic_coords(&second).unwrap();
+        assert!((longitude + 95.5).abs() < 1e-9);
+        assert!((latitude - 1.0 / 3600.0).abs() < 1e-12);
+        assert_eq!(geographic_coords(&header_at(10, 10, 1)), None);
+
+        let stats = trace_spacing(&[first, second]).unwrap();
+        assert_eq!(stats.coordinate_units, CoordinateUnits::SecondsOfArc);
+        assert!((stats.median - 30.887).abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_computed_offset_prefers_coordinates() {
+        let mut header = header_at(1_000, 2_000, -10);
+        header.source_to_group_distance = 75;
+        // Source and group coincide in the fixture.
+        assert_eq!(computed_offset(&header), Some(0.0));
+
+        header.group_x = 1_300;
+        header.group_y = 2_400;
+        assert!((computed_offset(&header).unwrap() - 50.0).abs() < 1e-9);
+
+        header.source_x = 0;
+        header.source_y = 0;
+        assert_eq!(computed_offset(&header), Some(75.0));
+
+        header.group_x = 0;
+        header.group_y = 0;
+        assert_eq!(computed_offset(&header), None);
+        assert_eq!(
+            trace_offsets(&[header]),
+            vec![TraceOffset {
+                stored: 75,
+                computed: None
+            }]
+        );
+    }
+
+    #[test]
+    fn test_spacing_requires_two_positioned_traces() {
+        let headers = vec![header_at(10, 10, 1), header_at(0, 0, 1)];
+        assert!(trace_spacing(&headers).is_err());
+    }
+}
@@ -3,9 +3,15 @@
 //! Loads canonical header definitions from a JSON spec file and exposes them
 //! for the frontend to render field metadata. Keeping this in data makes it
 //! easy to update or extend to Rev 1 or custom formats without code changes.
+//!
+//! Later revisions are stored as definitions that `extends` an earlier bundled
+//! spec and list only the fields they add or redefine.
 
+use crate::segy::ByteOrder;
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
 use std::collections::HashMap;
+use std::sync::OnceLock;
 
 /// Header field specification metadata used by the UI and validators.
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -30,8 +36,144 @@ pub struct HeaderFieldSpec {
     pub code_mapping: Option<HashMap<String, String>>,
 }
 
+impl HeaderFieldSpec {
+    /// Look up the human-friendly label for a coded value.
+    ///
+    /// Returns `None` when the field has no code mapping or the code is unknown.
+    pub fn label_for_code(&self, code: i64) -> Option<&str> {
+        self.code_mapping
+            .as_ref()?
+            .get(&code.to_string())
+            .map(String::as_str)
+    }
+
+    /// Decode this field's integer value from raw header bytes.
+    ///
+    /// `base_offset` is the 1-based byte position of `bytes[0]` in the same
+    /// coordinate system as `byte_start` (3201 for a binary header slice, 1 for
+    /// a trace header slice). Returns `None` for unsupported data types or
+    /// ranges that fall outside `bytes`.
+    pub fn read_value(
+        &self,
+        bytes: &[u8],
+        base_offset: usize,
+        byte_order: ByteOrder,
+    ) -> Option<i64> {
+        let start = usize::from(self.byte_start).checked_sub(base_offset)?;
+        let end = usize::from(self.byte_end).checked_sub(base_offset)? + 1;
+        let raw = bytes.get(start..end)?;
+
+        let value = match (self.data_type.as_str(), byte_order) {
+            ("int16", ByteOrder::BigEndian) => i16::from_be_bytes(raw.try_into().ok()?) as i64,
+            ("int16", ByteOrder::LittleEndian) => i16::from_le_bytes(raw.try_into().ok()?) as i64,
+            ("uint16", ByteOrder::BigEndian) => u16::from_be_bytes(raw.try_into().ok()?) as i64,
+            ("uint16", ByteOrder::LittleEndian) => u16::from_le_bytes(raw.try_into().ok()?) as i64,
+            ("int32", ByteOrder::BigEndian) => i32::from_be_bytes(raw.try_into().ok()?) as i64,
+            ("int32", ByteOrder::LittleEndian) => i32::from_le_bytes(raw.try_into().ok()?) as i64,
+            _ => return None,
+        };
+        Some(value)
+    }
+}
+
+/// Build display strings for coded header values.
+///
+/// For every field with a code mapping whose value in `values` is an integer,
+/// produces `"<code> (<label>)"`, or just `"<code>"` when the code is not in
+/// the mapping. Fields without a mapping or non-integer values are skipped.
+pub fn resolve_header_labels(
+    values: &HashMap<String, Value>,
+    fields: &[HeaderFieldSpec],
+) -> HashMap<String, String> {
+    fields
+        .iter()
+        .filter(|field| field.code_mapping.is_some())
+        .filter_map(|field| {
+            let code = values.get(&field.field_key)?.as_i64()?;
+            let display = match field.label_for_code(code) {
+                Some(label) => format!("{} ({})", code, label),
+                None => code.to_string(),
+            };
+            Some((field.field_key.clone(), display))
+        })
+        .collect()
+}
+
+/// Integer header field whose value differs between two headers.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FieldDiff {
+    /// Stable key of the differing field.
+    pub field_key: String,
+    /// Value in the first header.
+    pub value_a: i64,
+    /// Value in the second header.
+    pub value_b: i64,
+}
+
+/// List the integer fields whose values differ between two raw headers.
+///
+/// Both slices are decoded with `HeaderFieldSpec::read_value` using the same
+/// `base_offset`; fields it cannot decode are skipped. Results follow the
+/// order of `fields`.
+pub fn diff_header_fields(
+    a: &[u8],
+    b: &[u8],
+    base_offset: usize,
+    byte_order: ByteOrder,
+    fields: &[HeaderFieldSpec],
+) -> Vec<FieldDiff> {
+    fields
+        .iter()
+        .filter_map(|field| {
+            let value_a = field.read_value(a, base_offset, byte_order)?;
+            let value_b = field.read_value(b, base_offset, byte_order)?;
+            (value_a != value_b).then(|| FieldDiff {
+                field_key: field.field_key.clone(),
+                value_a,
+                value_b,
+            })
+        })
+        .collect()
+}
+
+/// One header field decoded from raw bytes, with its spec and code label.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DecodedField {
+    /// Spec the value was decoded with.
+    pub spec: HeaderFieldSpec,
+    /// Integer value, or `null` when the data type is not supported.
+    pub value: Value,
+    /// Label from the field's code mapping, when it has one for `value`.
+    pub label: Option<String>,
+}
+
+/// Decode every field of a raw header, in `fields` order.
+///
+/// Uses `HeaderFieldSpec::read_value` with the same `base_offset` convention;
+/// fields it cannot decode are kept with a `null` value.
+pub fn decode_header_fields(
+    bytes: &[u8],
+    base_offset: usize,
+    byte_order: ByteOrder,
+    fields: &[HeaderFieldSpec],
+) -> Vec<DecodedField> {
+    fields
+        .iter()
+        .map(|field| {
+            let value = field.read_value(bytes, base_offset, byte_order);
+            DecodedField {
+                spec: field.clone(),
+                value: value.map_or(Value::Null, Value::from),
+                label: value
+                    .and_then(|code| field.label_for_code(code))
+                    .map(str::to_string),
+            }
+        })
+        .collect()
+}
+
 /// Binary header specification block loaded from the JSON spec.
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct BinaryHeaderSpec {
     /// Total size of the header in bytes.
     pub size: usize,
@@ -42,7 +184,7 @@ pub struct BinaryHeaderSpec {
 }
 
 /// Trace header specification block loaded from the JSON spec.
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TraceHeaderSpec {
     /// Total size of the header in bytes.
     pub size: usize,
@@ -51,7 +193,7 @@ pub struct TraceHeaderSpec {
 }
 
 /// Complete SEG-Y format specification.
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SegyFormatSpec {
     /// Version string provided by the spec file.
     pub version: String,
@@ -63,11 +205,258 @@ pub struct SegyFormatSpec {
     pub trace_header: TraceHeaderSpec,
 }
 
+/// Embedded spec JSON and its materialized form, parsed on first use.
+struct BundledSpec {
+    name: &'static str,
+    json: &'static str,
+    parsed: OnceLock<Result<SegyFormatSpec, String>>,
+}
+
+impl BundledSpec {
+    const fn new(name: &'static str, json: &'static str) -> Self {
+        Self {
+            name,
+            json,
+            parsed: OnceLock::new(),
+        }
+    }
+}
+
+/// Bundled spec definitions keyed by the name used in `extends`.
+static BUNDLED_SPECS: [BundledSpec; 4] = [
+    BundledSpec::new("rev0", include_str!("../../config/segy_rev0_spec.json")),
+    BundledSpec::new("rev1", include_str!("../../config/segy_rev1_spec.json")),
+    BundledSpec::new("rev2", include_str!("../../config/segy_rev2_spec.json")),
+    BundledSpec::new("rev21", include_str!("../../config/segy_rev21_spec.json")),
+];
+
+/// Spec definition as stored in JSON, optionally extending another spec.
+#[derive(Debug, Clone, Deserialize)]
+struct SegyFormatDefinition {
+    version: String,
+    reference: String,
+    /// Name of a bundled spec whose fields this definition builds on.
+    #[serde(default)]
+    extends: Option<String>,
+    #[serde(default)]
+    binary_header: Option<HeaderBlockDefinition>,
+    #[serde(default)]
+    trace_header: Option<HeaderBlockDefinition>,
+}
+
+/// Header block definition; sizes may be omitted when extending a base spec.
+#[derive(Debug, Clone, Default, Deserialize)]
+struct HeaderBlockDefinition {
+    size: Option<usize>,
+    byte_offset: Option<usize>,
+    /// Fields to add, replacing any base field with the same `field_key`.
+    #[serde(default)]
+    fields: Vec<HeaderFieldSpec>,
+    /// Partial updates applied to existing fields after `fields` are merged.
+    #[serde(default)]
+    overrides: Vec<HeaderFieldOverride>,
+}
+
+/// Partial field update used by patch specs, e.g. to move inline/crossline bytes.
+#[derive(Debug, Clone, Deserialize)]
+struct HeaderFieldOverride {
+    field_key: String,
+    name: Option<String>,
+    byte_start: Option<u16>,
+    byte_end: Option<u16>,
+    data_type: Option<String>,
+    description: Option<String>,
+    required: Option<bool>,
+    code_mapping: Option<HashMap<String, String>>,
+}
+
+impl HeaderFieldOverride {
+    /// Apply the provided properties to a field, leaving the rest untouched.
+    fn apply(self, field: &mut HeaderFieldSpec) {
+        if let Some(name) = self.name {
+            field.name = name;
+        }
+        if let Some(byte_start) = self.byte_start {
+            field.byte_start = byte_start;
+        }
+        if let Some(byte_end) = self.byte_end {
+            field.byte_end = byte_end;
+        }
+        if let Some(data_type) = self.data_type {
+            field.data_type = data_type;
+        }
+        if let Some(description) = self.description {
+            field.description = description;
+        }
+        if let Some(required) = self.required {
+            field.required = required;
+        }
+        if let Some(code_mapping) = self.code_mapping {
+            field.code_mapping = Some(code_mapping);
+        }
+    }
+}
+
 impl SegyFormatSpec {
     /// Load SEG-Y Rev 0 specification from embedded JSON.
     pub fn load_rev0() -> Result<Self, String> {
-        const SPEC_JSON: &str = include_str!("../../config/segy_rev0_spec.json");
-        serde_json::from_str(SPEC_JSON).map_err(|e| format!("Failed to parse SEG-Y spec: {}", e))
+        Self::load_bundled("rev0")
+    }
+
+    /// Load a bundled specification by name (`rev0`, `rev1`, `rev2`, `rev21`).
+    ///
+    /// Each bundled spec is parsed once per process and cloned on later calls.
+    pub fn load_bundled(name: &str) -> Result<Self, String> {
+        Self::bundled(name).cloned()
+    }
+
+    /// Load a custom specification from a JSON file on disk.
+    ///
+    /// The file uses the same format as the bundled specs, so it can either be
+    /// a complete definition or `extends` a bundled spec by name and list only
+    /// added `fields` and `overrides`.
+    pub fn load_from_path(path: &str) -> Result<Self, String> {
+        let json = std::fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read SEG-Y spec '{}': {}", path, e))?;
+        let definition: SegyFormatDefinition = serde_json::from_str(&json)
+            .map_err(|e| format!("Failed to parse SEG-Y spec '{}': {}", path, e))?;
+
+        Self::materialize_definition(definition)
+    }
+
+    /// Load the specification matching a binary header revision code.
+    ///
+    /// Revision codes follow the binary header convention of major version in
+    /// the high byte and minor version in the low byte (0x0201 is Rev 2.1).
+    pub fn load_for_revision(revision: u16) -> Result<Self, String> {
+        let name = match revision {
+            0x0201..=u16::MAX => "rev21",
+            0x0200 => "rev2",
+            0x0100..=0x01FF => "rev1",
+            _ => "rev0",
+        };
+        Self::load_bundled(name)
+    }
+
+    /// Return the cached bundled spec, parsing and materializing it on first use.
+    ///
+    /// Only bundled specs can be extended and they form a fixed, acyclic
+    /// chain, so materializing a parent here never re-enters the same cell.
+    fn bundled(name: &str) -> Result<&'static Self, String> {
+        let bundled = BUNDLED_SPECS
+            .iter()
+            .find(|spec| spec.name == name)
+            .ok_or_else(|| format!("Unknown bundled SEG-Y spec: {}", name))?;
+
+        bundled
+            .parsed
+            .get_or_init(|| {
+                let definition: SegyFormatDefinition = serde_json::from_str(bundled.json)
+                    .map_err(|e| format!("Failed to parse SEG-Y spec '{}': {}", name, e))?;
+                Self::materialize_definition(definition)
+            })
+            .as_ref()
+            .map_err(Clone::clone)
+    }
+
+    /// Resolve a definition and its `extends` chain into a complete spec.
+    ///
+    /// Fields from the definition replace base fields with the same key and
+    /// are otherwise appended; the result is ordered by starting byte.
+    fn materialize_definition(definition: SegyFormatDefinition) -> Result<Self, String> {
+        let base = match &definition.extends {
+            Some(parent) => Some(Self::load_bundled(parent)?),
+            None => None,
+        };
+
+        let binary_block = definition.binary_header.unwrap_or_default();
+        let trace_block = definition.trace_header.unwrap_or_default();
+
+        let (binary_header, trace_header) = match base {
+            Some(base) => (base.binary_header, base.trace_header),
+            None => (
+                BinaryHeaderSpec {
+                    size: binary_block
+                        .size
+                        .ok_or("Binary header spec is missing 'size'")?,
+                    byte_offset: binary_block
+                        .byte_offset
+                        .ok_or("Binary header spec is missing 'byte_offset'")?,
+                    fields: Vec::new(),
+                },
+                TraceHeaderSpec {
+                    size: trace_block
+                        .size
+                        .ok_or("Trace header spec is missing 'size'")?,
+                    fields: Vec::new(),
+                },
+            ),
+        };
+
+        let spec = Self {
+            version: definition.version,
+            reference: definition.reference,
+            binary_header: BinaryHeaderSpec {
+                size: binary_block.size.unwrap_or(binary_header.size),
+                byte_offset: binary_block
+                    .byte_offset
+                    .unwrap_or(binary_header.byte_offset),
+                fields: merge_fields(
+                    binary_header.fields,
+                    binary_block.fields,
+                    binary_block.overrides,
+                )?,
+            },
+            trace_header: TraceHeaderSpec {
+                size: trace_block.size.unwrap_or(trace_header.size),
+                fields: merge_fields(
+                    trace_header.fields,
+                    trace_block.fields,
+                    trace_block.overrides,
+                )?,
+            },
+        };
+
+        spec.validate().map_err(|problems| {
+            format!(
+                "Invalid SEG-Y spec '{}': {}",
+                spec.version,
+                problems.join("; ")
+            )
+        })?;
+        Ok(spec)
+    }
+
+    /// Check field byte ranges for consistency.
+    ///
+    /// Every field must have `byte_start <= byte_end`, lie within its header's
+    /// declared byte range, and not overlap another field in the same header.
+    /// All problems are collected so a spec author sees them in one pass.
+    pub fn validate(&self) -> Result<(), Vec<String>> {
+        let mut problems = Vec::new();
+
+        let binary_start = self.binary_header.byte_offset + 1;
+        let binary_end = self.binary_header.byte_offset + self.binary_header.size;
+        check_block_fields(
+            "binary header",
+            &self.binary_header.fields,
+            binary_start,
+            binary_end,
+            &mut problems,
+        );
+        check_block_fields(
+            "trace header",
+            &self.trace_header.fields,
+            1,
+            self.trace_header.size,
+            &mut problems,
+        );
+
+        if problems.is_empty() {
+            Ok(())
+        } else {
+            Err(problems)
+        }
     }
 
     /// Get binary header field specifications.
@@ -79,12 +468,141 @@ impl SegyFormatSpec {
     pub fn get_trace_header_fields(&self) -> Vec<HeaderFieldSpec> {
         self.trace_header.fields.clone()
     }
+
+    /// Return this spec with trace header fields remapped by `overrides`.
+    ///
+    /// Each override replaces the field with the same `field_key`, or is
+    /// added when the key is new. Other fields whose bytes an override now
+    /// claims are dropped, since those bytes no longer hold them (e.g. moving
+    /// inlines to byte 9 drops `field_record_number`). The result must pass
+    /// `validate`.
+    pub fn with_trace_overrides(&self, overrides: Vec<HeaderFieldSpec>) -> Result<Self, String> {
+        let mut spec = self.clone();
+        for remapped in &overrides {
+            drop_displaced(&mut spec.trace_header.fields, remapped);
+        }
+        spec.trace_header.fields = merge_fields(spec.trace_header.fields, overrides, Vec::new())?;
+
+        spec.validate().map_err(|problems| {
+            format!("Invalid trace header overrides: {}", problems.join("; "))
+        })?;
+        Ok(spec)
+    }
+}
+
+/// Check one header block's fields for inverted, out-of-bounds, or overlapping ranges.
+///
+/// `first_byte` and `last_byte` are the inclusive 1-based bounds of the block.
+fn check_block_fields(
+    block: &str,
+    fields: &[HeaderFieldSpec],
+    first_byte: usize,
+    last_byte: usize,
+    problems: &mut Vec<String>,
+) {
+    let mut sorted: Vec<&HeaderFieldSpec> = fields.iter().collect();
+    sorted.sort_by_key(|f| (f.byte_start, f.byte_end));
+
+    let mut widest: Option<&HeaderFieldSpec> = None;
+    for field in sorted {
+        let (start, end) = (usize::from(field.byte_start), usize::from(field.byte_end));
+        if start > end {
+            problems.push(format!(
+                "{} field '{}' starts after it ends ({}-{})",
+                block, field.field_key, start, end
+            ));
+            continue;
+        }
+        if start < first_byte || end > last_byte {
+            problems.push(format!(
+                "{} field '{}' ({}-{}) is outside bytes {}-{}",
+                block, field.field_key, start, end, first_byte, last_byte
+            ));
+        }
+
+        if let Some(previous) = widest {
+            if field.byte_start <= previous.byte_end {
+                problems.push(format!(
+                    "{} field '{}' ({}-{}) overlaps '{}' ({}-{})",
+                    block,
+                    field.field_key,
+                    field.byte_start,
+                    field.byte_end,
+                    previous.field_key,
+                    previous.byte_start,
+                    previous.byte_end
+                ));
+            }
+        }
+        match widest {
+            Some(previous) if previous.byte_end >= field.byte_end => {}
+            _ => widest = Some(field),
+        }
+    }
+}
+
+/// Merge definition fields into base fields, replacing by `field_key`, then
+/// apply partial overrides to existing fields.
+///
+/// An override that moves a field's bytes drops the fields it now covers,
+/// since those bytes no longer hold them (e.g. moving inlines to byte 9 drops
+/// `field_record_number`). Added `fields` drop nothing, so one that overlaps
+/// an unrelated field fails `validate`.
+fn merge_fields(
+    mut base: Vec<HeaderFieldSpec>,
+    fields: Vec<HeaderFieldSpec>,
+    overrides: Vec<HeaderFieldOverride>,
+) -> Result<Vec<HeaderFieldSpec>, String> {
+    for field in fields {
+        match base.iter_mut().find(|f| f.field_key == field.field_key) {
+            Some(existing) => *existing = field,
+            None => base.push(field),
+        }
+    }
+
+    for field_override in overrides {
+        let index = base
+            .iter()
+            .position(|f| f.field_key == field_override.field_key)
+            .ok_or_else(|| {
+                format!(
+                    "Override targets unknown field '{}'",
+                    field_override.field_key
+                )
+            })?;
+        let moves_bytes = field_override.byte_start.is_some() || field_override.byte_end.is_some();
+        field_override.apply(&mut base[index]);
+        if moves_bytes {
+            let field = base[index].clone();
+            drop_displaced(&mut base, &field);
+        }
+    }
+
+    base.sort_by_key(|f| f.byte_start);
+    Ok(base)
+}
+
+/// Remove fields other than `claimed` itself that overlap its bytes.
+fn drop_displaced(fields: &mut Vec<HeaderFieldSpec>, claimed: &HeaderFieldSpec) {
+    fields.retain(|field| {
+        field.field_key == claimed.field_key
+            || field.byte_end < claimed.byte_start
+            || field.byte_start > claimed.byte_end
+    });
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_bundled_specs_are_parsed_once() {
+        let first = SegyFormatSpec::bundled("rev0").unwrap();
+        SegyFormatSpec::load_rev0().unwrap();
+        let second = SegyFormatSpec::bundled("rev0").unwrap();
+        assert!(std::ptr::eq(first, second));
+    }
+
     #[test]
     fn test_load_spec() {
         let spec = SegyFormatSpec::load_rev0().expect("Failed to load spec");
@@ -120,4 +638,213 @@ mod tests {
         let codes = format_field.code_mapping.as_ref().unwrap();
         assert_eq!(codes.get("1"), Some(&"IBM Float32".to_string()));
     }
+
+    #[test]
+    fn test_load_for_revision_extends_base() {
+        let rev0 = SegyFormatSpec::load_for_revision(0).unwrap();
+        let rev21 = SegyFormatSpec::load_for_revision(0x0201).unwrap();
+        assert_eq!(rev21.version, "SEG-Y Rev 2.1 (2023)");
+
+        let fields = rev21.get_binary_header_fields();
+        assert!(fields.iter().any(|f| f.field_key == "survey_type"));
+        assert!(fields.iter().any(|f| f.field_key == "job_id"));
+        assert!(rev21.trace_header.fields.len() > rev0.trace_header.fields.len());
+
+        // Rev 2 redefines the format code mapping rather than duplicating it.
+        let formats: Vec<_> = fields
+            .iter()
+            .filter(|f| f.field_key == "data_sample_format")
+            .collect();
+        assert_eq!(formats.len(), 1);
+        assert_eq!(formats[0].label_for_code(6), Some("IEEE Float64"));
+    }
+
+    #[test]
+    fn test_load_from_path_with_overrides() {
+        let path = std::env::temp_dir().join("tracelens_custom_spec_test.json");
+        std::fs::write(
+            &path,
+            r#"{
+                "version": "Custom",
+                "reference": "Test",
+                "extends": "rev1",
+                "trace_header": {
+                    "overrides": [
+                        { "field_key": "inline_number", "byte_start": 9, "byte_end": 12 }
+                    ]
+                }
+            }"#,
+        )
+        .unwrap();
+
+        let spec = SegyFormatSpec::load_from_path(path.to_str().unwrap()).unwrap();
+        let inline = spec
+            .trace_header
+            .fields
+            .iter()
+            .find(|f| f.field_key == "inline_number")
+            .unwrap();
+        assert_eq!((inline.byte_start, inline.byte_end), (9, 12));
+        assert!(!spec
+            .trace_header
+            .fields
+            .iter()
+            .any(|f| f.field_key == "field_record_number"));
+        std::fs::remove_file(path).ok();
+    }
+
+    #[test]
+    fn test_load_from_path_rejects_overlapping_fields() {
+        let path = std::env::temp_dir().join("tracelens_overlap_spec_test.json");
+        std::fs::write(
+            &path,
+            r#"{
+                "version": "Overlap",
+                "reference": "Test",
+                "extends": "rev1",
+                "trace_header": {
+                    "fields": [
+                        {
+                            "name": "Custom inline",
+                            "field_key": "custom_inline",
+                            "byte_start": 189,
+                            "byte_end": 192,
+                            "data_type": "int32",
+                            "description": "Typo for the inline bytes",
+                            "required": false
+                        }
+                    ]
+                }
+            }"#,
+        )
+        .unwrap();
+
+        let error = SegyFormatSpec::load_from_path(path.to_str().unwrap()).unwrap_err();
+        std::fs::remove_file(path).ok();
+        assert!(error.contains("overlaps"), "{}", error);
+        assert!(error.contains("189-192"), "{}", error);
+    }
+
+    #[test]
+    fn test_trace_overrides_replace_displaced_fields() {
+        let base = SegyFormatSpec::load_bundled("rev1").unwrap();
+        let mut inline = base
+            .trace_header
+            .fields
+            .iter()
+            .find(|f| f.field_key == "inline_number")
+            .unwrap()
+            .clone();
+        (inline.byte_start, inline.byte_end) = (9, 12);
+
+        let spec = base.with_trace_overrides(vec![inline.clone()]).unwrap();
+        let fields = spec.get_trace_header_fields();
+        let at_byte_9: Vec<_> = fields.iter().filter(|f| f.byte_start == 9).collect();
+        assert_eq!(at_byte_9.len(), 1);
+        assert_eq!(at_byte_9[0].field_key, "inline_number");
+        assert!(!fields.iter().any(|f| f.byte_start == 189));
+        assert_eq!(fields.len(), base.trace_header.fields.len() - 1);
+
+        inline.byte_end = 300;
+        assert!(base.with_trace_overrides(vec![inline]).is_err());
+    }
+
+    #[test]
+    fn test_validate_reports_overlap() {
+        let mut spec = SegyFormatSpec::load_bundled("rev1").unwrap();
+        assert!(spec.validate().is_ok());
+
+        let mut duplicate = spec
+            .trace_header
+            .fields
+            .iter()
+            .find(|f| f.field_key == "inline_number")
+            .unwrap()
+            .clone();
+        duplicate.field_key = "custom_inline".to_string();
+        spec.trace_header.fields.push(duplicate);
+
+        let problems = spec.validate().unwrap_err();
+        assert_eq!(problems.len(), 1);
+        assert!(problems[0].contains("overlaps"));
+        assert!(problems[0].contains("189-192"));
+    }
+
+    #[test]
+    fn test_resolve_header_labels() {
+        let spec = SegyFormatSpec::load_rev0().unwrap();
+        let fields = spec.get_binary_header_fields();
+        let values = HashMap::from([
+            ("data_sample_format".to_string(), Value::from(1)),
+            ("job_id".to_string(), Value::from(42)),
+        ]);
+
+        let labels = resolve_header_labels(&values, &fields);
+        assert_eq!(
+            labels.get("data_sample_format").map(String::as_str),
+            Some("1 (IBM Float32)")
+        );
+        assert!(!labels.contains_key("job_id"));
+    }
+
+    #[test]
+    fn test_read_value_from_binary_header_bytes() {
+        let spec = SegyFormatSpec::load_rev0().unwrap();
+        let fields = spec.get_binary_header_fields();
+        let samples = fields
+            .iter()
+            .find(|f| f.field_key == "samples_per_trace")
+            .unwrap();
+
+        let mut bytes = vec![0u8; 400];
+        bytes[20..22].copy_from_slice(&1500u16.to_be_bytes());
+        assert_eq!(
+            samples.read_value(&bytes, 3201, ByteOrder::BigEndian),
+            Some(1500)
+        );
+    }
+
+    #[test]
+    fn test_decode_header_fields_resolves_labels() {
+        let spec = SegyFormatSpec::load_rev0().unwrap();
+        let fields = spec.get_trace_header_fields();
+        let mut bytes = vec![0u8; 240];
+        bytes[20..24].copy_from_slice(&1234i32.to_be_bytes());
+        bytes[28..30].copy_from_slice(&2i16.to_be_bytes());
+
+        let decoded = decode_header_fields(&bytes, 1, ByteOrder::BigEndian, &fields);
+        assert_eq!(decoded.len(), fields.len());
+        let get = |key: &str| {
+            decoded
+                .iter()
+                .find(|field| field.spec.field_key == key)
+                .unwrap()
+        };
+        assert_eq!(get("cdp_ensemble_number").value, Value::from(1234));
+        assert_eq!(get("cdp_ensemble_number").label, None);
+        assert_eq!(get("trace_id_code").value, Value::from(2));
+        assert!(get("trace_id_code").label.is_some());
+    }
+
+    #[test]
+    fn test_diff_header_fields_reports_changed_values() {
+        let spec = SegyFormatSpec::load_rev0().unwrap();
+        let fields = spec.get_trace_header_fields();
+        let mut a = vec![0u8; 240];
+        let mut b = a.clone();
+        a[20..24].copy_from_slice(&100i32.to_be_bytes());
+        b[20..24].copy_from_slice(&101i32.to_be_bytes());
+        a[114..116].copy_from_slice(&500u16.to_be_bytes());
+        b[114..116].copy_from_slice(&500u16.to_be_bytes());
+
+        let diffs = diff_header_fields(&a, &b, 1, ByteOrder::BigEndian, &fields);
+        assert_eq!(
+            diffs,
+            vec![FieldDiff {
+                field_key: "cdp_ensemble_number".to_string(),
+                value_a: 100,
+                value_b: 101,
+            }]
+        );
+    }
 }
@@ -5,15 +5,24 @@
 
 use crate::error::AppError;
 use crate::segy::parser::binary_header::DataSampleFormat;
+use crate::segy::trace_store::TraceStore;
 use crate::segy::{
-    constants, BinaryHeader, ByteOrder, SegyFileConfig, TextualHeader, TraceBlock, TraceData,
+    constants, utils, BinaryHeader, ByteOrder, SegyFileConfig, TextualHeader, TraceBlock,
+    TraceData, TraceHeader, TRACE_HEADER_SIZE,
 };
 use std::fs::File;
-use std::io::{Seek, SeekFrom};
+use std::io::{Read, Seek, SeekFrom};
+use std::ops::Range;
 
 /// Minimum file size for a valid SEG-Y file (textual + binary headers only).
 const MIN_SEGY_SIZE: u64 = constants::FILE_HEADER_SIZE as u64;
 
+/// Upper bound on extended textual headers; larger counts are treated as corrupt.
+const MAX_EXTENDED_TEXTUAL_HEADERS: usize = 1000;
+
+/// Stanza closing a variable number of extended textual headers (Rev 2).
+const END_TEXT_STANZA: &str = "((SEG: EndText))";
+
 /// Parsed header bundle and file metadata.
 pub(crate) struct HeaderBundle {
     /// Parsed textual header.
@@ -30,7 +39,6 @@ pub(crate) fn read_headers(file: &mut File) -> Result<HeaderBundle, AppError> {
         message: format!("Failed to read file metadata: {}", e),
     })?;
     let file_size = metadata.len();
-    ensure_min_file_size(file_size)?;
 
     // Reset reader to the file start to read the headers.
     file.seek(SeekFrom::Start(0))
@@ -38,11 +46,24 @@ pub(crate) fn read_headers(file: &mut File) -> Result<HeaderBundle, AppError> {
             message: format!("Failed to seek to file start: {}", e),
         })?;
 
-    let textual_header = TextualHeader::from_reader(file).map_err(|e| AppError::SegyError {
+    parse_headers(file, file_size)
+}
+
+/// Parse textual and binary headers from a reader positioned at the file start.
+///
+/// `file_size` is the total size of the underlying data and is validated
+/// before any bytes are read.
+pub(crate) fn parse_headers<R: Read>(
+    reader: &mut R,
+    file_size: u64,
+) -> Result<HeaderBundle, AppError> {
+    ensure_min_file_size(file_size)?;
+
+    let textual_header = TextualHeader::from_reader(reader).map_err(|e| AppError::SegyError {
         message: format!("Failed to read textual header: {}", e),
     })?;
 
-    let binary_header = BinaryHeader::from_reader(file).map_err(|e| AppError::SegyError {
+    let binary_header = BinaryHeader::from_reader(reader).map_err(|e| AppError::SegyError {
         message: format!("Failed to parse binary header: {}", e),
     })?;
 
@@ -53,23 +74,122 @@ pub(crate) fn read_headers(file: &mut File) -> Result<HeaderBundle, AppError> {
     })
 }
 
+/// Locate the first trace, past the binary header and any extended textual headers.
+///
+/// A Rev 2 `byte_offset_of_first_trace` wins when set. Otherwise the Rev 1+
+/// extended textual header count is applied, where -1 means a variable count
+/// closed by an EndText stanza and the blocks are scanned for it. Counts over
+/// the sanity cap or past the end of the file are ignored.
+pub(crate) fn first_trace_offset(
+    store: &TraceStore,
+    binary_header: &BinaryHeader,
+) -> Result<usize, AppError> {
+    let declared_offset = binary_header
+        .byte_offset_of_first_trace
+        .and_then(|offset| usize::try_from(offset).ok())
+        .filter(|&offset| offset >= constants::FILE_HEADER_SIZE && offset <= store.len());
+    if let Some(offset) = declared_offset {
+        return Ok(offset);
+    }
+    if binary_header.segy_revision < 0x0100 {
+        return Ok(constants::FILE_HEADER_SIZE);
+    }
+
+    let count = match binary_header.extended_textual_headers {
+        -1 => count_stanza_terminated_headers(store)?,
+        count => usize::try_from(count).unwrap_or(0),
+    };
+    let offset = constants::FILE_HEADER_SIZE + count * constants::TEXTUAL_HEADER_SIZE;
+    if count > MAX_EXTENDED_TEXTUAL_HEADERS || offset > store.len() {
+        log::warn!(
+            "Ignoring implausible extended textual header count {}",
+            binary_header.extended_textual_headers
+        );
+        return Ok(constants::FILE_HEADER_SIZE);
+    }
+    Ok(offset)
+}
+
+/// Count extended textual headers up to and including the EndText stanza.
+///
+/// Returns zero when no stanza is found within the sanity cap.
+fn count_stanza_terminated_headers(store: &TraceStore) -> Result<usize, AppError> {
+    let mut start = constants::FILE_HEADER_SIZE;
+    for count in 1..=MAX_EXTENDED_TEXTUAL_HEADERS {
+        let end = start + constants::TEXTUAL_HEADER_SIZE;
+        if end > store.len() {
+            break;
+        }
+        if utils::text_to_ascii(&store.slice(start..end)?).contains(END_TEXT_STANZA) {
+            return Ok(count);
+        }
+        start = end;
+    }
+
+    log::warn!(
+        "Variable extended textual header count without a {} stanza",
+        END_TEXT_STANZA
+    );
+    Ok(0)
+}
+
 /// Compute total trace count from file size and per-trace block size.
 ///
-/// Returns `None` when the size is invalid or the calculation would overflow.
-pub(crate) fn compute_total_traces(file_size: u64, trace_block_size: usize) -> Option<usize> {
+/// `data_offset` is the byte position of the first trace. Returns `None` when
+/// the size is invalid or the calculation would overflow.
+pub(crate) fn compute_total_traces(
+    file_size: u64,
+    data_offset: usize,
+    trace_block_size: usize,
+) -> Option<usize> {
     if trace_block_size == 0 || trace_block_size as u64 > file_size {
         return None;
     }
 
-    let data_size = file_size.saturating_sub(constants::FILE_HEADER_SIZE as u64);
+    let data_size = file_size.saturating_sub(data_offset as u64);
     Some((data_size / trace_block_size as u64) as usize)
 }
 
-/// Parse a full trace block (header + samples) from raw bytes.
+/// Count bytes after the last complete fixed-size trace.
+///
+/// A nonzero result indicates a trailing partial trace, which usually means
+/// the file is truncated or padded.
+pub(crate) fn compute_trailing_bytes(
+    file_size: u64,
+    data_offset: usize,
+    trace_block_size: usize,
+) -> u64 {
+    if trace_block_size == 0 {
+        return 0;
+    }
+
+    let data_size = file_size.saturating_sub(data_offset as u64);
+    data_size % trace_block_size as u64
+}
+
+/// Read `num_samples` from the first trace header, if present and non-zero.
+///
+/// `header` starts at the first trace. Used to recover the trace length when
+/// the binary header reports zero.
+pub(crate) fn read_first_trace_num_samples(header: &[u8], byte_order: ByteOrder) -> Option<u16> {
+    // Trace header bytes 115-116 (1-based) hold the per-trace sample count.
+    let raw: [u8; 2] = header.get(114..116)?.try_into().ok()?;
+    let samples = match byte_order {
+        ByteOrder::BigEndian => u16::from_be_bytes(raw),
+        ByteOrder::LittleEndian => u16::from_le_bytes(raw),
+    };
+    (samples > 0).then_some(samples)
+}
+
+/// Parse a full trace block (headers + samples) from raw bytes.
+///
+/// `extended_headers` Rev 2 header blocks sit between the trace header and
+/// the samples; their raw bytes are kept on the block.
 pub(crate) fn parse_trace_block(
     trace_bytes: &[u8],
     format: DataSampleFormat,
     samples_per_trace: u16,
+    extended_headers: usize,
     byte_order: ByteOrder,
 ) -> Result<TraceBlock, AppError> {
     let samples = i16::try_from(samples_per_trace).map_err(|_| AppError::ValidationError {
@@ -80,21 +200,54 @@ pub(crate) fn parse_trace_block(
     })?;
 
     let mut cursor = std::io::Cursor::new(trace_bytes);
-    TraceBlock::from_reader(&mut cursor, format, Some(samples), byte_order).map_err(|e| {
-        AppError::SegyError {
-            message: format!("Trace parse failed: {}", e),
-        }
+    TraceBlock::from_reader(
+        &mut cursor,
+        format,
+        Some(samples),
+        extended_headers,
+        byte_order,
+    )
+    .map_err(|e| AppError::SegyError {
+        message: format!("Trace parse failed: {}", e),
+    })
+}
+
+/// Parse only the 240-byte trace header from raw trace bytes.
+pub(crate) fn parse_trace_header(
+    trace_bytes: &[u8],
+    byte_order: ByteOrder,
+) -> Result<TraceHeader, AppError> {
+    let header_bytes = trace_bytes
+        .get(..TRACE_HEADER_SIZE)
+        .ok_or_else(|| AppError::SegyError {
+            message: "Trace header is truncated".to_string(),
+        })?;
+
+    TraceHeader::from_reader(header_bytes, byte_order).map_err(|e| AppError::SegyError {
+        message: format!("Trace header parse failed: {}", e),
     })
 }
 
 /// Parse trace samples only (skip header) from raw bytes.
+///
+/// Only the samples in `samples` are decoded; bytes outside the window are
+/// never touched. Samples start `header_size` bytes into the trace and are
+/// read in `byte_order`, like the trace header.
 pub(crate) fn parse_trace_data(
     trace_bytes: &[u8],
+    header_size: usize,
     format: DataSampleFormat,
-    samples_per_trace: u16,
+    samples: Range<usize>,
+    byte_order: ByteOrder,
 ) -> Result<TraceData, AppError> {
-    let data_offset = constants::TRACE_HEADER_SIZE;
-    let samples = usize::from(samples_per_trace);
+    let data_offset = samples
+        .start
+        .checked_mul(format.bytes_per_sample())
+        .and_then(|skip| header_size.checked_add(skip))
+        .ok_or_else(|| AppError::ValidationError {
+            message: "Trace data offset overflow".to_string(),
+        })?;
+    let samples = samples.len();
     let data_size = samples
         .checked_mul(format.bytes_per_sample())
         .ok_or_else(|| AppError::ValidationError {
@@ -114,8 +267,10 @@ pub(crate) fn parse_trace_data(
         })?;
 
     let mut cursor = std::io::Cursor::new(data_bytes);
-    TraceData::from_reader(&mut cursor, format, samples).map_err(|e| AppError::SegyError {
-        message: format!("Trace data parse failed: {}", e),
+    TraceData::from_reader(&mut cursor, format, samples, byte_order).map_err(|e| {
+        AppError::SegyError {
+            message: format!("Trace data parse failed: {}", e),
+        }
     })
 }
 
@@ -129,8 +284,36 @@ pub(crate) fn validate_file_path(file_path: &str) -> Result<(), AppError> {
     Ok(())
 }
 
+/// Memory-map a SEG-Y file after re-checking its current size.
+///
+/// Mapping an empty file fails with an opaque OS error on some platforms, so
+/// empty and truncated files are rejected before the map is created.
+pub(crate) fn map_file(file: &File) -> Result<memmap2::Mmap, AppError> {
+    let file_size = file
+        .metadata()
+        .map_err(|e| AppError::IoError {
+            message: format!("Failed to read file metadata: {}", e),
+        })?
+        .len();
+    ensure_min_file_size(file_size)?;
+
+    // Safety: Memory mapping is safe here because:
+    // 1. The caller holds the File handle for the mmap lifetime
+    // 2. The file is opened in read-only mode (no writes from this process)
+    // 3. All slice accesses are validated before dereferencing mapped memory
+    unsafe { memmap2::Mmap::map(file) }.map_err(|e| AppError::IoError {
+        message: format!("Failed to memory-map file: {}", e),
+    })
+}
+
 /// Ensure the file is large enough to contain the SEG-Y headers.
 fn ensure_min_file_size(file_size: u64) -> Result<(), AppError> {
+    if file_size == 0 {
+        return Err(AppError::SegyError {
+            message: "File is empty".to_string(),
+        });
+    }
+
     if file_size < MIN_SEGY_SIZE {
         return Err(AppError::SegyError {
             message: format!(
@@ -142,6 +325,23 @@ fn ensure_min_file_size(file_size: u64) -> Result<(), AppError> {
     Ok(())
 }
 
+/// Check that a `[start, end)` sample window is non-empty and within a trace
+/// of `trace_samples` samples.
+pub(crate) fn validate_sample_window(
+    window: &Range<usize>,
+    trace_samples: usize,
+) -> Result<(), AppError> {
+    if window.start >= window.end || window.end > trace_samples {
+        return Err(AppError::ValidationError {
+            message: format!(
+                "Sample window {}..{} is invalid for traces of {} samples",
+                window.start, window.end, trace_samples
+            ),
+        });
+    }
+    Ok(())
+}
+
 /// Validate the requested trace range and ensure the configuration is usable.
 pub(crate) fn validate_trace_range(
     config: &SegyFileConfig,
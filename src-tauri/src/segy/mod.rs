@@ -12,28 +12,45 @@
 //! 3. Parse trace headers and data on demand.
 //! 4. Render traces into variable-density or wiggle views.
 
+pub mod analysis;
+#[cfg(test)]
+pub(crate) mod bench;
 mod constants;
+pub mod geometry;
 pub mod header_spec;
 mod io;
 mod model;
 pub mod parser;
 mod reader;
 pub mod rendering;
+#[cfg(test)]
+pub(crate) mod synthetic;
+mod trace_cache;
+mod trace_store;
 mod utils;
+pub mod validation;
 
 /// Size constants for SEG-Y structures.
 pub use constants::*;
 /// Binary header definition and byte-order detection.
 pub use parser::{
-    BinaryHeader, ByteOrder, CoordinateUnits, DataSampleFormat, MeasurementSystem, SampleFormat,
-    TextualHeader, TraceBlock, TraceData, TraceHeader, TraceIdentificationCode, TraceSortingCode,
+    BinaryHeader, ByteOrder, CoordinateUnits, DataSampleFormat, DownsampleMode, MeasurementSystem,
+    RecordingTime, SampleFormat, TextualHeader, TimeBasis, TraceBlock, TraceData, TraceHeader,
+    TraceIdentificationCode, TraceSortingCode,
 };
 
 /// Header specification structures loaded from the JSON spec.
-pub use header_spec::{HeaderFieldSpec, SegyFormatSpec};
+pub use header_spec::{DecodedField, FieldDiff, HeaderFieldSpec, SegyFormatSpec};
 /// High-level data models and derived file configuration.
-pub use model::{SegyData, SegyFileConfig};
+pub use model::{
+    FileSummary, RawBinaryHeader, RawTextualHeader, SampleFormatSuggestion, SamplesPayload,
+    ScanProgress, SegyData, SegyFileConfig,
+};
 /// SEG-Y reader and cacheable state for Tauri commands.
 pub use reader::{SegyReader, SegyReaderState};
+/// Bounded LRU cache of decoded trace blocks used by `SegyReader`.
+pub use trace_cache::{TraceCache, DEFAULT_TRACE_CACHE_SAMPLES};
 /// Detected textual header encoding.
 pub use utils::TextEncoding;
+/// File validation findings against the header spec.
+pub use validation::{ConsistencyWarning, ValidationFinding, ValidationSeverity};
@@ -5,7 +5,7 @@
 
 use crate::error::AppError;
 use crate::segy::parser::binary_header::DataSampleFormat;
-use crate::segy::{constants, BinaryHeader, ByteOrder, TextEncoding, TextualHeader};
+use crate::segy::{constants, BinaryHeader, ByteOrder, TextEncoding, TextualHeader, TraceData};
 
 /// SEG-Y file data structure containing headers only (no traces loaded eagerly)
 ///
@@ -22,6 +22,10 @@ pub struct SegyData {
     /// Total number of traces in file (if determinable)
     pub total_traces: Option<usize>,
 
+    /// Bytes after the last complete trace; nonzero suggests a truncated or
+    /// padded file
+    pub trailing_bytes: u64,
+
     /// File size in bytes
     pub file_size: u64,
 
@@ -30,6 +34,137 @@ pub struct SegyData {
 
     /// Detected byte order for binary data
     pub byte_order: ByteOrder,
+
+    /// True when samples per trace was taken from the first trace header
+    /// because the binary header reported zero
+    pub samples_per_trace_inferred: bool,
+}
+
+/// One-call overview of an open file for the file-info sidebar.
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct FileSummary {
+    /// Name of the spec matching the declared revision, e.g. `"SEG-Y Rev 1 (2002)"`
+    pub revision: String,
+
+    /// Detected byte order for binary data
+    pub byte_order: ByteOrder,
+
+    /// Sample format name, e.g. `"IBM float (32-bit)"`
+    pub sample_format: String,
+
+    /// Samples per trace used for reading, after inference or overrides
+    pub samples_per_trace: u16,
+
+    /// Sample interval in milliseconds
+    pub sample_interval_ms: f32,
+
+    /// Total number of traces in file (if determinable)
+    pub total_traces: Option<usize>,
+
+    /// Bytes after the last complete trace
+    pub trailing_bytes: u64,
+
+    /// Trace sorting name, e.g. `"CDP ensemble"`
+    pub sort: String,
+
+    /// Measurement system name, e.g. `"Meters"`
+    pub measurement_system: String,
+}
+
+/// Untouched textual header bytes with the encoding detected for them.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct RawTextualHeader {
+    /// Original 3200 bytes as stored in the file
+    pub bytes: Vec<u8>,
+
+    /// Encoding detected when the header was parsed
+    pub encoding: TextEncoding,
+}
+
+/// Untouched binary header bytes with the byte order detected for them.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct RawBinaryHeader {
+    /// Original 400 bytes as stored in the file
+    pub bytes: Vec<u8>,
+
+    /// Byte order detected when the header was parsed
+    pub byte_order: ByteOrder,
+}
+
+/// Progress of a long trace scan, reported every few thousand traces.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct ScanProgress {
+    /// Traces scanned so far
+    pub processed: usize,
+
+    /// Traces the scan will visit in total
+    pub total: usize,
+}
+
+/// Sample format suggested by comparing IBM and IEEE decodes of a few traces.
+#[derive(Debug, Clone, Copy, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct SampleFormatSuggestion {
+    /// Format code declared in the binary header
+    pub declared: u16,
+
+    /// Format code that decodes more plausibly; equal to `declared` when no
+    /// override is needed or the data cannot tell
+    pub suggested: u16,
+
+    /// Fraction of non-zero samples implausible as IBM float
+    pub ibm_implausible: f32,
+
+    /// Fraction of non-zero samples implausible as IEEE float
+    pub ieee_implausible: f32,
+
+    /// Non-zero samples compared
+    pub samples_checked: usize,
+}
+
+impl From<&TextualHeader> for RawTextualHeader {
+    fn from(header: &TextualHeader) -> Self {
+        Self {
+            bytes: header.raw_data().to_vec(),
+            encoding: header.encoding(),
+        }
+    }
+}
+
+/// Trace samples flattened into a single row-major f32 buffer.
+///
+/// Trace `i` occupies `data[i * samples_per_trace..(i + 1) * samples_per_trace]`.
+/// Traces shorter than the longest one are right-padded with NaN, which
+/// serializes to `null` in JSON.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct SamplesPayload {
+    /// Number of traces in the buffer
+    pub trace_count: usize,
+
+    /// Samples per trace row, equal to the longest trace
+    pub samples_per_trace: usize,
+
+    /// Row-major samples converted to f32
+    pub data: Vec<f32>,
+}
+
+impl SamplesPayload {
+    /// Flatten decoded traces of any sample format into a payload.
+    pub fn from_traces(traces: &[TraceData]) -> Self {
+        let samples_per_trace = traces.iter().map(TraceData::len).max().unwrap_or(0);
+        let mut data = Vec::with_capacity(traces.len() * samples_per_trace);
+        for trace in traces {
+            let samples = trace.to_f32_vec();
+            let pad = samples_per_trace - samples.len();
+            data.extend(samples);
+            data.resize(data.len() + pad, f32::NAN);
+        }
+
+        Self {
+            trace_count: traces.len(),
+            samples_per_trace,
+            data,
+        }
+    }
 }
 
 /// Configuration for SEG-Y file parameters used across trace loading operations
@@ -42,6 +177,31 @@ pub struct SegyFileConfig {
     pub data_sample_format: u16,
     /// Detected byte order for the file.
     pub byte_order: ByteOrder,
+    /// True when `samples_per_trace` was inferred from the first trace header.
+    #[serde(default)]
+    pub samples_per_trace_inferred: bool,
+    /// False when traces may differ in length and offsets must be walked.
+    ///
+    /// Configs saved without the field are treated as fixed length.
+    #[serde(default = "default_fixed_length_traces")]
+    pub fixed_length_traces: bool,
+    /// Byte position of the first trace, past any extended textual headers.
+    ///
+    /// Detected when the file is opened; values in override configs are ignored.
+    #[serde(default = "default_data_offset")]
+    pub data_offset: usize,
+    /// Rev 2 extra 240-byte header blocks following each standard trace header;
+    /// always taken from the binary header on open, like `data_offset`.
+    #[serde(default)]
+    pub extended_trace_headers: usize,
+}
+
+fn default_data_offset() -> usize {
+    constants::FILE_HEADER_SIZE
+}
+
+fn default_fixed_length_traces() -> bool {
+    true
 }
 
 impl SegyFileConfig {
@@ -56,9 +216,21 @@ impl SegyFileConfig {
             samples_per_trace,
             data_sample_format: header.data_sample_format as i16 as u16,
             byte_order: header.byte_order,
+            samples_per_trace_inferred: false,
+            fixed_length_traces: header.has_fixed_length_traces(),
+            data_offset: default_data_offset(),
+            extended_trace_headers: header.num_extended_trace_headers(),
         })
     }
 
+    /// Bytes of header in front of each trace's samples: the standard header
+    /// plus any extended header blocks.
+    pub fn trace_header_size(&self) -> usize {
+        self.extended_trace_headers
+            .saturating_add(1)
+            .saturating_mul(constants::TRACE_HEADER_SIZE)
+    }
+
     /// Calculate the total size of a trace block (header + data)
     pub fn trace_block_size(&self) -> Result<usize, AppError> {
         if self.samples_per_trace == 0 {
@@ -80,7 +252,7 @@ impl SegyFileConfig {
                 message: "Trace data size overflow".to_string(),
             })?;
 
-        constants::TRACE_HEADER_SIZE
+        self.trace_header_size()
             .checked_add(trace_data_size)
             .ok_or_else(|| AppError::ValidationError {
                 message: "Trace block size overflow".to_string(),
@@ -97,7 +269,7 @@ impl SegyFileConfig {
                     message: "Trace offset overflow".to_string(),
                 })?;
 
-        constants::FILE_HEADER_SIZE
+        self.data_offset
             .checked_add(offset)
             .ok_or_else(|| AppError::ValidationError {
                 message: "Trace position overflow".to_string(),
@@ -113,3 +285,35 @@ impl SegyFileConfig {
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_samples_payload_pads_short_traces_with_nan() {
+        let traces = vec![
+            TraceData::Int16(vec![1, 2, 3]),
+            TraceData::IeeeFloat32(vec![0.5]),
+        ];
+        let payload = SamplesPayload::from_traces(&traces);
+
+        assert_eq!(payload.trace_count, 2);
+        assert_eq!(payload.samples_per_trace, 3);
+        assert_eq!(&payload.data[..4], &[1.0, 2.0, 3.0, 0.5]);
+        assert!(payload.data[4..].iter().all(|v| v.is_nan()));
+    }
+
+    #[test]
+    fn test_config_without_optional_fields_deserializes() {
+        let config: SegyFileConfig = serde_json::from_str(
+            r#"{"samplesPerTrace": 10, "dataSampleFormat": 5, "byteOrder": "BigEndian"}"#,
+        )
+        .unwrap();
+
+        assert!(config.fixed_length_traces);
+        assert!(!config.samples_per_trace_inferred);
+        assert_eq!(config.data_offset, constants::FILE_HEADER_SIZE);
+        assert_eq!(config.extended_trace_headers, 0);
+    }
+}
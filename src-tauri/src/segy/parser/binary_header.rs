@@ -51,6 +51,18 @@ impl DataSampleFormat {
             _ => Err(format!("Invalid data sample format code: {}", code)),
         }
     }
+
+    /// Human-readable name, e.g. `"IBM float (32-bit)"`.
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::IbmFloat32 => "IBM float (32-bit)",
+            Self::Int32 => "Integer (32-bit)",
+            Self::Int16 => "Integer (16-bit)",
+            Self::FixedPointWithGain => "Fixed point with gain (32-bit)",
+            Self::IeeeFloat32 => "IEEE float (32-bit)",
+            Self::Int8 => "Integer (8-bit)",
+        }
+    }
 }
 
 /// Trace sorting code
@@ -81,6 +93,17 @@ impl TraceSortingCode {
             _ => Err(format!("Invalid trace sorting code: {}", code)),
         }
     }
+
+    /// Human-readable name, e.g. `"CDP ensemble"`.
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Unknown => "Unknown",
+            Self::AsRecorded => "As recorded",
+            Self::CdpEnsemble => "CDP ensemble",
+            Self::SingleFold => "Single fold continuous profile",
+            Self::HorizontallyStacked => "Horizontally stacked",
+        }
+    }
 }
 
 /// Measurement system code
@@ -105,6 +128,15 @@ impl MeasurementSystem {
             _ => Err(format!("Invalid measurement system code: {}", code)),
         }
     }
+
+    /// Human-readable name, e.g. `"Meters"`.
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Unknown => "Unknown",
+            Self::Meters => "Meters",
+            Self::Feet => "Feet",
+        }
+    }
 }
 
 /// Byte order (endianness) of binary data
@@ -211,7 +243,55 @@ pub struct BinaryHeader {
     /// Vibratory polarity code (bytes 3259-3260)
     pub vibratory_polarity: i16,
 
-    /// Unassigned bytes (3261-3600)
+    /// SEG-Y format revision number, e.g. 0x0100 for Rev 1 (bytes 3501-3502)
+    #[serde(default)]
+    pub segy_revision: u16,
+
+    /// Fixed length trace flag: 1=all traces same length, 0=variable (bytes 3503-3504)
+    #[serde(default)]
+    pub fixed_length_trace_flag: i16,
+
+    /// Number of 3200-byte extended textual headers (bytes 3505-3506)
+    #[serde(default)]
+    pub extended_textual_headers: i16,
+
+    /// Rev 2: data traces per ensemble when larger than 32767 (bytes 3261-3264)
+    #[serde(default)]
+    pub extended_traces_per_ensemble: Option<i32>,
+
+    /// Rev 2: samples per data trace when larger than 65535 (bytes 3269-3272)
+    #[serde(default)]
+    pub extended_samples_per_trace: Option<i32>,
+
+    /// Rev 2: sample interval as an IEEE double (bytes 3273-3280)
+    #[serde(default)]
+    pub extended_sample_interval: Option<f64>,
+
+    /// Rev 2: maximum number of additional 240-byte trace headers (bytes 3507-3510)
+    #[serde(default)]
+    pub max_additional_trace_headers: Option<i32>,
+
+    /// Rev 2: time basis code for all traces (bytes 3511-3512)
+    #[serde(default)]
+    pub time_basis_code: Option<i16>,
+
+    /// Rev 2: number of traces in the file or stream (bytes 3513-3520)
+    #[serde(default)]
+    pub number_of_traces: Option<u64>,
+
+    /// Rev 2: byte offset of the first trace from the file start (bytes 3521-3528)
+    #[serde(default)]
+    pub byte_offset_of_first_trace: Option<u64>,
+
+    /// Rev 2: number of 3200-byte data trailer stanzas (bytes 3529-3532)
+    #[serde(default)]
+    pub data_trailer_stanzas: Option<i32>,
+
+    /// Unassigned bytes (3261-3500 followed by 3507-3600)
+    ///
+    /// Kept raw for every revision, including the bytes Rev 2 fields decode from.
+    /// Use `unassigned_pre_revision` and `unassigned_post_revision` to tell
+    /// the two blocks apart.
     pub unassigned: Vec<u8>,
 }
 
@@ -255,10 +335,41 @@ fn detect_endianness(data: &[u8]) -> Endianness {
     }
 }
 
+/// Detect the byte order of a raw 400-byte binary header without parsing it.
+///
+/// Useful when the header may contain codes the full parser rejects.
+pub fn detect_byte_order(data: &[u8]) -> ByteOrder {
+    match detect_endianness(data) {
+        Endianness::Big => ByteOrder::BigEndian,
+        Endianness::Little => ByteOrder::LittleEndian,
+    }
+}
+
 impl BinaryHeader {
     /// Size of the binary header in bytes
     pub const SIZE: usize = 400;
 
+    /// Offset of the Rev 1 revision fields within the binary header (byte 3501)
+    const REVISION_FIELDS_OFFSET: usize = 300;
+
+    /// Length of the unassigned block before the revision fields (3261-3500)
+    pub const UNASSIGNED_PRE_REVISION_LEN: usize = Self::REVISION_FIELDS_OFFSET - 60;
+
+    /// Unassigned bytes 3261-3500, before the revision fields.
+    ///
+    /// Sliced from `unassigned`, so headers deserialized with a shorter
+    /// vector return what they have.
+    pub fn unassigned_pre_revision(&self) -> &[u8] {
+        let end = Self::UNASSIGNED_PRE_REVISION_LEN.min(self.unassigned.len());
+        &self.unassigned[..end]
+    }
+
+    /// Unassigned bytes 3507-3600, after the revision fields.
+    pub fn unassigned_post_revision(&self) -> &[u8] {
+        let start = Self::UNASSIGNED_PRE_REVISION_LEN.min(self.unassigned.len());
+        &self.unassigned[start..]
+    }
+
     /// Parse a binary header from a reader with automatic endianness detection
     ///
     /// Automatically detects whether data is big-endian (standard) or little-endian.
@@ -349,12 +460,27 @@ impl BinaryHeader {
         let impulse_polarity = read_i16!(reader);
         let vibratory_polarity = read_i16!(reader);
 
-        // Read unassigned bytes (3261-3600 = 340 bytes).
-        let bytes_read = 60;
-        let unassigned_size = Self::SIZE - bytes_read;
-        let mut unassigned = vec![0u8; unassigned_size];
+        // Read unassigned bytes before the revision fields (3261-3500 = 240 bytes).
+        let mut unassigned = vec![0u8; Self::UNASSIGNED_PRE_REVISION_LEN];
         reader.read_exact(&mut unassigned)?;
 
+        let segy_revision = read_i16!(reader) as u16;
+        let fixed_length_trace_flag = read_i16!(reader);
+        let extended_textual_headers = read_i16!(reader);
+
+        // Read the remaining unassigned bytes (3507-3600 = 94 bytes).
+        let mut unassigned_post = vec![0u8; Self::SIZE - Self::REVISION_FIELDS_OFFSET - 6];
+        reader.read_exact(&mut unassigned_post)?;
+
+        // Rev 2 assigns meaning to some of the bytes earlier revisions leave
+        // unassigned; they stay in `unassigned` as raw bytes either way.
+        let rev2 = if segy_revision >= 0x0200 {
+            Rev2Fields::parse(&unassigned, &unassigned_post, endianness)
+        } else {
+            Rev2Fields::default()
+        };
+        unassigned.extend_from_slice(&unassigned_post);
+
         let byte_order = match endianness {
             Endianness::Big => ByteOrder::BigEndian,
             Endianness::Little => ByteOrder::LittleEndian,
@@ -389,20 +515,87 @@ impl BinaryHeader {
             measurement_system,
             impulse_polarity,
             vibratory_polarity,
+            segy_revision,
+            fixed_length_trace_flag,
+            extended_textual_headers,
+            extended_traces_per_ensemble: rev2.extended_traces_per_ensemble,
+            extended_samples_per_trace: rev2.extended_samples_per_trace,
+            extended_sample_interval: rev2.extended_sample_interval,
+            max_additional_trace_headers: rev2.max_additional_trace_headers,
+            time_basis_code: rev2.time_basis_code,
+            number_of_traces: rev2.number_of_traces,
+            byte_offset_of_first_trace: rev2.byte_offset_of_first_trace,
+            data_trailer_stanzas: rev2.data_trailer_stanzas,
             unassigned,
         })
     }
 
+    /// Check whether all traces share the binary header's sample count.
+    ///
+    /// The fixed length flag was introduced in Rev 1, so Rev 0 files (where the
+    /// bytes are unassigned and usually zero) are always treated as fixed length.
+    pub fn has_fixed_length_traces(&self) -> bool {
+        self.segy_revision < 0x0100 || self.fixed_length_trace_flag != 0
+    }
+
     /// Get bytes per sample based on the data format
     pub fn bytes_per_sample(&self) -> usize {
         self.data_sample_format.bytes_per_sample()
     }
 
+    /// Number of extra 240-byte trace header blocks after each standard one.
+    ///
+    /// Rev 2 only (bytes 3507-3510); earlier revisions and negative counts
+    /// yield zero.
+    pub fn num_extended_trace_headers(&self) -> usize {
+        self.max_additional_trace_headers
+            .and_then(|count| usize::try_from(count).ok())
+            .unwrap_or(0)
+    }
+
     /// Calculate the expected size of a trace data block in bytes
     ///
-    /// This is the trace header (240 bytes) plus the trace data
+    /// This is the trace header (240 bytes, plus 240 per extended header
+    /// block) plus the trace data
     pub fn trace_block_size(&self) -> usize {
-        240 + (self.samples_per_trace as usize * self.bytes_per_sample())
+        240 * (1 + self.num_extended_trace_headers())
+            + (self.samples_per_trace as usize * self.bytes_per_sample())
+    }
+}
+
+/// Rev 2 binary header fields, all `None` for earlier revisions.
+#[derive(Default)]
+struct Rev2Fields {
+    extended_traces_per_ensemble: Option<i32>,
+    extended_samples_per_trace: Option<i32>,
+    extended_sample_interval: Option<f64>,
+    max_additional_trace_headers: Option<i32>,
+    time_basis_code: Option<i16>,
+    number_of_traces: Option<u64>,
+    byte_offset_of_first_trace: Option<u64>,
+    data_trailer_stanzas: Option<i32>,
+}
+
+impl Rev2Fields {
+    /// Decode from the unassigned blocks at bytes 3261-3500 and 3507-3600.
+    fn parse(pre: &[u8], post: &[u8], endianness: Endianness) -> Self {
+        match endianness {
+            Endianness::Big => Self::parse_with::<BigEndian>(pre, post),
+            Endianness::Little => Self::parse_with::<LittleEndian>(pre, post),
+        }
+    }
+
+    fn parse_with<B: ByteOrderTrait>(pre: &[u8], post: &[u8]) -> Self {
+        Self {
+            extended_traces_per_ensemble: Some(B::read_i32(&pre[0..4])),
+            extended_samples_per_trace: Some(B::read_i32(&pre[8..12])),
+            extended_sample_interval: Some(B::read_f64(&pre[12..20])),
+            max_additional_trace_headers: Some(B::read_i32(&post[0..4])),
+            time_basis_code: Some(B::read_i16(&post[4..6])),
+            number_of_traces: Some(B::read_u64(&post[6..14])),
+            byte_offset_of_first_trace: Some(B::read_u64(&post[14..22])),
+            data_trailer_stanzas: Some(B::read_i32(&post[22..26])),
+        }
     }
 }
 
@@ -437,7 +630,18 @@ impl Default for BinaryHeader {
             measurement_system: MeasurementSystem::Meters,
             impulse_polarity: 0,
             vibratory_polarity: 0,
-            unassigned: vec![0u8; 340],
+            segy_revision: 0,
+            fixed_length_trace_flag: 0,
+            extended_textual_headers: 0,
+            extended_traces_per_ensemble: None,
+            extended_samples_per_trace: None,
+            extended_sample_interval: None,
+            max_additional_trace_headers: None,
+            time_basis_code: None,
+            number_of_traces: None,
+            byte_offset_of_first_trace: None,
+            data_trailer_stanzas: None,
+            unassigned: vec![0u8; 334],
         }
     }
 }
@@ -463,5 +667,76 @@ mod tests {
         };
 
         assert_eq!(header.trace_block_size(), 240 + 1000 * 4);
+
+        let extended = BinaryHeader {
+            max_additional_trace_headers: Some(2),
+            ..header
+        };
+        assert_eq!(extended.num_extended_trace_headers(), 2);
+        assert_eq!(extended.trace_block_size(), 3 * 240 + 1000 * 4);
+    }
+
+    fn rev2_header_bytes(revision: u16) -> Vec<u8> {
+        let mut bytes = vec![0u8; BinaryHeader::SIZE];
+        bytes[16..18].copy_from_slice(&2000i16.to_le_bytes());
+        bytes[20..22].copy_from_slice(&500i16.to_le_bytes());
+        bytes[24..26].copy_from_slice(&5i16.to_le_bytes());
+        bytes[68..72].copy_from_slice(&500i32.to_le_bytes());
+        bytes[72..80].copy_from_slice(&2000.0f64.to_le_bytes());
+        bytes[300..302].copy_from_slice(&revision.to_le_bytes());
+        bytes[306..310].copy_from_slice(&2i32.to_le_bytes());
+        bytes[312..320].copy_from_slice(&1234u64.to_le_bytes());
+        bytes[320..328].copy_from_slice(&9600u64.to_le_bytes());
+        bytes
+    }
+
+    #[test]
+    fn test_rev2_fields_parsed_with_detected_byte_order() {
+        let header = BinaryHeader::from_reader(rev2_header_bytes(0x0200).as_slice()).unwrap();
+
+        assert_eq!(header.byte_order, ByteOrder::LittleEndian);
+        assert_eq!(header.extended_samples_per_trace, Some(500));
+        assert_eq!(header.extended_sample_interval, Some(2000.0));
+        assert_eq!(header.max_additional_trace_headers, Some(2));
+        assert_eq!(header.number_of_traces, Some(1234));
+        assert_eq!(header.byte_offset_of_first_trace, Some(9600));
+        assert_eq!(header.unassigned.len(), 334);
+    }
+
+    #[test]
+    fn test_unassigned_blocks_split_at_revision_fields() {
+        let mut bytes = rev2_header_bytes(0x0100);
+        bytes[60] = 0xAA; // byte 3261
+        bytes[299] = 0xBB; // byte 3500
+        bytes[306] = 0xCC; // byte 3507
+        bytes[399] = 0xDD; // byte 3600
+        let header = BinaryHeader::from_reader(bytes.as_slice()).unwrap();
+
+        let pre = header.unassigned_pre_revision();
+        let post = header.unassigned_post_revision();
+        assert_eq!((pre.len(), post.len()), (240, 94));
+        assert_eq!((pre[0], pre[239]), (0xAA, 0xBB));
+        assert_eq!((post[0], post[93]), (0xCC, 0xDD));
+    }
+
+    #[test]
+    fn test_rev2_fields_absent_before_rev2() {
+        let header = BinaryHeader::from_reader(rev2_header_bytes(0x0100).as_slice()).unwrap();
+
+        assert_eq!(header.extended_samples_per_trace, None);
+        assert_eq!(header.byte_offset_of_first_trace, None);
+    }
+
+    #[test]
+    fn test_variable_length_flag_requires_rev1() {
+        let rev0 = BinaryHeader::default();
+        assert!(rev0.has_fixed_length_traces());
+
+        let rev1_variable = BinaryHeader {
+            segy_revision: 0x0100,
+            fixed_length_trace_flag: 0,
+            ..Default::default()
+        };
+        assert!(!rev1_variable.has_fixed_length_traces());
     }
 }
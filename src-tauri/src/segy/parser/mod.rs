@@ -20,5 +20,7 @@ pub use binary_header::{
     BinaryHeader, ByteOrder, DataSampleFormat, MeasurementSystem, TraceSortingCode,
 };
 pub use textual_header::TextualHeader;
-pub use trace::{CoordinateUnits, TraceBlock, TraceHeader, TraceIdentificationCode};
-pub use trace_data::{SampleFormat, TraceData};
+pub use trace::{
+    CoordinateUnits, RecordingTime, TimeBasis, TraceBlock, TraceHeader, TraceIdentificationCode,
+};
+pub use trace_data::{DownsampleMode, SampleFormat, TraceData};
@@ -7,7 +7,7 @@
 use serde::{Deserialize, Serialize};
 use std::io::{self, Read};
 
-use crate::segy::utils::{detect_text_encoding, text_to_ascii, TextEncoding};
+use crate::segy::utils::{ascii_lines, detect_text_encoding, text_to_ascii, TextEncoding};
 
 /// Textual header consisting of 3200 bytes of EBCDIC or ASCII card images
 ///
@@ -64,15 +64,22 @@ impl TextualHeader {
         // Detect encoding
         let encoding = detect_text_encoding(&data);
 
-        // Convert card images to ASCII lines
-        let lines = (0..Self::CARD_COUNT)
-            .map(|i| {
-                let start = i * Self::CARD_SIZE;
-                let end = start + Self::CARD_SIZE;
-                let card_bytes = &data[start..end];
-                text_to_ascii(card_bytes)
-            })
-            .collect();
+        // ASCII headers with embedded line terminators are reflowed by line;
+        // everything else is sliced into fixed-width card images.
+        let reflowed = match encoding {
+            TextEncoding::Ascii => ascii_lines(&data, Self::CARD_COUNT, Self::CARD_SIZE),
+            TextEncoding::Ebcdic => None,
+        };
+        let lines = reflowed.unwrap_or_else(|| {
+            (0..Self::CARD_COUNT)
+                .map(|i| {
+                    let start = i * Self::CARD_SIZE;
+                    let end = start + Self::CARD_SIZE;
+                    let card_bytes = &data[start..end];
+                    text_to_ascii(card_bytes)
+                })
+                .collect()
+        });
 
         Ok(Self {
             raw_data: data,
@@ -132,6 +139,21 @@ mod tests {
         assert_eq!(header.raw_data().len(), TextualHeader::SIZE);
     }
 
+    #[test]
+    fn test_ascii_crlf_lines_are_reflowed() {
+        let mut data = Vec::new();
+        for card in 1..=40 {
+            data.extend_from_slice(format!("C{:2} SHORT LINE\r\n", card).as_bytes());
+        }
+        data.resize(TextualHeader::SIZE, b' ');
+
+        let header = TextualHeader::new(data).unwrap();
+        assert_eq!(header.encoding(), TextEncoding::Ascii);
+        assert_eq!(header.lines.len(), TextualHeader::CARD_COUNT);
+        assert_eq!(header.lines[0], "C 1 SHORT LINE");
+        assert_eq!(header.lines[39], "C40 SHORT LINE");
+    }
+
     #[test]
     fn test_invalid_size() {
         let data = vec![0u8; 100];
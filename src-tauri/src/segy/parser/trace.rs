@@ -4,10 +4,11 @@
 
 use byteorder::ReadBytesExt;
 use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
 use std::io::{self, Read};
 
 use super::binary_header::ByteOrder;
-use super::trace_data::TraceData;
+use super::trace_data::{DownsampleMode, TraceData};
 
 /// Trace identification code
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
@@ -57,6 +58,37 @@ impl TraceIdentificationCode {
             _ => Err(format!("Invalid trace identification code: {}", code)),
         }
     }
+
+    /// Raw code as stored in bytes 29-30.
+    pub fn code(&self) -> i16 {
+        match *self {
+            Self::SeismicData => 1,
+            Self::Dead => 2,
+            Self::Dummy => 3,
+            Self::TimeBreak => 4,
+            Self::Uphole => 5,
+            Self::Sweep => 6,
+            Self::Timing => 7,
+            Self::WaterBreak => 8,
+            Self::Optional(code) => code,
+        }
+    }
+
+    /// Human-readable name for legends, e.g. `"Time break"` or
+    /// `"Optional use (12)"`.
+    pub fn label(&self) -> Cow<'static, str> {
+        Cow::Borrowed(match self {
+            Self::SeismicData => "Seismic data",
+            Self::Dead => "Dead",
+            Self::Dummy => "Dummy",
+            Self::TimeBreak => "Time break",
+            Self::Uphole => "Uphole",
+            Self::Sweep => "Sweep",
+            Self::Timing => "Timing",
+            Self::WaterBreak => "Water break",
+            Self::Optional(code) => return Cow::Owned(format!("Optional use ({})", code)),
+        })
+    }
 }
 
 /// Coordinate units
@@ -83,6 +115,89 @@ impl CoordinateUnits {
     }
 }
 
+/// Time basis of trace recording times
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TimeBasis {
+    /// Local time
+    Local,
+    /// Greenwich Mean Time
+    Gmt,
+    /// Other, described in the textual header
+    Other,
+    /// Coordinated Universal Time (Rev 2)
+    Utc,
+    /// GPS time (Rev 2)
+    Gps,
+    /// Unset or unrecognized code
+    Unknown,
+}
+
+impl TimeBasis {
+    /// Map a time basis code; unset and unrecognized codes become `Unknown`.
+    pub fn from_code(code: i16) -> Self {
+        match code {
+            1 => Self::Local,
+            2 => Self::Gmt,
+            3 => Self::Other,
+            4 => Self::Utc,
+            5 => Self::Gps,
+            _ => Self::Unknown,
+        }
+    }
+}
+
+/// Calendar date and time a trace was recorded
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RecordingTime {
+    /// Four-digit year
+    pub year: i16,
+    /// Month of year (1-12)
+    pub month: u8,
+    /// Day of month (1-31)
+    pub day: u8,
+    /// Day of year as recorded (1-366)
+    pub day_of_year: u16,
+    /// Hour of day (0-23)
+    pub hour: u8,
+    /// Minute of hour (0-59)
+    pub minute: u8,
+    /// Second of minute (0-59)
+    pub second: u8,
+    /// Time zone or clock the fields are expressed in
+    pub time_basis: TimeBasis,
+}
+
+/// Convert a day of year to month and day, or `None` if out of range.
+fn month_and_day(year: i16, day_of_year: u16) -> Option<(u8, u8)> {
+    let leap = (year % 4 == 0 && year % 100 != 0) || year % 400 == 0;
+    let month_lengths = [
+        31,
+        if leap { 29 } else { 28 },
+        31,
+        30,
+        31,
+        30,
+        31,
+        31,
+        30,
+        31,
+        30,
+        31,
+    ];
+
+    let mut remaining = day_of_year;
+    for (month, &length) in month_lengths.iter().enumerate() {
+        if remaining == 0 {
+            return None;
+        }
+        if remaining <= length {
+            return Some((month as u8 + 1, remaining as u8));
+        }
+        remaining -= length;
+    }
+    None
+}
+
 /// Trace header containing metadata for a single trace
 ///
 /// The trace header is 240 bytes and precedes the trace data samples.
@@ -281,9 +396,13 @@ pub struct TraceHeader {
     /// Second of minute (bytes 165-166)
     pub second: i16,
 
-    /// Time basis code: 1=local, 2=GMT, 3=other (bytes 167-168)
+    /// Time basis code: 1=local, 2=GMT, 3=other, 4=UTC, 5=GPS (bytes 167-168)
     pub time_basis_code: i16,
 
+    /// Recording time assembled from bytes 157-168, when set and valid
+    #[serde(default)]
+    pub recorded_at: Option<RecordingTime>,
+
     /// Trace weighting factor (bytes 169-170)
     pub trace_weighting_factor: i16,
 
@@ -423,7 +542,7 @@ impl TraceHeader {
         let mut unassigned = vec![0u8; unassigned_size];
         reader.read_exact(&mut unassigned)?;
 
-        Ok(Self {
+        let mut header = Self {
             trace_seq_line,
             trace_seq_reel,
             field_record_number,
@@ -489,6 +608,7 @@ impl TraceHeader {
             minute,
             second,
             time_basis_code,
+            recorded_at: None,
             trace_weighting_factor,
             geophone_group_num_roll_pos1,
             geophone_group_num_first_trace,
@@ -496,8 +616,131 @@ impl TraceHeader {
             gap_size,
             overtravel,
             unassigned,
+        };
+        header.recorded_at = header.recording_time();
+        Ok(header)
+    }
+
+    /// Assemble the recording date and time from the header fields.
+    ///
+    /// Returns `None` when the year or day of year is unset (zero) or any
+    /// field is out of range. Rev 0 allowed two-digit years, so years below
+    /// 100 are read as 1950-2049.
+    pub fn recording_time(&self) -> Option<RecordingTime> {
+        if self.year <= 0 || self.day_of_year <= 0 {
+            return None;
+        }
+
+        let year = match self.year {
+            0..=49 => 2000 + self.year,
+            50..=99 => 1900 + self.year,
+            year => year,
+        };
+        let day_of_year = u16::try_from(self.day_of_year).ok()?;
+        let (month, day) = month_and_day(year, day_of_year)?;
+        let hour = u8::try_from(self.hour).ok().filter(|&h| h < 24)?;
+        let minute = u8::try_from(self.minute).ok().filter(|&m| m < 60)?;
+        let second = u8::try_from(self.second).ok().filter(|&s| s < 60)?;
+
+        Some(RecordingTime {
+            year,
+            month,
+            day,
+            day_of_year,
+            hour,
+            minute,
+            second,
+            time_basis: TimeBasis::from_code(self.time_basis_code),
         })
     }
+
+    /// Integer value of the field named `key`, using the serialized field names.
+    ///
+    /// Enum fields yield their raw code. Returns `None` for unknown keys and
+    /// for the non-integer `recorded_at` and `unassigned` fields.
+    pub fn field_value(&self, key: &str) -> Option<i64> {
+        macro_rules! integer_fields {
+            ($($name:ident),* $(,)?) => {
+                match key {
+                    $(stringify!($name) => Some(self.$name as i64),)*
+                    "trace_id_code" => Some(self.trace_id_code.code() as i64),
+                    "coordinate_units" => Some(self.coordinate_units as i64),
+                    _ => None,
+                }
+            };
+        }
+        integer_fields!(
+            trace_seq_line,
+            trace_seq_reel,
+            field_record_number,
+            trace_number,
+            source_point_number,
+            cdp_ensemble_number,
+            trace_number_in_ensemble,
+            num_vert_summed,
+            num_horz_stacked,
+            data_use,
+            source_to_group_distance,
+            receiver_elevation,
+            surface_elevation_at_source,
+            source_depth,
+            datum_elevation_at_receiver,
+            datum_elevation_at_source,
+            water_depth_at_source,
+            water_depth_at_receiver,
+            elevation_scaler,
+            coordinate_scaler,
+            source_x,
+            source_y,
+            group_x,
+            group_y,
+            weathering_velocity,
+            subweathering_velocity,
+            uphole_time_at_source,
+            uphole_time_at_group,
+            source_static_correction,
+            group_static_correction,
+            total_static,
+            lag_time_a,
+            lag_time_b,
+            delay_recording_time,
+            mute_time_start,
+            mute_time_end,
+            num_samples,
+            sample_interval_us,
+            gain_type,
+            instrument_gain_constant,
+            instrument_initial_gain,
+            correlated,
+            sweep_freq_start,
+            sweep_freq_end,
+            sweep_length_ms,
+            sweep_type,
+            sweep_taper_start_ms,
+            sweep_taper_end_ms,
+            taper_type,
+            alias_filter_freq,
+            alias_filter_slope,
+            notch_filter_freq,
+            notch_filter_slope,
+            low_cut_freq,
+            high_cut_freq,
+            low_cut_slope,
+            high_cut_slope,
+            year,
+            day_of_year,
+            hour,
+            minute,
+            second,
+            time_basis_code,
+            trace_weighting_factor,
+            geophone_group_num_roll_pos1,
+            geophone_group_num_first_trace,
+            geophone_group_num_last_trace,
+            gap_size,
+            overtravel,
+        )
+    }
 }
 
 /// Complete trace block: header + data
@@ -508,12 +751,20 @@ pub struct TraceBlock {
 
     /// Trace data samples
     pub data: TraceData,
+
+    /// Raw bytes of any Rev 2 extended trace headers, 240 per block
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub extended_headers: Vec<u8>,
 }
 
 impl TraceBlock {
     /// Create a new trace block
     pub fn new(header: TraceHeader, data: TraceData) -> Self {
-        Self { header, data }
+        Self {
+            header,
+            data,
+            extended_headers: Vec::new(),
+        }
     }
 
     /// Parse a complete trace block from a reader
@@ -523,29 +774,134 @@ impl TraceBlock {
     /// * `reader` - Reader positioned at the start of a trace block
     /// * `sample_format` - The data sample format from the binary header
     /// * `num_samples` - Number of samples (can override header value)
-    /// * `byte_order` - Byte order for reading header values
+    /// * `extended_headers` - Extended header blocks following the trace header
+    /// * `byte_order` - Byte order of header values and samples
     pub fn from_reader<R: Read>(
         reader: &mut R,
         sample_format: super::binary_header::DataSampleFormat,
         num_samples: Option<i16>,
+        extended_headers: usize,
         byte_order: ByteOrder,
     ) -> io::Result<Self> {
         let header = TraceHeader::from_reader(&mut *reader, byte_order)?;
+        let extended_size = extended_headers
+            .checked_mul(TraceHeader::SIZE)
+            .ok_or_else(|| {
+                io::Error::new(io::ErrorKind::InvalidInput, "Too many extended headers")
+            })?;
+        let mut extended = vec![0u8; extended_size];
+        reader.read_exact(&mut extended)?;
         let samples = num_samples.unwrap_or(header.num_samples);
-        let data = TraceData::from_reader(&mut *reader, sample_format, samples as usize)?;
+        let data =
+            TraceData::from_reader(&mut *reader, sample_format, samples as usize, byte_order)?;
 
-        Ok(Self { header, data })
+        Ok(Self {
+            header,
+            data,
+            extended_headers: extended,
+        })
     }
 
     /// Downsample the trace to a maximum number of samples, updating the header.
-    pub fn downsample(mut self, max_samples: usize) -> Self {
+    pub fn downsample(mut self, max_samples: usize, mode: DownsampleMode) -> Self {
         if max_samples == 0 {
             return self;
         }
 
-        let data = self.data.downsample(max_samples);
+        let data = self.data.downsample(max_samples, mode);
         self.header.num_samples = data.len() as i16;
         self.data = data;
         self
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::segy::synthetic::make_trace_header;
+
+    fn header_with_time(year: i16, day_of_year: i16, hms: [i16; 3], basis: i16) -> TraceHeader {
+        let fields = [year, day_of_year, hms[0], hms[1], hms[2], basis];
+        make_trace_header(|bytes| {
+            for (i, value) in fields.iter().enumerate() {
+                bytes[156 + i * 2..158 + i * 2].copy_from_slice(&value.to_be_bytes());
+            }
+        })
+    }
+
+    #[test]
+    fn test_trace_id_code_round_trips_and_labels() {
+        for code in [1, 4, 8, 9, 32767] {
+            assert_eq!(
+                TraceIdentificationCode::from_code(code).unwrap().code(),
+                code
+            );
+        }
+        assert_eq!(TraceIdentificationCode::TimeBreak.label(), "Time break");
+        assert_eq!(
+            TraceIdentificationCode::Optional(12).label(),
+            "Optional use (12)"
+        );
+    }
+
+    #[test]
+    fn test_field_value_reads_named_fields() {
+        let header = make_trace_header(|bytes| {
+            bytes[20..24].copy_from_slice(&1234i32.to_be_bytes());
+            bytes[28..30].copy_from_slice(&4i16.to_be_bytes());
+            bytes[88..90].copy_from_slice(&2i16.to_be_bytes());
+            bytes[114..116].copy_from_slice(&(-1i16).to_be_bytes());
+        });
+
+        assert_eq!(header.field_value("cdp_ensemble_number"), Some(1234));
+        assert_eq!(header.field_value("trace_id_code"), Some(4));
+        assert_eq!(header.field_value("coordinate_units"), Some(2));
+        assert_eq!(header.field_value("num_samples"), Some(-1));
+        assert_eq!(header.field_value("recorded_at"), None);
+        assert_eq!(header.field_value("no_such_field"), None);
+    }
+
+    #[test]
+    fn test_recording_time_converts_day_of_year() {
+        let header = header_with_time(2024, 60, [13, 45, 30], 4);
+        let time = header.recorded_at.unwrap();
+
+        // 2024 is a leap year, so day 60 is February 29.
+        assert_eq!((time.year, time.month, time.day), (2024, 2, 29));
+        assert_eq!((time.hour, time.minute, time.second), (13, 45, 30));
+        assert_eq!(time.time_basis, TimeBasis::Utc);
+        assert_eq!(
+            header_with_time(2023, 60, [0, 0, 0], 1)
+                .recorded_at
+                .unwrap()
+                .month,
+            3
+        );
+    }
+
+    #[test]
+    fn test_recording_time_two_digit_years_and_invalid_fields() {
+        assert_eq!(
+            header_with_time(98, 1, [0, 0, 0], 0)
+                .recorded_at
+                .unwrap()
+                .year,
+            1998
+        );
+        assert_eq!(
+            header_with_time(5, 1, [0, 0, 0], 0)
+                .recorded_at
+                .unwrap()
+                .year,
+            2005
+        );
+
+        assert!(header_with_time(0, 0, [0, 0, 0], 0).recorded_at.is_none());
+        assert!(header_with_time(2023, 366, [0, 0, 0], 1)
+            .recorded_at
+            .is_none());
+        assert!(header_with_time(2023, 10, [24, 0, 0], 1)
+            .recorded_at
+            .is_none());
+    }
+}
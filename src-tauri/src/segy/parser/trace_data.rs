@@ -8,11 +8,12 @@
 //! - 32-bit IEEE floating point
 //! - 8-bit two's complement integer
 
-use byteorder::{BigEndian, ReadBytesExt};
 use serde::{Deserialize, Serialize};
 use std::io::{self, Read};
+use std::ops::Range;
 
-use super::binary_header::DataSampleFormat;
+use super::binary_header::{ByteOrder, DataSampleFormat};
+use super::trace::TraceHeader;
 
 /// Sample format enum for runtime format representation
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
@@ -38,6 +39,19 @@ impl From<DataSampleFormat> for SampleFormat {
     }
 }
 
+/// Strategy used to reduce a trace to a maximum number of samples.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum DownsampleMode {
+    /// Keep every Nth sample (fast, but aliases high frequencies)
+    #[default]
+    Stride,
+    /// Average each stride-length bucket
+    Mean,
+    /// Emit the minimum and maximum of each bucket, doubling the output length
+    MinMax,
+}
+
 /// Trace data samples in various formats
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum TraceData {
@@ -69,6 +83,7 @@ impl TraceData {
     /// * `reader` - Reader positioned at the start of trace data
     /// * `format` - The data sample format
     /// * `num_samples` - Number of samples to read
+    /// * `byte_order` - Byte order of multi-byte samples
     ///
     /// # Errors
     ///
@@ -77,26 +92,27 @@ impl TraceData {
         reader: &mut R,
         format: DataSampleFormat,
         num_samples: usize,
+        byte_order: ByteOrder,
     ) -> io::Result<Self> {
         match format {
             DataSampleFormat::IbmFloat32 => {
-                let samples = Self::read_ibm_float32(reader, num_samples)?;
+                let samples = Self::read_ibm_float32(reader, num_samples, byte_order)?;
                 Ok(Self::IbmFloat32(samples))
             }
             DataSampleFormat::Int32 => {
-                let samples = Self::read_int32(reader, num_samples)?;
+                let samples = Self::read_int32(reader, num_samples, byte_order)?;
                 Ok(Self::Int32(samples))
             }
             DataSampleFormat::Int16 => {
-                let samples = Self::read_int16(reader, num_samples)?;
+                let samples = Self::read_int16(reader, num_samples, byte_order)?;
                 Ok(Self::Int16(samples))
             }
             DataSampleFormat::FixedPointWithGain => {
-                let samples = Self::read_fixed_point_with_gain(reader, num_samples)?;
+                let samples = Self::read_fixed_point_with_gain(reader, num_samples, byte_order)?;
                 Ok(Self::FixedPointWithGain(samples))
             }
             DataSampleFormat::IeeeFloat32 => {
-                let samples = Self::read_ieee_float32(reader, num_samples)?;
+                let samples = Self::read_ieee_float32(reader, num_samples, byte_order)?;
                 Ok(Self::IeeeFloat32(samples))
             }
             DataSampleFormat::Int8 => {
@@ -115,8 +131,15 @@ impl TraceData {
     ///
     /// Value = S × 16^(C-64) × F
     ///
+    /// The word is assembled in the file's byte order before conversion, so
+    /// little-endian files hold the same IBM bits byte-swapped.
+    ///
     /// Optimized with batch reading and vectorized conversion
-    fn read_ibm_float32<R: Read>(reader: &mut R, count: usize) -> io::Result<Vec<f32>> {
+    fn read_ibm_float32<R: Read>(
+        reader: &mut R,
+        count: usize,
+        byte_order: ByteOrder,
+    ) -> io::Result<Vec<f32>> {
         // Batch read all bytes at once (major optimization)
         let byte_count = count * 4;
         let mut raw_bytes = vec![0u8; byte_count];
@@ -125,10 +148,7 @@ impl TraceData {
         // Convert using iterator - compiler optimizes this well
         let samples = raw_bytes
             .chunks_exact(4)
-            .map(|chunk| {
-                let raw = u32::from_be_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]);
-                Self::ibm_to_ieee_fast(raw)
-            })
+            .map(|chunk| Self::ibm_to_ieee_fast(word_u32(chunk, byte_order)))
             .collect();
 
         Ok(samples)
@@ -203,42 +223,57 @@ impl TraceData {
     }
 
     /// Read 32-bit two's complement integer samples (optimized with batch read)
-    fn read_int32<R: Read>(reader: &mut R, count: usize) -> io::Result<Vec<i32>> {
+    fn read_int32<R: Read>(
+        reader: &mut R,
+        count: usize,
+        byte_order: ByteOrder,
+    ) -> io::Result<Vec<i32>> {
         let byte_count = count * 4;
         let mut raw_bytes = vec![0u8; byte_count];
         reader.read_exact(&mut raw_bytes)?;
 
         let samples = raw_bytes
             .chunks_exact(4)
-            .map(|chunk| i32::from_be_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]))
+            .map(|chunk| word_u32(chunk, byte_order) as i32)
             .collect();
 
         Ok(samples)
     }
 
     /// Read 16-bit two's complement integer samples (optimized with batch read)
-    fn read_int16<R: Read>(reader: &mut R, count: usize) -> io::Result<Vec<i16>> {
+    fn read_int16<R: Read>(
+        reader: &mut R,
+        count: usize,
+        byte_order: ByteOrder,
+    ) -> io::Result<Vec<i16>> {
         let byte_count = count * 2;
         let mut raw_bytes = vec![0u8; byte_count];
         reader.read_exact(&mut raw_bytes)?;
 
         let samples = raw_bytes
             .chunks_exact(2)
-            .map(|chunk| i16::from_be_bytes([chunk[0], chunk[1]]))
+            .map(|chunk| match byte_order {
+                ByteOrder::BigEndian => i16::from_be_bytes([chunk[0], chunk[1]]),
+                ByteOrder::LittleEndian => i16::from_le_bytes([chunk[0], chunk[1]]),
+            })
             .collect();
 
         Ok(samples)
     }
 
     /// Read IEEE 32-bit floating point samples (optimized with batch read)
-    fn read_ieee_float32<R: Read>(reader: &mut R, count: usize) -> io::Result<Vec<f32>> {
+    fn read_ieee_float32<R: Read>(
+        reader: &mut R,
+        count: usize,
+        byte_order: ByteOrder,
+    ) -> io::Result<Vec<f32>> {
         let byte_count = count * 4;
         let mut raw_bytes = vec![0u8; byte_count];
         reader.read_exact(&mut raw_bytes)?;
 
         let samples = raw_bytes
             .chunks_exact(4)
-            .map(|chunk| f32::from_be_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]))
+            .map(|chunk| f32::from_bits(word_u32(chunk, byte_order)))
             .collect();
 
         Ok(samples)
@@ -257,23 +292,27 @@ impl TraceData {
 
     /// Read 32-bit fixed point with gain samples
     ///
-    /// Format (4 bytes):
+    /// Format (4-byte word, most significant byte first):
     /// - Byte 1: all zeros
     /// - Byte 2: gain code (8 bits, 2^0 to 2^7)
     /// - Bytes 3-4: 16-bit two's complement data
+    ///
+    /// Little-endian files store the whole word byte-swapped.
     fn read_fixed_point_with_gain<R: Read>(
         reader: &mut R,
         count: usize,
+        byte_order: ByteOrder,
     ) -> io::Result<Vec<(u8, i16)>> {
-        let mut samples = Vec::with_capacity(count);
-
-        for _ in 0..count {
-            let _zeros = reader.read_u8()?; // First byte (should be zero)
-            let gain = reader.read_u8()?; // Gain code
-            let value = reader.read_i16::<BigEndian>()?; // Data value
+        let mut raw_bytes = vec![0u8; count * 4];
+        reader.read_exact(&mut raw_bytes)?;
 
-            samples.push((gain, value));
-        }
+        let samples = raw_bytes
+            .chunks_exact(4)
+            .map(|chunk| {
+                let word = word_u32(chunk, byte_order);
+                ((word >> 16) as u8, word as u16 as i16)
+            })
+            .collect();
 
         Ok(samples)
     }
@@ -295,45 +334,239 @@ impl TraceData {
         self.len() == 0
     }
 
+    /// Copy the samples in `range`, keeping the sample format.
+    ///
+    /// Panics if the range is out of bounds; callers validate it first.
+    pub fn window(&self, range: Range<usize>) -> Self {
+        match self {
+            Self::IbmFloat32(v) => Self::IbmFloat32(v[range].to_vec()),
+            Self::Int32(v) => Self::Int32(v[range].to_vec()),
+            Self::Int16(v) => Self::Int16(v[range].to_vec()),
+            Self::FixedPointWithGain(v) => Self::FixedPointWithGain(v[range].to_vec()),
+            Self::IeeeFloat32(v) => Self::IeeeFloat32(v[range].to_vec()),
+            Self::Int8(v) => Self::Int8(v[range].to_vec()),
+        }
+    }
+
+    /// Convert samples to an owned `Vec<f32>`.
+    ///
+    /// Integer formats are cast directly; fixed-point samples are scaled by
+    /// `2^gain`. This is the single conversion shared by rendering, analysis,
+    /// and the command layer.
+    pub fn to_f32_vec(&self) -> Vec<f32> {
+        match self {
+            Self::IbmFloat32(samples) | Self::IeeeFloat32(samples) => samples.clone(),
+            Self::Int32(samples) => samples.iter().map(|&v| v.to_f32()).collect(),
+            Self::Int16(samples) => samples.iter().map(|&v| v.to_f32()).collect(),
+            Self::FixedPointWithGain(samples) => samples.iter().map(|&v| v.to_f32()).collect(),
+            Self::Int8(samples) => samples.iter().map(|&v| v.to_f32()).collect(),
+        }
+    }
+
+    /// Replace the contents of `out` with the samples converted to f32,
+    /// reusing its allocation.
+    pub fn copy_f32_into(&self, out: &mut Vec<f32>) {
+        out.clear();
+        match self {
+            Self::IbmFloat32(samples) | Self::IeeeFloat32(samples) => {
+                out.extend_from_slice(samples)
+            }
+            Self::Int32(samples) => out.extend(samples.iter().map(|&v| v.to_f32())),
+            Self::Int16(samples) => out.extend(samples.iter().map(|&v| v.to_f32())),
+            Self::FixedPointWithGain(samples) => out.extend(samples.iter().map(|&v| v.to_f32())),
+            Self::Int8(samples) => out.extend(samples.iter().map(|&v| v.to_f32())),
+        }
+    }
+
+    /// Convert samples to f32, applying the trace weighting factor when a
+    /// header is supplied.
+    ///
+    /// SEG-Y defines the weighting factor (trace header bytes 169-170) as
+    /// `2^-N`, so a factor of 2 scales every sample by 1/4. Without a header
+    /// this is identical to `to_f32_vec`.
+    pub fn to_f32_vec_weighted(&self, header: Option<&TraceHeader>) -> Vec<f32> {
+        let mut samples = self.to_f32_vec();
+        if let Some(header) = header.filter(|header| header.trace_weighting_factor != 0) {
+            let scale = 2f32.powi(-i32::from(header.trace_weighting_factor));
+            samples.iter_mut().for_each(|v| *v *= scale);
+        }
+        samples
+    }
+
     /// Downsample to a maximum number of samples, keeping relative spacing.
-    pub fn downsample(self, max_samples: usize) -> Self {
+    ///
+    /// `Mean` and `MinMax` compute in f32 and round back for integer
+    /// variants. `MinMax` returns up to twice `max_samples` values.
+    pub fn downsample(self, max_samples: usize, mode: DownsampleMode) -> Self {
         if max_samples == 0 {
             return self;
         }
 
         match self {
-            Self::IbmFloat32(samples) => Self::IbmFloat32(downsample_vec(samples, max_samples)),
-            Self::Int32(samples) => Self::Int32(downsample_vec(samples, max_samples)),
-            Self::Int16(samples) => Self::Int16(downsample_vec(samples, max_samples)),
+            Self::IbmFloat32(samples) => {
+                Self::IbmFloat32(downsample_vec(samples, max_samples, mode))
+            }
+            Self::Int32(samples) => Self::Int32(downsample_vec(samples, max_samples, mode)),
+            Self::Int16(samples) => Self::Int16(downsample_vec(samples, max_samples, mode)),
             Self::FixedPointWithGain(samples) => {
-                Self::FixedPointWithGain(downsample_vec(samples, max_samples))
+                Self::FixedPointWithGain(downsample_vec(samples, max_samples, mode))
             }
-            Self::IeeeFloat32(samples) => Self::IeeeFloat32(downsample_vec(samples, max_samples)),
-            Self::Int8(samples) => Self::Int8(downsample_vec(samples, max_samples)),
+            Self::IeeeFloat32(samples) => {
+                Self::IeeeFloat32(downsample_vec(samples, max_samples, mode))
+            }
+            Self::Int8(samples) => Self::Int8(downsample_vec(samples, max_samples, mode)),
         }
     }
+
+    /// Reduce the trace to per-bucket min/max pairs for envelope display.
+    ///
+    /// Returns `2 * target_pixels` values alternating min and max, one pair
+    /// per output pixel row. Traces shorter than `target_pixels` yield one
+    /// pair per sample instead. NaN samples are skipped, and a bucket of only
+    /// NaN yields a NaN pair.
+    pub fn downsample_minmax(&self, target_pixels: usize) -> Vec<f32> {
+        match self {
+            Self::IbmFloat32(samples) | Self::IeeeFloat32(samples) => {
+                minmax_buckets(samples, target_pixels)
+            }
+            Self::Int32(samples) => minmax_buckets(samples, target_pixels),
+            Self::Int16(samples) => minmax_buckets(samples, target_pixels),
+            Self::FixedPointWithGain(samples) => minmax_buckets(samples, target_pixels),
+            Self::Int8(samples) => minmax_buckets(samples, target_pixels),
+        }
+    }
+}
+
+/// Sample types that can be averaged or compared in f32 space.
+trait DownsampleSample: Copy {
+    fn to_f32(self) -> f32;
+    /// Convert back from f32, using `like` for any per-sample metadata.
+    fn from_f32(value: f32, like: Self) -> Self;
+}
+
+impl DownsampleSample for f32 {
+    fn to_f32(self) -> f32 {
+        self
+    }
+
+    fn from_f32(value: f32, _like: Self) -> Self {
+        value
+    }
+}
+
+macro_rules! impl_int_downsample_sample {
+    ($($ty:ty),*) => {
+        $(impl DownsampleSample for $ty {
+            fn to_f32(self) -> f32 {
+                self as f32
+            }
+
+            fn from_f32(value: f32, _like: Self) -> Self {
+                // `as` saturates at the type bounds.
+                value.round() as $ty
+            }
+        })*
+    };
+}
+
+impl_int_downsample_sample!(i32, i16, i8);
+
+impl DownsampleSample for (u8, i16) {
+    fn to_f32(self) -> f32 {
+        (self.1 as f32) * 2.0_f32.powi(self.0 as i32)
+    }
+
+    fn from_f32(value: f32, like: Self) -> Self {
+        let gain = like.0;
+        (gain, (value / 2.0_f32.powi(gain as i32)).round() as i16)
+    }
 }
 
-/// Downsample a vector using a fixed stride derived from the target length.
-fn downsample_vec<T>(samples: Vec<T>, max_samples: usize) -> Vec<T> {
+/// Downsample a vector using buckets of a fixed stride derived from the target length.
+fn downsample_vec<T: DownsampleSample>(
+    samples: Vec<T>,
+    max_samples: usize,
+    mode: DownsampleMode,
+) -> Vec<T> {
     let len = samples.len();
     if len <= max_samples {
         return samples;
     }
 
     let stride = len.div_ceil(max_samples);
-    let mut downsampled = Vec::with_capacity(len.div_ceil(stride));
-    for (idx, sample) in samples.into_iter().enumerate() {
-        if idx % stride == 0 {
-            downsampled.push(sample);
+    match mode {
+        DownsampleMode::Stride => samples.into_iter().step_by(stride).collect(),
+        DownsampleMode::Mean => samples
+            .chunks(stride)
+            .map(|bucket| {
+                let sum: f32 = bucket.iter().map(|s| s.to_f32()).sum();
+                T::from_f32(sum / bucket.len() as f32, bucket[0])
+            })
+            .collect(),
+        DownsampleMode::MinMax => {
+            let mut downsampled = Vec::with_capacity(len.div_ceil(stride) * 2);
+            for bucket in samples.chunks(stride) {
+                let mut min = bucket[0];
+                let mut max = bucket[0];
+                for &sample in &bucket[1..] {
+                    if sample.to_f32() < min.to_f32() {
+                        min = sample;
+                    }
+                    if sample.to_f32() > max.to_f32() {
+                        max = sample;
+                    }
+                }
+                downsampled.push(min);
+                downsampled.push(max);
+            }
+            downsampled
+        }
+    }
+}
+
+/// Split samples into `buckets` near-equal ranges and emit each range's min and max.
+fn minmax_buckets<T: DownsampleSample>(samples: &[T], buckets: usize) -> Vec<f32> {
+    let len = samples.len();
+    let buckets = buckets.min(len);
+    let mut envelope = Vec::with_capacity(buckets * 2);
+
+    for bucket in 0..buckets {
+        let start = bucket * len / buckets;
+        let end = (bucket + 1) * len / buckets;
+        let (min, max) = samples[start..end].iter().fold(
+            (f32::INFINITY, f32::NEG_INFINITY),
+            |(min, max), sample| {
+                let value = sample.to_f32();
+                (min.min(value), max.max(value))
+            },
+        );
+        // A bucket of only NaN samples has no extremes.
+        if min > max {
+            envelope.extend([f32::NAN; 2]);
+            continue;
         }
+        envelope.push(min);
+        envelope.push(max);
+    }
+
+    envelope
+}
+
+/// Assemble a 4-byte sample word in the file's byte order.
+#[inline(always)]
+fn word_u32(chunk: &[u8], byte_order: ByteOrder) -> u32 {
+    let bytes = [chunk[0], chunk[1], chunk[2], chunk[3]];
+    match byte_order {
+        ByteOrder::BigEndian => u32::from_be_bytes(bytes),
+        ByteOrder::LittleEndian => u32::from_le_bytes(bytes),
     }
-    downsampled
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::segy::synthetic::make_trace_header;
+    use crate::segy::ByteOrder;
 
     #[test]
     fn test_ibm_float_zero() {
@@ -350,6 +583,49 @@ mod tests {
         assert!(result.is_finite());
     }
 
+    #[test]
+    fn test_little_endian_ibm_float_matches_big_endian() {
+        let words = [0x4264_0000u32, 0xC264_0000];
+        let big_endian: Vec<u8> = words.iter().flat_map(|w| w.to_be_bytes()).collect();
+        let mut swapped = big_endian.clone();
+        swapped.chunks_exact_mut(4).for_each(<[u8]>::reverse);
+
+        let decode = |bytes: &[u8], byte_order| {
+            TraceData::from_reader(&mut &bytes[..], DataSampleFormat::IbmFloat32, 2, byte_order)
+                .unwrap()
+                .to_f32_vec()
+        };
+        let expected = words.map(TraceData::ibm_to_ieee);
+        assert!(expected[0] > 0.0 && expected[1] == -expected[0]);
+        assert_eq!(decode(&big_endian, ByteOrder::BigEndian), expected);
+        assert_eq!(decode(&swapped, ByteOrder::LittleEndian), expected);
+    }
+
+    #[test]
+    fn test_little_endian_samples_round_trip() {
+        let values = [1.5f32, -0.25, 3.0e6, f32::MIN_POSITIVE];
+        let bytes: Vec<u8> = values.iter().flat_map(|v| v.to_le_bytes()).collect();
+        let data = TraceData::from_reader(
+            &mut bytes.as_slice(),
+            DataSampleFormat::IeeeFloat32,
+            values.len(),
+            ByteOrder::LittleEndian,
+        )
+        .unwrap();
+        assert_eq!(data.to_f32_vec(), values);
+
+        // Fixed point words are byte-swapped as a whole: gain 3, value -2.
+        let word = (3u32 << 16) | u32::from(-2i16 as u16);
+        let data = TraceData::from_reader(
+            &mut word.to_le_bytes().as_slice(),
+            DataSampleFormat::FixedPointWithGain,
+            1,
+            ByteOrder::LittleEndian,
+        )
+        .unwrap();
+        assert!(matches!(data, TraceData::FixedPointWithGain(ref s) if s[..] == [(3, -2)]));
+    }
+
     #[test]
     fn test_trace_data_len() {
         let data = TraceData::Int16(vec![1, 2, 3, 4, 5]);
@@ -357,10 +633,46 @@ mod tests {
         assert!(!data.is_empty());
     }
 
+    #[test]
+    fn test_to_f32_vec_converts_every_variant() {
+        assert_eq!(
+            TraceData::IbmFloat32(vec![1.5, -2.0]).to_f32_vec(),
+            vec![1.5, -2.0]
+        );
+        assert_eq!(TraceData::IeeeFloat32(vec![0.25]).to_f32_vec(), vec![0.25]);
+        assert_eq!(
+            TraceData::Int32(vec![-70_000, 3]).to_f32_vec(),
+            vec![-70_000.0, 3.0]
+        );
+        assert_eq!(
+            TraceData::Int16(vec![-300, 7]).to_f32_vec(),
+            vec![-300.0, 7.0]
+        );
+        assert_eq!(
+            TraceData::Int8(vec![-128, 127]).to_f32_vec(),
+            vec![-128.0, 127.0]
+        );
+        // Gain code 3 scales by 2^3; gain 0 leaves the value as-is.
+        assert_eq!(
+            TraceData::FixedPointWithGain(vec![(3, -5), (0, 11)]).to_f32_vec(),
+            vec![-40.0, 11.0]
+        );
+    }
+
+    #[test]
+    fn test_weighting_factor_scales_by_inverse_power_of_two() {
+        let header =
+            make_trace_header(|bytes| bytes[168..170].copy_from_slice(&2i16.to_be_bytes()));
+        let data = TraceData::Int16(vec![8, -4]);
+
+        assert_eq!(data.to_f32_vec_weighted(Some(&header)), vec![2.0, -1.0]);
+        assert_eq!(data.to_f32_vec_weighted(None), data.to_f32_vec());
+    }
+
     #[test]
     fn test_trace_data_downsample() {
         let data = TraceData::Int16(vec![1, 2, 3, 4, 5, 6, 7, 8, 9, 10]);
-        let downsampled = data.downsample(4);
+        let downsampled = data.downsample(4, DownsampleMode::Stride);
         match downsampled {
             TraceData::Int16(samples) => {
                 assert_eq!(samples, vec![1, 4, 7, 10]);
@@ -368,4 +680,39 @@ mod tests {
             _ => panic!("Unexpected trace data variant"),
         }
     }
+
+    #[test]
+    fn test_minmax_envelope_preserves_high_frequency_amplitude() {
+        // Period of four samples: stride sampling lands on the zero crossings.
+        let sine: Vec<f32> = (0..10_000)
+            .map(|i| (i as f32 * std::f32::consts::FRAC_PI_2).sin())
+            .collect();
+        let data = TraceData::IeeeFloat32(sine);
+
+        let envelope = data.downsample_minmax(100);
+        assert_eq!(envelope.len(), 200);
+        assert!(envelope
+            .chunks(2)
+            .all(|pair| pair[0] < -0.99 && pair[1] > 0.99));
+
+        match data.downsample(100, DownsampleMode::Stride) {
+            TraceData::IeeeFloat32(samples) => {
+                assert!(samples.iter().all(|v| v.abs() < 0.01));
+            }
+            _ => panic!("Unexpected trace data variant"),
+        }
+    }
+
+    #[test]
+    fn test_trace_data_downsample_mean_and_minmax() {
+        let data = TraceData::Int16(vec![1, 2, 3, 4, 5, 6, 7, 8, 9, 10]);
+        match data.clone().downsample(4, DownsampleMode::Mean) {
+            TraceData::Int16(samples) => assert_eq!(samples, vec![2, 5, 8, 10]),
+            _ => panic!("Unexpected trace data variant"),
+        }
+        match data.downsample(4, DownsampleMode::MinMax) {
+            TraceData::Int16(samples) => assert_eq!(samples, vec![1, 3, 4, 6, 7, 9, 10, 10]),
+            _ => panic!("Unexpected trace data variant"),
+        }
+    }
 }
@@ -1,32 +1,92 @@
 //! SEG-Y reader implementation with memory-mapped IO.
 //!
 //! `SegyReader` owns the memory map and provides safe, validated access to
-//! trace headers and samples. `SegyReaderState` caches the latest reader for
-//! Tauri commands.
+//! trace headers and samples. Decoded trace blocks are kept in a bounded LRU
+//! cache so repeated window requests skip re-decoding. `SegyReaderState`
+//! keeps the most recently used readers open for Tauri commands.
 
 use crate::error::AppError;
 use crate::segy::io;
-use crate::segy::{BinaryHeader, SegyData, SegyFileConfig, TextualHeader, TraceBlock, TraceData};
+use crate::segy::trace_cache::{TraceCache, DEFAULT_TRACE_CACHE_SAMPLES};
+use crate::segy::trace_store::TraceStore;
+use crate::segy::{
+    analysis::HistogramResult, constants, header_spec, BinaryHeader, ByteOrder, ConsistencyWarning,
+    DataSampleFormat, DecodedField, DownsampleMode, FieldDiff, FileSummary, HeaderFieldSpec,
+    RawBinaryHeader, SampleFormatSuggestion, ScanProgress, SegyData, SegyFileConfig,
+    SegyFormatSpec, TextualHeader, TraceBlock, TraceData, TraceHeader, TRACE_HEADER_SIZE,
+};
+use rayon::prelude::*;
+use std::borrow::Cow;
+use std::collections::HashMap;
 use std::fs::File;
-use std::sync::Arc;
+use std::io::{BufWriter, Write};
+use std::ops::{ControlFlow, Range};
+use std::path::Path;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard};
 use tokio::sync::RwLock;
 
-/// Memory-mapped SEG-Y reader with cached headers and configuration.
+/// Number of leading trace headers compared against the binary header.
+const CONSISTENCY_CHECK_TRACES: usize = 100;
+
+/// Traces decoded per batch by `export_samples_npy` and sample scans.
+const SAMPLE_CHUNK_TRACES: usize = 1024;
+
+/// Traces scanned between progress reports in full-range scans.
+pub const SCAN_PROGRESS_INTERVAL: usize = 4096;
+
+/// Callback receiving `ScanProgress` updates from long scans.
+pub type ProgressCallback<'a> = &'a (dyn Fn(ScanProgress) + Sync);
+
+/// Traces sampled, evenly spread through the file, by `detect_sample_format`.
+const FORMAT_DETECTION_TRACES: usize = 8;
+
+/// Fewest non-zero samples `detect_sample_format` needs to suggest a change.
+const FORMAT_DETECTION_MIN_SAMPLES: usize = 64;
+
+/// Magnitudes outside `1/limit..limit` are implausible for seismic samples.
+const PLAUSIBLE_MAGNITUDE: f32 = 1e15;
+
+/// Largest byte range `read_bytes` returns in one call (64 KiB).
+pub const MAX_READ_BYTES: usize = 64 * 1024;
+
+/// Most samples a single trace range request may decode (about 2 GB of f32).
+pub const MAX_TOTAL_SAMPLES: usize = 500_000_000;
+
+/// Memory-mapped (or in-memory) SEG-Y reader with cached headers and configuration.
 pub struct SegyReader {
     file_path: String,
     file_size: u64,
     textual_header: TextualHeader,
     binary_header: BinaryHeader,
     total_traces: Option<usize>,
+    trailing_bytes: u64,
     config: SegyFileConfig,
-    mmap: memmap2::Mmap,
+    backing: TraceStore,
+    // Per-trace byte offsets, present only for variable-length trace files.
+    trace_offsets: Option<Vec<usize>>,
+    trace_cache: Mutex<TraceCache>,
+    // Trace header fields remapped for this session (see `set_header_overrides`).
+    header_overrides: Mutex<Vec<HeaderFieldSpec>>,
     // Keep the file handle alive for the mmap lifetime (notably on Windows).
-    _file: File,
+    _file: Option<File>,
 }
 
 impl SegyReader {
     /// Open and parse a SEG-Y file from disk.
-    pub fn open(file_path: &str) -> Result<Self, AppError> {
+    ///
+    /// `cache_sample_budget` bounds the decoded trace cache by total sample
+    /// count; pass zero to disable caching.
+    ///
+    /// `override_config` replaces the configuration normally derived from the
+    /// binary header, so traces can be read from files whose header declares
+    /// the wrong sample count, format, or byte order. The textual and binary
+    /// headers are still read for display and must parse.
+    pub fn open(
+        file_path: &str,
+        cache_sample_budget: usize,
+        override_config: Option<SegyFileConfig>,
+    ) -> Result<Self, AppError> {
         io::validate_file_path(file_path)?;
 
         let mut file = File::open(file_path).map_err(|e| AppError::IoError {
@@ -34,40 +94,150 @@ impl SegyReader {
         })?;
 
         let header_bundle = io::read_headers(&mut file)?;
-        let config = SegyFileConfig::from_binary_header(&header_bundle.binary_header)?;
+        // Mapping fails on some network filesystems; read through the file
+        // handle there instead of refusing to open.
+        let (backing, file) = match io::map_file(&file) {
+            Ok(mmap) => {
+                log::info!("Opened '{}' with memory-mapped access", file_path);
+                // The mmap lifetime is tied to the File via the _file field.
+                (TraceStore::Mmap(mmap), Some(file))
+            }
+            Err(err) => {
+                log::warn!(
+                    "Memory-mapping '{}' failed ({}); falling back to seek-and-read access",
+                    file_path,
+                    err
+                );
+                (TraceStore::file(file, header_bundle.file_size), None)
+            }
+        };
 
-        let trace_block_size = config.trace_block_size().ok();
-        let total_traces = trace_block_size
-            .and_then(|size| io::compute_total_traces(header_bundle.file_size, size));
-
-        // Safety: Memory mapping is safe here because:
-        // 1. We hold an exclusive File handle that prevents external modification
-        // 2. The file is opened in read-only mode (no writes from this process)
-        // 3. The mmap lifetime is tied to the File via the _file field
-        // 4. We validate all slice accesses before dereferencing mapped memory
-        let mmap = unsafe { memmap2::Mmap::map(&file) }.map_err(|e| AppError::IoError {
-            message: format!("Failed to memory-map file: {}", e),
+        Self::from_parts(
+            file_path.to_string(),
+            header_bundle,
+            backing,
+            file,
+            cache_sample_budget,
+            override_config,
+        )
+    }
+
+    /// Parse a SEG-Y file held entirely in memory.
+    ///
+    /// The buffer is owned by the reader and traces are sliced from it
+    /// directly, so no file path is involved; `file_path()` returns an empty
+    /// string. Uses the default trace cache budget.
+    pub fn from_bytes(data: Vec<u8>) -> Result<Self, AppError> {
+        let header_bundle = io::parse_headers(&mut data.as_slice(), data.len() as u64)?;
+        Self::from_parts(
+            String::new(),
+            header_bundle,
+            TraceStore::Memory(data),
+            None,
+            DEFAULT_TRACE_CACHE_SAMPLES,
+            None,
+        )
+    }
+
+    /// Count the traces in a file from its headers alone.
+    ///
+    /// Reads only the textual and binary headers (no mmap, no trace scan), so
+    /// it stays cheap for multi-gigabyte files; as when opening, a zero
+    /// binary header sample count falls back to the first trace header.
+    /// Returns `None` when the trace block size cannot be determined, and
+    /// for variable-length trace files whose count needs a full scan.
+    pub fn count_traces(file_path: &str) -> Result<Option<usize>, AppError> {
+        io::validate_file_path(file_path)?;
+
+        let mut file = File::open(file_path).map_err(|e| AppError::IoError {
+            message: format!("Failed to open file '{}': {}", file_path, e),
         })?;
+        let header_bundle = io::read_headers(&mut file)?;
+        let mut config = SegyFileConfig::from_binary_header(&header_bundle.binary_header)?;
+        if !config.fixed_length_traces {
+            return Ok(None);
+        }
+
+        let store = TraceStore::file(file, header_bundle.file_size);
+        config.data_offset = io::first_trace_offset(&store, &header_bundle.binary_header)?;
+        infer_samples_per_trace(&mut config, &store)?;
+        Ok(config.trace_block_size().ok().and_then(|size| {
+            io::compute_total_traces(header_bundle.file_size, config.data_offset, size)
+        }))
+    }
+
+    /// Derive the file configuration and trace layout over parsed headers.
+    fn from_parts(
+        file_path: String,
+        header_bundle: io::HeaderBundle,
+        backing: TraceStore,
+        file: Option<File>,
+        cache_sample_budget: usize,
+        override_config: Option<SegyFileConfig>,
+    ) -> Result<Self, AppError> {
+        let overridden = override_config.is_some();
+        let mut config = match override_config {
+            Some(config) => {
+                config.data_sample_format_parsed()?;
+                config
+            }
+            None => SegyFileConfig::from_binary_header(&header_bundle.binary_header)?,
+        };
+        config.data_offset = io::first_trace_offset(&backing, &header_bundle.binary_header)?;
+        config.extended_trace_headers = header_bundle.binary_header.num_extended_trace_headers();
+
+        if !overridden {
+            infer_samples_per_trace(&mut config, &backing)?;
+        }
+
+        let trace_block_size = config.trace_block_size().ok();
+        let total_traces = trace_block_size.and_then(|size| {
+            io::compute_total_traces(header_bundle.file_size, config.data_offset, size)
+        });
+        let trailing_bytes = trace_block_size
+            .map(|size| {
+                io::compute_trailing_bytes(header_bundle.file_size, config.data_offset, size)
+            })
+            .unwrap_or(0);
 
-        Ok(Self {
-            file_path: file_path.to_string(),
+        let mut reader = Self {
+            file_path,
             file_size: header_bundle.file_size,
             textual_header: header_bundle.textual_header,
             binary_header: header_bundle.binary_header,
             total_traces,
+            trailing_bytes,
             config,
-            mmap,
+            backing,
+            trace_offsets: None,
+            trace_cache: Mutex::new(TraceCache::new(cache_sample_budget)),
+            header_overrides: Mutex::new(Vec::new()),
             _file: file,
-        })
+        };
+
+        if !reader.config.fixed_length_traces {
+            let offsets = reader.build_trace_offset_table()?;
+            reader.total_traces = Some(offsets.len() - 1);
+            reader.trailing_bytes = reader.file_size - offsets[offsets.len() - 1] as u64;
+            reader.trace_offsets = Some(offsets);
+        }
+
+        Ok(reader)
     }
 
     /// Open a SEG-Y file on a blocking thread to avoid stalling the async runtime.
-    pub async fn open_async(file_path: String) -> Result<Self, AppError> {
-        tokio::task::spawn_blocking(move || Self::open(&file_path))
-            .await
-            .map_err(|e| AppError::IoError {
-                message: format!("SEG-Y open task failed: {}", e),
-            })?
+    pub async fn open_async(
+        file_path: String,
+        cache_sample_budget: usize,
+        override_config: Option<SegyFileConfig>,
+    ) -> Result<Self, AppError> {
+        tokio::task::spawn_blocking(move || {
+            Self::open(&file_path, cache_sample_budget, override_config)
+        })
+        .await
+        .map_err(|e| AppError::IoError {
+            message: format!("SEG-Y open task failed: {}", e),
+        })?
     }
 
     /// Create a lightweight data summary for frontend consumption.
@@ -76,245 +246,2612 @@ impl SegyReader {
             textual_header: self.textual_header.clone(),
             binary_header: self.binary_header.clone(),
             total_traces: self.total_traces,
+            trailing_bytes: self.trailing_bytes,
             file_size: self.file_size,
             text_encoding: self.textual_header.encoding(),
             byte_order: self.binary_header.byte_order,
+            samples_per_trace_inferred: self.config.samples_per_trace_inferred,
         }
     }
 
+    /// Summarize the file for the info sidebar in one call.
+    ///
+    /// Sample count and format are the ones used for reading, so overrides
+    /// and inferred counts show up here.
+    pub fn summary(&self) -> Result<FileSummary, AppError> {
+        let binary = &self.binary_header;
+        Ok(FileSummary {
+            revision: self.revision_spec()?.version,
+            byte_order: binary.byte_order,
+            sample_format: self.config.data_sample_format_parsed()?.label().to_string(),
+            samples_per_trace: self.config.samples_per_trace,
+            sample_interval_ms: f32::from(binary.sample_interval_us) / 1000.0,
+            total_traces: self.total_traces,
+            trailing_bytes: self.trailing_bytes,
+            sort: binary.trace_sorting.label().to_string(),
+            measurement_system: binary.measurement_system.label().to_string(),
+        })
+    }
+
     /// Return the absolute file path for the opened SEG-Y file.
     pub fn file_path(&self) -> &str {
         &self.file_path
     }
 
+    /// Return the parsed binary header.
+    pub fn binary_header(&self) -> &BinaryHeader {
+        &self.binary_header
+    }
+
+    /// Return the parsed textual header.
+    pub fn textual_header(&self) -> &TextualHeader {
+        &self.textual_header
+    }
+
+    /// Return the untouched binary header bytes and their detected byte order.
+    pub fn raw_binary_header(&self) -> Result<RawBinaryHeader, AppError> {
+        let start = constants::TEXTUAL_HEADER_SIZE;
+        let bytes = self
+            .backing
+            .slice(start..start + constants::BINARY_HEADER_SIZE)?;
+        Ok(RawBinaryHeader {
+            bytes: bytes.into_owned(),
+            byte_order: self.binary_header.byte_order,
+        })
+    }
+
+    /// Cheap fingerprint of the file for cache keys and change detection.
+    ///
+    /// Hashes the file size, its modification time (read now, so edits made
+    /// while the file is open are noticed), and the 400 binary header bytes
+    /// with 64-bit FNV-1a, which is stable across sessions and builds. This
+    /// is a change-detection heuristic, not a cryptographic hash: a rewrite
+    /// that keeps the size, mtime, and binary header goes unnoticed. In-memory
+    /// readers hash only their size and binary header.
+    pub fn content_hash(&self) -> Result<u64, AppError> {
+        let (size, modified_ns) = match self.file_path.as_str() {
+            "" => (self.file_size, 0),
+            path => {
+                let metadata = std::fs::metadata(path).map_err(|e| AppError::IoError {
+                    message: format!("Failed to read metadata for '{}': {}", path, e),
+                })?;
+                let modified_ns = metadata
+                    .modified()
+                    .ok()
+                    .and_then(|time| time.duration_since(std::time::UNIX_EPOCH).ok())
+                    .map_or(0, |age| age.as_nanos());
+                (metadata.len(), modified_ns)
+            }
+        };
+        let binary_header = self.raw_binary_header()?.bytes;
+
+        let hash = fnv1a(FNV_OFFSET_BASIS, &size.to_le_bytes());
+        let hash = fnv1a(hash, &modified_ns.to_le_bytes());
+        Ok(fnv1a(hash, &binary_header))
+    }
+
+    /// Read `length` raw bytes starting at `offset`, for hex inspection.
+    ///
+    /// The range must lie within the file and `length` may not exceed
+    /// `MAX_READ_BYTES`.
+    pub fn read_bytes(&self, offset: u64, length: usize) -> Result<Vec<u8>, AppError> {
+        if length > MAX_READ_BYTES {
+            return Err(AppError::ValidationError {
+                message: format!(
+                    "Cannot read {} bytes at once (maximum is {})",
+                    length, MAX_READ_BYTES
+                ),
+            });
+        }
+        let file_len = self.backing.len();
+        let start = usize::try_from(offset).unwrap_or(usize::MAX);
+        match start.checked_add(length) {
+            Some(end) if end <= file_len => Ok(self.backing.slice(start..end)?.into_owned()),
+            _ => Err(AppError::ValidationError {
+                message: format!(
+                    "Byte range {}+{} exceeds file size {}",
+                    offset, length, file_len
+                ),
+            }),
+        }
+    }
+
     /// Return the derived configuration used for trace access.
     pub fn config(&self) -> &SegyFileConfig {
         &self.config
     }
 
+    /// Check whether the trace at `trace_index` is an auxiliary trace.
+    ///
+    /// Assumes each record starts with `aux_traces_per_record` auxiliary
+    /// traces followed by `traces_per_record` data traces, beginning at the
+    /// first trace in the file. Always `false` unless both counts are positive.
+    pub fn is_aux_trace(&self, trace_index: usize) -> bool {
+        let data = self.binary_header.traces_per_record;
+        let aux = self.binary_header.aux_traces_per_record;
+        if data <= 0 || aux <= 0 {
+            return false;
+        }
+        trace_index % (data as usize + aux as usize) < aux as usize
+    }
+
+    /// Samples kept per trace after windowing and downsampling, using the
+    /// configured trace length.
+    fn retained_samples(&self, window: Option<&Range<usize>>, max_samples: Option<usize>) -> usize {
+        let samples = window.map_or(usize::from(self.config.samples_per_trace), Range::len);
+        match max_samples {
+            Some(limit) if limit > 0 => samples.min(limit),
+            _ => samples,
+        }
+    }
+
     /// Load a single trace block (header + data) by index.
     pub fn load_single_trace(
         &self,
         trace_index: usize,
         max_samples: Option<usize>,
     ) -> Result<TraceBlock, AppError> {
-        let trace_bytes = self.trace_slice(trace_index)?;
         let format = self.config.data_sample_format_parsed()?;
+        let trace = self.cached_trace(trace_index, format)?;
+        Ok(apply_trace_limit((*trace).clone(), max_samples))
+    }
+
+    /// Decoded block for one trace, from the cache or parsed and cached.
+    ///
+    /// The cache lock is held only to look up and insert, so reading and
+    /// parsing run concurrently with other loads on this reader.
+    fn cached_trace(
+        &self,
+        trace_index: usize,
+        format: DataSampleFormat,
+    ) -> Result<Arc<TraceBlock>, AppError> {
+        if let Some(trace) = self.lock_trace_cache().get(trace_index) {
+            return Ok(trace);
+        }
 
+        let trace_bytes = self.trace_slice(trace_index)?;
         let trace = io::parse_trace_block(
-            trace_bytes,
+            &trace_bytes,
             format,
-            self.config.samples_per_trace,
+            self.trace_samples(&trace_bytes, format),
+            self.config.extended_trace_headers,
             self.config.byte_order,
         )
         .map_err(|e| AppError::SegyError {
             message: format!("Failed to parse trace {}: {}", trace_index, e),
         })?;
 
-        Ok(apply_trace_limit(trace, max_samples))
+        let trace = Arc::new(trace);
+        self.lock_trace_cache().insert(trace_index, trace.clone());
+        Ok(trace)
     }
 
     /// Load a contiguous range of trace blocks.
+    ///
+    /// With `skip_aux_traces`, auxiliary traces in the range (see
+    /// `is_aux_trace`) are left out, so fewer than `count` blocks may be
+    /// returned. Requests over `MAX_TOTAL_SAMPLES` are rejected up front.
     pub fn load_trace_range(
         &self,
         start_index: usize,
         count: usize,
         max_samples: Option<usize>,
+        skip_aux_traces: bool,
     ) -> Result<Vec<TraceBlock>, AppError> {
+        check_sample_budget(count, self.retained_samples(None, max_samples))?;
         io::validate_trace_range(&self.config, start_index, count, self.total_traces)?;
         if count == 0 {
             return Ok(Vec::new());
         }
 
         let format = self.config.data_sample_format_parsed()?;
-        let trace_block_size = self.config.trace_block_size()?;
-        let start_position = self.config.calculate_trace_position(start_index)?;
-        let end_position = start_position
-            .checked_add(trace_block_size.checked_mul(count).ok_or_else(|| {
-                AppError::ValidationError {
-                    message: "Requested trace range is too large".to_string(),
-                }
-            })?)
-            .ok_or_else(|| AppError::ValidationError {
-                message: "Requested trace range exceeds addressable space".to_string(),
-            })?;
+        self.ensure_range_in_file(start_index, count)?;
 
-        if end_position > self.mmap.len() {
-            return Err(AppError::SegyError {
-                message: format!(
-                    "Requested traces exceed file size (need {} bytes, file has {} bytes)",
-                    end_position,
-                    self.mmap.len()
-                ),
-            });
+        let mut traces = Vec::with_capacity(count);
+        for i in 0..count {
+            let trace_index = start_index + i;
+            if skip_aux_traces && self.is_aux_trace(trace_index) {
+                continue;
+            }
+            let trace = self.cached_trace(trace_index, format)?;
+            traces.push(apply_trace_limit((*trace).clone(), max_samples));
+        }
+
+        Ok(traces)
+    }
+
+    /// Load only trace sample data for a contiguous range of traces.
+    ///
+    /// `sample_window` restricts each trace to samples
+    /// `[start_sample, end_sample)`; it must lie within the trace length.
+    /// Whole traces are decoded into the reader's cache, so panning back over
+    /// the same traces, with any window, skips parsing. `max_samples`
+    /// downsamples after windowing. Requests over `MAX_TOTAL_SAMPLES` are
+    /// rejected up front.
+    pub fn load_trace_data_range(
+        &self,
+        start_index: usize,
+        count: usize,
+        max_samples: Option<usize>,
+        sample_window: Option<Range<usize>>,
+    ) -> Result<Vec<TraceData>, AppError> {
+        self.load_trace_data(start_index, count, max_samples, sample_window, true)
+    }
+
+    /// Load trace sample data as `load_trace_data_range` does. With
+    /// `fill_cache` false, traces missing from the cache decode only the
+    /// windowed samples and are not cached, so full-file scans do not evict
+    /// the interactive working set.
+    fn load_trace_data(
+        &self,
+        start_index: usize,
+        count: usize,
+        max_samples: Option<usize>,
+        sample_window: Option<Range<usize>>,
+        fill_cache: bool,
+    ) -> Result<Vec<TraceData>, AppError> {
+        check_sample_budget(
+            count,
+            self.retained_samples(sample_window.as_ref(), max_samples),
+        )?;
+        io::validate_trace_range(&self.config, start_index, count, self.total_traces)?;
+        if count == 0 {
+            return Ok(Vec::new());
         }
 
+        let format = self.config.data_sample_format_parsed()?;
+        self.ensure_range_in_file(start_index, count)?;
+
+        let windowed = |data: &TraceData| match &sample_window {
+            Some(window) => {
+                io::validate_sample_window(window, data.len())?;
+                Ok::<_, AppError>(data.window(window.clone()))
+            }
+            None => Ok(data.clone()),
+        };
         let mut traces = Vec::with_capacity(count);
         for i in 0..count {
-            let offset = start_position + (i * trace_block_size);
-            let trace_bytes = &self.mmap[offset..offset + trace_block_size];
-            let trace = io::parse_trace_block(
-                trace_bytes,
+            let cached = if fill_cache {
+                Some(self.cached_trace(start_index + i, format)?)
+            } else {
+                self.lock_trace_cache().get(start_index + i)
+            };
+            if let Some(trace) = cached {
+                traces.push(apply_data_limit(windowed(&trace.data)?, max_samples));
+                continue;
+            }
+
+            let trace_bytes = self.trace_slice(start_index + i)?;
+            let samples = self.trace_samples(&trace_bytes, format);
+            let window = match &sample_window {
+                Some(window) => {
+                    io::validate_sample_window(window, usize::from(samples))?;
+                    window.clone()
+                }
+                None => 0..usize::from(samples),
+            };
+            let data = io::parse_trace_data(
+                &trace_bytes,
+                self.config.trace_header_size(),
                 format,
-                self.config.samples_per_trace,
+                window,
                 self.config.byte_order,
             )
             .map_err(|e| AppError::SegyError {
-                message: format!("Failed to parse trace {}: {}", start_index + i, e),
+                message: format!("Failed to parse trace data {}: {}", start_index + i, e),
             })?;
 
-            traces.push(apply_trace_limit(trace, max_samples));
+            traces.push(apply_data_limit(data, max_samples));
         }
 
         Ok(traces)
     }
 
-    /// Load only trace sample data for a contiguous range of traces.
-    pub fn load_trace_data_range(
+    /// Load only trace headers for `count` traces starting at `start_index`,
+    /// taking every `stride`-th trace.
+    ///
+    /// Only header bytes are read, which makes this much cheaper than
+    /// `load_trace_range` when scanning header attributes across a line.
+    pub fn load_trace_headers_range(
         &self,
         start_index: usize,
         count: usize,
-        max_samples: Option<usize>,
-    ) -> Result<Vec<TraceData>, AppError> {
-        io::validate_trace_range(&self.config, start_index, count, self.total_traces)?;
+        stride: usize,
+    ) -> Result<Vec<TraceHeader>, AppError> {
+        if stride == 0 {
+            return Err(AppError::ValidationError {
+                message: "Trace stride must be at least 1".to_string(),
+            });
+        }
         if count == 0 {
             return Ok(Vec::new());
         }
 
-        let format = self.config.data_sample_format_parsed()?;
-        let trace_block_size = self.config.trace_block_size()?;
-        let start_position = self.config.calculate_trace_position(start_index)?;
-        let end_position = start_position
-            .checked_add(trace_block_size.checked_mul(count).ok_or_else(|| {
-                AppError::ValidationError {
-                    message: "Requested trace range is too large".to_string(),
-                }
-            })?)
+        let span = (count - 1)
+            .checked_mul(stride)
+            .and_then(|span| span.checked_add(1))
             .ok_or_else(|| AppError::ValidationError {
-                message: "Requested trace range exceeds addressable space".to_string(),
+                message: "Requested trace range is too large".to_string(),
             })?;
+        io::validate_trace_range(&self.config, start_index, span, self.total_traces)?;
+        self.ensure_range_in_file(start_index, span)?;
 
-        if end_position > self.mmap.len() {
-            return Err(AppError::SegyError {
-                message: format!(
-                    "Requested traces exceed file size (need {} bytes, file has {} bytes)",
-                    end_position,
-                    self.mmap.len()
-                ),
+        (0..count)
+            .map(|i| {
+                let trace_index = start_index + i * stride;
+                let trace_bytes = self.trace_header_slice(trace_index, 1, TRACE_HEADER_SIZE)?;
+                io::parse_trace_header(&trace_bytes, self.config.byte_order).map_err(|e| {
+                    AppError::SegyError {
+                        message: format!("Failed to parse trace header {}: {}", trace_index, e),
+                    }
+                })
+            })
+            .collect()
+    }
+
+    /// Load an evenly strided subset of the whole file for an overview render.
+    ///
+    /// Picks the smallest stride that leaves at most `columns` traces, starting
+    /// from the first, so only those traces are read however large the file
+    /// is. Each is mean-downsampled to at most `max_samples` samples.
+    pub fn load_overview_traces(
+        &self,
+        columns: usize,
+        max_samples: usize,
+    ) -> Result<Vec<TraceData>, AppError> {
+        if columns == 0 || max_samples == 0 {
+            return Err(AppError::ValidationError {
+                message: "Overview needs at least one column and one sample".to_string(),
             });
         }
+        let total = match self.total_traces {
+            Some(total) if total > 0 => total,
+            _ => {
+                return Err(AppError::ValidationError {
+                    message: "Overview needs a file with a known, non-zero trace count".to_string(),
+                })
+            }
+        };
+        let stride = total.div_ceil(columns);
+        let count = total.div_ceil(stride);
+        check_sample_budget(count, self.retained_samples(None, Some(max_samples)))?;
 
-        let mut traces = Vec::with_capacity(count);
-        for i in 0..count {
-            let offset = start_position + (i * trace_block_size);
-            let trace_bytes = &self.mmap[offset..offset + trace_block_size];
-            let data = io::parse_trace_data(trace_bytes, format, self.config.samples_per_trace)
+        let format = self.config.data_sample_format_parsed()?;
+        (0..count)
+            .map(|i| {
+                let trace_index = i * stride;
+                let trace_bytes = self.trace_slice(trace_index)?;
+                let samples = usize::from(self.trace_samples(&trace_bytes, format));
+                let data = io::parse_trace_data(
+                    &trace_bytes,
+                    self.config.trace_header_size(),
+                    format,
+                    0..samples,
+                    self.config.byte_order,
+                )
                 .map_err(|e| AppError::SegyError {
-                    message: format!("Failed to parse trace data {}: {}", start_index + i, e),
+                    message: format!("Failed to parse trace data {}: {}", trace_index, e),
                 })?;
+                Ok(data.downsample(max_samples, DownsampleMode::Mean))
+            })
+            .collect()
+    }
 
-            traces.push(apply_data_limit(data, max_samples));
+    /// Compare the binary header sample count and interval against the first
+    /// trace headers.
+    ///
+    /// Diagnostic only: parsing is unaffected. Trace header values of zero are
+    /// treated as unset and skipped. Checking stops at the first trace header
+    /// that cannot be read.
+    pub fn check_header_consistency(&self) -> Vec<ConsistencyWarning> {
+        let traces = self.total_traces.map_or(CONSISTENCY_CHECK_TRACES, |total| {
+            total.min(CONSISTENCY_CHECK_TRACES)
+        });
+        let expected = [
+            ("num_samples", self.binary_header.samples_per_trace),
+            ("sample_interval_us", self.binary_header.sample_interval_us),
+        ];
+
+        let mut warnings = Vec::new();
+        for trace_index in 0..traces {
+            let Ok(header) = self
+                .trace_header_slice(trace_index, 1, TRACE_HEADER_SIZE)
+                .and_then(|bytes| io::parse_trace_header(&bytes, self.config.byte_order))
+            else {
+                break;
+            };
+
+            let found = [header.num_samples, header.sample_interval_us];
+            for ((field_key, binary_value), trace_value) in expected.iter().zip(found) {
+                if trace_value != 0 && trace_value != *binary_value {
+                    warnings.push(ConsistencyWarning {
+                        trace_index,
+                        field_key: field_key.to_string(),
+                        binary_value: i64::from(*binary_value),
+                        trace_value: i64::from(trace_value),
+                        message: format!(
+                            "Trace {} has {} = {}, binary header says {}",
+                            trace_index, field_key, trace_value, binary_value
+                        ),
+                    });
+                }
+            }
         }
 
-        Ok(traces)
+        warnings
     }
 
-    /// Return the byte slice for a single trace block within the memory map.
-    fn trace_slice(&self, trace_index: usize) -> Result<&[u8], AppError> {
-        if let Some(total_traces) = self.total_traces {
-            if trace_index >= total_traces {
-                return Err(AppError::ValidationError {
-                    message: format!(
-                        "Trace index {} out of range (total {})",
-                        trace_index, total_traces
-                    ),
+    /// Minimum and maximum of an integer trace header field across all traces.
+    ///
+    /// The field's byte range comes from the header spec for the file's
+    /// revision, and only those bytes are read from each trace header; no
+    /// samples are decoded.
+    pub fn header_field_range(
+        &self,
+        field_key: &str,
+        on_progress: ProgressCallback<'_>,
+    ) -> Result<(i64, i64), AppError> {
+        let field = self.trace_header_field(field_key)?;
+        let total_traces = self.total_traces.ok_or_else(|| AppError::SegyError {
+            message: "Trace count is unknown".to_string(),
+        })?;
+
+        let mut range: Option<(i64, i64)> = None;
+        self.scan_traces_chunked(SCAN_PROGRESS_INTERVAL, |chunk| {
+            for trace_index in chunk.clone() {
+                let value = self.read_header_field(&field, trace_index)?;
+                range = Some(match range {
+                    Some((min, max)) => (min.min(value), max.max(value)),
+                    None => (value, value),
                 });
             }
-        }
+            on_progress(ScanProgress {
+                processed: chunk.end,
+                total: total_traces,
+            });
+            Ok(ControlFlow::Continue(()))
+        })?;
 
-        let trace_block_size = self.config.trace_block_size()?;
-        let start = self.config.calculate_trace_position(trace_index)?;
-        let end = start
-            .checked_add(trace_block_size)
-            .ok_or_else(|| AppError::ValidationError {
-                message: "Trace slice end overflow".to_string(),
-            })?;
+        range.ok_or_else(|| AppError::ValidationError {
+            message: "File contains no traces".to_string(),
+        })
+    }
 
-        if end > self.mmap.len() {
-            return Err(AppError::SegyError {
-                message: format!(
-                    "Trace {} exceeds file size (end {} bytes, file has {} bytes)",
-                    trace_index,
-                    end,
-                    self.mmap.len()
-                ),
-            });
+    /// Read one integer trace header field for `count` traces from `start_index`.
+    ///
+    /// Only the field's bytes are read from each header, in parallel, and
+    /// values are returned in trace order. Fields with a non-integer type
+    /// yield a `ValidationError`.
+    pub fn extract_header_field(
+        &self,
+        field_key: &str,
+        start_index: usize,
+        count: usize,
+    ) -> Result<Vec<i64>, AppError> {
+        let field = self.trace_header_field(field_key)?;
+        io::validate_trace_range(&self.config, start_index, count, self.total_traces)?;
+        if count == 0 {
+            return Ok(Vec::new());
         }
+        self.ensure_range_in_file(start_index, count)?;
 
-        Ok(&self.mmap[start..end])
+        (start_index..start_index + count)
+            .into_par_iter()
+            .map(|trace_index| self.read_header_field(&field, trace_index))
+            .collect()
     }
-}
 
-/// Shared, async-safe state that caches the most recent SEG-Y reader.
-pub struct SegyReaderState {
-    reader: RwLock<Option<Arc<SegyReader>>>,
-}
+    /// Index of the first trace whose integer header field equals `target`.
+    ///
+    /// Only the field's bytes are read. By default every trace is checked in
+    /// order. With `sorted`, the field is trusted to be monotonic across the
+    /// file (ascending or descending, judged from the first and last trace)
+    /// and is binary searched instead, reading about `log2(n)` headers; an
+    /// unsorted field then may miss a match. Returns `None` when no trace
+    /// matches. `on_progress` is called as the unsorted scan advances.
+    pub fn find_trace_by_field(
+        &self,
+        field_key: &str,
+        target: i64,
+        sorted: bool,
+        on_progress: ProgressCallback<'_>,
+    ) -> Result<Option<usize>, AppError> {
+        let field = self.trace_header_field(field_key)?;
+        let total_traces = self.total_traces.ok_or_else(|| AppError::SegyError {
+            message: "Trace count is unknown".to_string(),
+        })?;
+        if total_traces == 0 {
+            return Ok(None);
+        }
+        self.ensure_range_in_file(0, total_traces)?;
 
-impl Default for SegyReaderState {
-    fn default() -> Self {
-        Self {
-            reader: RwLock::new(None),
+        if !sorted {
+            let mut found = None;
+            self.scan_traces_chunked(SCAN_PROGRESS_INTERVAL, |chunk| {
+                for trace_index in chunk.clone() {
+                    if self.read_header_field(&field, trace_index)? == target {
+                        found = Some(trace_index);
+                        return Ok(ControlFlow::Break(()));
+                    }
+                }
+                on_progress(ScanProgress {
+                    processed: chunk.end,
+                    total: total_traces,
+                });
+                Ok(ControlFlow::Continue(()))
+            })?;
+            return Ok(found);
         }
-    }
-}
 
-impl SegyReaderState {
-    /// Create a new empty reader state.
-    pub fn new() -> Self {
-        Self::default()
+        // First trace not yet past `target` in the field's sort direction.
+        let descending = self.read_header_field(&field, 0)?
+            > self.read_header_field(&field, total_traces - 1)?;
+        let (mut low, mut high) = (0, total_traces);
+        while low < high {
+            let mid = low + (high - low) / 2;
+            let value = self.read_header_field(&field, mid)?;
+            let before_target = if descending {
+                value > target
+            } else {
+                value < target
+            };
+            if before_target {
+                low = mid + 1;
+            } else {
+                high = mid;
+            }
+        }
+        Ok((low < total_traces && self.read_header_field(&field, low)? == target).then_some(low))
     }
 
-    /// Open a new reader and cache it, replacing any previous reader.
-    pub async fn open(&self, file_path: String) -> Result<Arc<SegyReader>, AppError> {
-        let reader = SegyReader::open_async(file_path.clone()).await?;
-        let reader = Arc::new(reader);
+    /// List the trace header fields whose values differ between two traces.
+    ///
+    /// Fields come from `effective_trace_spec`; only the two
+    /// header slices are read, so the traces may be anywhere in the file.
+    pub fn diff_trace_headers(
+        &self,
+        index_a: usize,
+        index_b: usize,
+    ) -> Result<Vec<FieldDiff>, AppError> {
+        for trace_index in [index_a, index_b] {
+            io::validate_trace_range(&self.config, trace_index, 1, self.total_traces)?;
+            self.ensure_range_in_file(trace_index, 1)?;
+        }
+        let spec = self.effective_trace_spec()?;
+        let header_a = self.trace_header_slice(index_a, 1, TRACE_HEADER_SIZE)?;
+        let header_b = self.trace_header_slice(index_b, 1, TRACE_HEADER_SIZE)?;
 
-        let mut guard = self.reader.write().await;
-        *guard = Some(reader.clone());
+        Ok(header_spec::diff_header_fields(
+            &header_a,
+            &header_b,
+            1,
+            self.config.byte_order,
+            &spec.get_trace_header_fields(),
+        ))
+    }
 
-        Ok(reader)
+    /// Decode every field of one trace header with `effective_trace_spec`,
+    /// resolving code labels.
+    pub fn decode_trace_header(&self, trace_index: usize) -> Result<Vec<DecodedField>, AppError> {
+        io::validate_trace_range(&self.config, trace_index, 1, self.total_traces)?;
+        self.ensure_range_in_file(trace_index, 1)?;
+        let spec = self.effective_trace_spec()?;
+        let header_bytes = self.trace_header_slice(trace_index, 1, TRACE_HEADER_SIZE)?;
+
+        Ok(header_spec::decode_header_fields(
+            &header_bytes,
+            1,
+            self.config.byte_order,
+            &spec.get_trace_header_fields(),
+        ))
     }
 
-    /// Return the cached reader if it matches the path, otherwise open a new one.
-    pub async fn get_or_open(&self, file_path: String) -> Result<Arc<SegyReader>, AppError> {
-        if file_path.is_empty() {
+    /// Write integer trace header fields for `count` traces to a CSV file.
+    ///
+    /// The first row holds the spec `name` of each field in `field_keys`
+    /// order; every following row holds one trace's values. Only header bytes
+    /// are read, as in `extract_header_field`.
+    pub fn export_headers_csv(
+        &self,
+        output_path: &Path,
+        field_keys: &[String],
+        start_index: usize,
+        count: usize,
+        on_progress: ProgressCallback<'_>,
+    ) -> Result<(), AppError> {
+        if field_keys.is_empty() {
             return Err(AppError::ValidationError {
-                message: "File path cannot be empty".to_string(),
+                message: "CSV export needs at least one header field".to_string(),
             });
         }
+        let fields = field_keys
+            .iter()
+            .map(|key| self.trace_header_field(key))
+            .collect::<Result<Vec<_>, _>>()?;
+        io::validate_trace_range(&self.config, start_index, count, self.total_traces)?;
+        if count > 0 {
+            self.ensure_range_in_file(start_index, count)?;
+        }
 
-        if let Some(reader) = self.reader.read().await.as_ref() {
-            if reader.file_path() == file_path {
-                return Ok(reader.clone());
+        let write_error = |e: std::io::Error| AppError::IoError {
+            message: format!("Failed to write '{}': {}", output_path.display(), e),
+        };
+        let mut writer = BufWriter::new(File::create(output_path).map_err(write_error)?);
+        let names: Vec<_> = fields.iter().map(|field| csv_field(&field.name)).collect();
+        writeln!(writer, "{}", names.join(",")).map_err(write_error)?;
+
+        for chunk_start in (start_index..start_index + count).step_by(SCAN_PROGRESS_INTERVAL) {
+            let chunk_end = (chunk_start + SCAN_PROGRESS_INTERVAL).min(start_index + count);
+            let rows = (chunk_start..chunk_end)
+                .into_par_iter()
+                .map(|trace_index| {
+                    fields
+                        .iter()
+                        .map(|field| self.read_header_field(field, trace_index))
+                        .collect::<Result<Vec<_>, _>>()
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            for row in rows {
+                let values: Vec<_> = row.iter().map(i64::to_string).collect();
+                writeln!(writer, "{}", values.join(",")).map_err(write_error)?;
             }
+            on_progress(ScanProgress {
+                processed: chunk_end - start_index,
+                total: count,
+            });
         }
-
-        self.open(file_path).await
+        writer.flush().map_err(write_error)
     }
-}
 
-/// Apply a sample limit to a trace block, preserving header consistency.
-fn apply_trace_limit(trace: TraceBlock, max_samples: Option<usize>) -> TraceBlock {
-    match max_samples {
-        Some(limit) => trace.downsample(limit),
-        None => trace,
+    /// Write trace samples as a 2D little-endian float32 NumPy `.npy` array.
+    ///
+    /// The array has shape `(count, samples)`: one row per trace in file
+    /// order, samples along the second axis. All traces must have the same
+    /// length. Traces are decoded in chunks, so the range may exceed memory.
+    ///
+    /// The array is written beside `output_path` and renamed into place once
+    /// complete, so a failed export never leaves a truncated file behind.
+    pub fn export_samples_npy(
+        &self,
+        output_path: &Path,
+        start_index: usize,
+        count: usize,
+        on_progress: ProgressCallback<'_>,
+    ) -> Result<(), AppError> {
+        if count == 0 {
+            return Err(AppError::ValidationError {
+                message: "NumPy export needs at least one trace".to_string(),
+            });
+        }
+        let end_index =
+            start_index
+                .checked_add(count)
+                .ok_or_else(|| AppError::ValidationError {
+                    message: "Trace range end overflow".to_string(),
+                })?;
+        io::validate_trace_range(&self.config, start_index, count, self.total_traces)?;
+        self.ensure_range_in_file(start_index, count)?;
+
+        let mut partial_name = output_path.file_name().unwrap_or_default().to_owned();
+        partial_name.push(".partial");
+        let partial_path = output_path.with_file_name(partial_name);
+        let written = self
+            .write_samples_npy(&partial_path, start_index..end_index, on_progress)
+            .and_then(|()| {
+                std::fs::rename(&partial_path, output_path).map_err(|e| AppError::IoError {
+                    message: format!("Failed to write '{}': {}", output_path.display(), e),
+                })
+            });
+        if written.is_err() {
+            std::fs::remove_file(&partial_path).ok();
+        }
+        written
     }
-}
 
-/// Apply a sample limit to raw trace data.
+    /// Write the `.npy` array for `export_samples_npy` to `path`.
+    fn write_samples_npy(
+        &self,
+        path: &Path,
+        traces: Range<usize>,
+        on_progress: ProgressCallback<'_>,
+    ) -> Result<(), AppError> {
+        let (start_index, count) = (traces.start, traces.len());
+        let write_error = |e: std::io::Error| AppError::IoError {
+            message: format!("Failed to write '{}': {}", path.display(), e),
+        };
+
+        let mut writer: Option<BufWriter<File>> = None;
+        let mut samples = 0;
+        for chunk_start in traces.clone().step_by(SAMPLE_CHUNK_TRACES) {
+            let chunk_count = SAMPLE_CHUNK_TRACES.min(traces.end - chunk_start);
+            let traces = self.load_trace_data(chunk_start, chunk_count, None, None, false)?;
+            let writer = match &mut writer {
+                Some(writer) => writer,
+                None => {
+                    samples = traces[0].len();
+                    let mut file = BufWriter::new(File::create(path).map_err(write_error)?);
+                    file.write_all(&npy_header(count, samples))
+                        .map_err(write_error)?;
+                    writer.insert(file)
+                }
+            };
+            for (offset, trace) in traces.iter().enumerate() {
+                if trace.len() != samples {
+                    return Err(AppError::ValidationError {
+                        message: format!(
+                            "Trace {} has {} samples but NumPy export needs {} for every trace",
+                            chunk_start + offset,
+                            trace.len(),
+                            samples
+                        ),
+                    });
+                }
+                let bytes: Vec<u8> = trace
+                    .to_f32_vec()
+                    .iter()
+                    .flat_map(|value| value.to_le_bytes())
+                    .collect();
+                writer.write_all(&bytes).map_err(write_error)?;
+            }
+            on_progress(ScanProgress {
+                processed: chunk_start + chunk_count - start_index,
+                total: count,
+            });
+        }
+
+        match writer {
+            Some(mut writer) => writer.flush().map_err(write_error),
+            None => Ok(()),
+        }
+    }
+
+    /// Remap trace header fields for every later spec-driven header read.
+    ///
+    /// `overrides` are merged onto the spec for this file's revision (see
+    /// `SegyFormatSpec::with_trace_overrides`) and replace any previous set;
+    /// pass an empty list to restore the standard layout. Field extraction,
+    /// ranges, diffs, and CSV export then read the remapped bytes. The fixed
+    /// `TraceHeader` fields are unaffected.
+    pub fn set_header_overrides(&self, overrides: Vec<HeaderFieldSpec>) -> Result<(), AppError> {
+        self.revision_spec()?
+            .with_trace_overrides(overrides.clone())
+            .map_err(|message| AppError::ValidationError { message })?;
+        *self
+            .header_overrides
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner()) = overrides;
+        Ok(())
+    }
+
+    /// Spec for this file's revision with any header overrides applied.
+    pub fn effective_trace_spec(&self) -> Result<SegyFormatSpec, AppError> {
+        let overrides = self
+            .header_overrides
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .clone();
+        let spec = self.revision_spec()?;
+        if overrides.is_empty() {
+            return Ok(spec);
+        }
+        spec.with_trace_overrides(overrides)
+            .map_err(AppError::config)
+    }
+
+    /// Bundled spec matching this file's declared revision.
+    fn revision_spec(&self) -> Result<SegyFormatSpec, AppError> {
+        SegyFormatSpec::load_for_revision(self.binary_header.segy_revision)
+            .map_err(AppError::config)
+    }
+
+    /// Histogram the f32-converted samples of `count` traces in `bins` bins.
+    ///
+    /// Samples are decoded in batches, header bytes skipped, and never held
+    /// beyond one batch: a first pass finds the finite amplitude range and a
+    /// second counts samples into equal-width bins over it. All-constant data
+    /// (such as all zeros) yields a single bin; non-finite samples are not
+    /// counted. `on_progress` totals both passes, so it reports `2 * count`
+    /// traces.
+    pub fn amplitude_histogram(
+        &self,
+        start_index: usize,
+        count: usize,
+        bins: usize,
+        on_progress: ProgressCallback<'_>,
+    ) -> Result<HistogramResult, AppError> {
+        if bins == 0 || count == 0 {
+            return Err(AppError::ValidationError {
+                message: "Amplitude histogram needs at least one trace and one bin".to_string(),
+            });
+        }
+
+        let total = count.saturating_mul(2);
+        let report = |processed| on_progress(ScanProgress { processed, total });
+
+        let mut range: Option<(f32, f32)> = None;
+        self.for_each_sample_chunk(
+            start_index,
+            count,
+            |samples| {
+                for &value in samples.iter().filter(|v| v.is_finite()) {
+                    let (min, max) = range.get_or_insert((value, value));
+                    *min = min.min(value);
+                    *max = max.max(value);
+                }
+            },
+            report,
+        )?;
+
+        let (min, max) = range.unwrap_or((0.0, 0.0));
+        let mut histogram = HistogramResult::with_range(min, max, bins);
+        self.for_each_sample_chunk(
+            start_index,
+            count,
+            |samples| histogram.add(samples),
+            |done| report(count + done),
+        )?;
+        Ok(histogram)
+    }
+
+    /// Decode `count` traces in batches and pass each trace's f32 samples to
+    /// `visit`, then the number of traces done so far to `on_chunk`.
+    fn for_each_sample_chunk(
+        &self,
+        start_index: usize,
+        count: usize,
+        mut visit: impl FnMut(&[f32]),
+        mut on_chunk: impl FnMut(usize),
+    ) -> Result<(), AppError> {
+        let mut samples = Vec::new();
+        let traces = start_index..start_index.saturating_add(count);
+        self.scan_trace_range_chunked(traces, SAMPLE_CHUNK_TRACES, |chunk| {
+            for trace in self.load_trace_data(chunk.start, chunk.len(), None, None, false)? {
+                trace.copy_f32_into(&mut samples);
+                visit(&samples);
+            }
+            on_chunk(chunk.end - start_index);
+            Ok(ControlFlow::Continue(()))
+        })
+    }
+
+    /// Visit every trace in windows of `chunk_size` traces, passing each
+    /// window's trace indices to `f`.
+    ///
+    /// For memory-mapped files each window's bytes are advised as sequential
+    /// before `f` runs and released after, so a full scan of a file larger
+    /// than RAM keeps roughly one window resident instead of the whole
+    /// mapping. Other stores just visit the windows. Stops at the first error
+    /// from `f`, or early when `f` returns `ControlFlow::Break`.
+    pub fn scan_traces_chunked<F>(&self, chunk_size: usize, f: F) -> Result<(), AppError>
+    where
+        F: FnMut(Range<usize>) -> Result<ControlFlow<()>, AppError>,
+    {
+        let total_traces = self.total_traces.ok_or_else(|| AppError::SegyError {
+            message: "Trace count is unknown".to_string(),
+        })?;
+        self.scan_trace_range_chunked(0..total_traces, chunk_size, f)
+    }
+
+    /// `scan_traces_chunked` over the traces in `traces` only.
+    fn scan_trace_range_chunked<F>(
+        &self,
+        traces: Range<usize>,
+        chunk_size: usize,
+        mut f: F,
+    ) -> Result<(), AppError>
+    where
+        F: FnMut(Range<usize>) -> Result<ControlFlow<()>, AppError>,
+    {
+        if chunk_size == 0 {
+            return Err(AppError::ValidationError {
+                message: "Scan chunk size must be at least 1".to_string(),
+            });
+        }
+        for chunk_start in traces.clone().step_by(chunk_size) {
+            let chunk = chunk_start..chunk_start.saturating_add(chunk_size).min(traces.end);
+            let bytes = self.trace_byte_range(&chunk);
+            if let Some(bytes) = &bytes {
+                self.backing.advise_sequential(bytes.clone());
+            }
+            let result = f(chunk);
+            if let Some(bytes) = bytes {
+                self.backing.release(bytes);
+            }
+            if result?.is_break() {
+                break;
+            }
+        }
+        Ok(())
+    }
+
+    /// Bytes spanned by the trace blocks in `traces`, clamped to the store,
+    /// or `None` when their positions are unknown.
+    fn trace_byte_range(&self, traces: &Range<usize>) -> Option<Range<usize>> {
+        let (start, end) = match &self.trace_offsets {
+            Some(offsets) => (*offsets.get(traces.start)?, *offsets.get(traces.end)?),
+            None => {
+                let start = self.config.calculate_trace_position(traces.start).ok()?;
+                let size = self.config.trace_block_size().ok()?;
+                (start, start.checked_add(size.checked_mul(traces.len())?)?)
+            }
+        };
+        let len = self.backing.len();
+        Some(start.min(len)..end.min(len))
+    }
+
+    /// Suggest whether 4-byte samples are really IBM or IEEE float.
+    ///
+    /// Legacy files sometimes declare one float format but hold the other.
+    /// Samples from a few traces spread through the file are decoded both
+    /// ways. IBM values are implausible when non-finite, out of range, or
+    /// unnormalized (a zero leading hex digit, which real IBM encoders never
+    /// write but ~1 in 16 IEEE words shows); IEEE values when non-finite or
+    /// out of range. The format with fewer implausible samples is suggested,
+    /// IBM on a tie since IEEE data almost never normalizes perfectly.
+    ///
+    /// Only a suggestion: nothing is applied, and files declaring other
+    /// formats, or with too few non-zero samples, keep their declared code.
+    pub fn detect_sample_format(&self) -> Result<SampleFormatSuggestion, AppError> {
+        let declared = self.config.data_sample_format;
+        let mut suggestion = SampleFormatSuggestion {
+            declared,
+            suggested: declared,
+            ibm_implausible: 0.0,
+            ieee_implausible: 0.0,
+            samples_checked: 0,
+        };
+        let format = self.config.data_sample_format_parsed()?;
+        let total = self.total_traces.unwrap_or(0);
+        if !matches!(
+            format,
+            DataSampleFormat::IbmFloat32 | DataSampleFormat::IeeeFloat32
+        ) || total == 0
+        {
+            return Ok(suggestion);
+        }
+
+        let (mut ibm_bad, mut ieee_bad) = (0usize, 0usize);
+        let sampled = FORMAT_DETECTION_TRACES.min(total);
+        for trace_index in (0..sampled).map(|i| i * total / sampled) {
+            let trace_bytes = self.trace_slice(trace_index)?;
+            let samples = usize::from(self.trace_samples(&trace_bytes, format));
+            let header_size = self.config.trace_header_size();
+            let data = trace_bytes
+                .get(header_size..header_size + samples * 4)
+                .unwrap_or_default();
+            let decode = |format| {
+                io::parse_trace_data(
+                    &trace_bytes,
+                    header_size,
+                    format,
+                    0..samples,
+                    self.config.byte_order,
+                )
+                .map(|data| data.to_f32_vec())
+            };
+            let as_ibm = decode(DataSampleFormat::IbmFloat32)?;
+            let as_ieee = decode(DataSampleFormat::IeeeFloat32)?;
+
+            for (index, chunk) in data.chunks_exact(4).enumerate() {
+                let bytes = [chunk[0], chunk[1], chunk[2], chunk[3]];
+                let word = match self.config.byte_order {
+                    ByteOrder::BigEndian => u32::from_be_bytes(bytes),
+                    ByteOrder::LittleEndian => u32::from_le_bytes(bytes),
+                };
+                if word & 0x7FFF_FFFF == 0 {
+                    continue;
+                }
+                suggestion.samples_checked += 1;
+                let unnormalized = word & 0x00F0_0000 == 0;
+                if unnormalized || !plausible_magnitude(as_ibm[index]) {
+                    ibm_bad += 1;
+                }
+                if !plausible_magnitude(as_ieee[index]) {
+                    ieee_bad += 1;
+                }
+            }
+        }
+
+        let checked = suggestion.samples_checked;
+        if checked < FORMAT_DETECTION_MIN_SAMPLES {
+            return Ok(suggestion);
+        }
+        suggestion.ibm_implausible = ibm_bad as f32 / checked as f32;
+        suggestion.ieee_implausible = ieee_bad as f32 / checked as f32;
+        suggestion.suggested = if ibm_bad <= ieee_bad {
+            DataSampleFormat::IbmFloat32 as u16
+        } else {
+            DataSampleFormat::IeeeFloat32 as u16
+        };
+        Ok(suggestion)
+    }
+
+    /// Look up an integer trace header field in `effective_trace_spec`.
+    fn trace_header_field(&self, field_key: &str) -> Result<HeaderFieldSpec, AppError> {
+        let spec = self.effective_trace_spec()?;
+        let field = spec
+            .get_trace_header_fields()
+            .into_iter()
+            .find(|field| field.field_key == field_key)
+            .ok_or_else(|| AppError::ValidationError {
+                message: format!("Unknown trace header field: {}", field_key),
+            })?;
+
+        match field.data_type.as_str() {
+            "int16" | "uint16" | "int32" => Ok(field),
+            other => Err(AppError::ValidationError {
+                message: format!(
+                    "Trace header field {} has non-integer type {}",
+                    field_key, other
+                ),
+            }),
+        }
+    }
+
+    /// Read one header field of one trace straight from the backing store.
+    fn read_header_field(
+        &self,
+        field: &HeaderFieldSpec,
+        trace_index: usize,
+    ) -> Result<i64, AppError> {
+        let (first_byte, last_byte) = (usize::from(field.byte_start), usize::from(field.byte_end));
+        let field_bytes = self.trace_header_slice(trace_index, first_byte, last_byte)?;
+        field
+            .read_value(&field_bytes, first_byte, self.config.byte_order)
+            .ok_or_else(|| AppError::SegyError {
+                message: format!(
+                    "Failed to read {} from trace header {}",
+                    field.field_key, trace_index
+                ),
+            })
+    }
+
+    /// Drop all decoded trace blocks held by this reader.
+    pub fn clear_trace_cache(&self) {
+        self.lock_trace_cache().clear();
+    }
+
+    /// Lock the trace cache, recovering from a poisoned mutex.
+    ///
+    /// The cache holds only derived data, so a panic mid-update cannot leave
+    /// it in a state worse than stale entries.
+    fn lock_trace_cache(&self) -> MutexGuard<'_, TraceCache> {
+        self.trace_cache
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// Build a table of trace byte offsets for files with variable-length traces.
+    ///
+    /// Walks the file from the first trace, reading only each trace header's
+    /// `num_samples` (bytes 115-116) to find the start of the next trace. Entry
+    /// `i` is the start of trace `i`; the final entry marks the end of the last
+    /// complete trace, so the table has one more entry than there are traces.
+    pub fn build_trace_offset_table(&self) -> Result<Vec<usize>, AppError> {
+        let bytes_per_sample = self.config.data_sample_format_parsed()?.bytes_per_sample();
+        let file_len = self.backing.len();
+
+        let mut offsets = Vec::new();
+        let mut position = self.config.data_offset;
+        while let Some(header_end) = position
+            .checked_add(self.config.trace_header_size())
+            .filter(|&end| end <= file_len)
+        {
+            let raw: [u8; 2] = self
+                .backing
+                .slice(position + 114..position + 116)?
+                .as_ref()
+                .try_into()
+                .expect("two-byte slice");
+            let samples = match self.config.byte_order {
+                ByteOrder::BigEndian => u16::from_be_bytes(raw),
+                ByteOrder::LittleEndian => u16::from_le_bytes(raw),
+            };
+
+            let trace_end = usize::from(samples)
+                .checked_mul(bytes_per_sample)
+                .and_then(|data_size| header_end.checked_add(data_size))
+                .ok_or_else(|| AppError::ValidationError {
+                    message: "Trace offset overflow".to_string(),
+                })?;
+            if trace_end > file_len {
+                break;
+            }
+
+            offsets.push(position);
+            position = trace_end;
+        }
+        offsets.push(position);
+
+        Ok(offsets)
+    }
+
+    /// Number of samples stored in a trace block.
+    ///
+    /// Fixed-length files use the configured count; variable-length files
+    /// derive it from the block size recorded in the offset table.
+    fn trace_samples(&self, trace_bytes: &[u8], format: DataSampleFormat) -> u16 {
+        match self.trace_offsets {
+            Some(_) => {
+                let data_size = trace_bytes
+                    .len()
+                    .saturating_sub(self.config.trace_header_size());
+                (data_size / format.bytes_per_sample()) as u16
+            }
+            None => self.config.samples_per_trace,
+        }
+    }
+
+    /// Ensure a fixed-length trace range lies entirely within the file.
+    ///
+    /// Variable-length ranges are bounded by the offset table instead.
+    fn ensure_range_in_file(&self, start_index: usize, count: usize) -> Result<(), AppError> {
+        if self.trace_offsets.is_some() {
+            return Ok(());
+        }
+
+        let trace_block_size = self.config.trace_block_size()?;
+        let start_position = self.config.calculate_trace_position(start_index)?;
+        let end_position = start_position
+            .checked_add(trace_block_size.checked_mul(count).ok_or_else(|| {
+                AppError::ValidationError {
+                    message: "Requested trace range is too large".to_string(),
+                }
+            })?)
+            .ok_or_else(|| AppError::ValidationError {
+                message: "Requested trace range exceeds addressable space".to_string(),
+            })?;
+
+        if end_position > self.backing.len() {
+            return Err(AppError::SegyError {
+                message: format!(
+                    "Requested traces exceed file size (need {} bytes, file has {} bytes)",
+                    end_position,
+                    self.backing.len()
+                ),
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Return the bytes of a single trace block from the backing store.
+    fn trace_slice(&self, trace_index: usize) -> Result<Cow<'_, [u8]>, AppError> {
+        let range = self.trace_block_range(trace_index)?;
+        self.backing.slice(range)
+    }
+
+    /// Return bytes `first_byte..=last_byte` of a trace header, numbered from
+    /// 1 as in header specs, without reading the rest of the trace block.
+    ///
+    /// On the seek-and-read store this keeps header scans from copying every
+    /// trace's samples.
+    fn trace_header_slice(
+        &self,
+        trace_index: usize,
+        first_byte: usize,
+        last_byte: usize,
+    ) -> Result<Cow<'_, [u8]>, AppError> {
+        let trace = self.trace_block_range(trace_index)?;
+        let start = trace.start + first_byte.saturating_sub(1);
+        let end = trace.start + last_byte;
+        if first_byte == 0 || first_byte > last_byte || end > trace.end {
+            return Err(AppError::ValidationError {
+                message: format!(
+                    "Header bytes {}-{} are outside trace {}",
+                    first_byte, last_byte, trace_index
+                ),
+            });
+        }
+        self.backing.slice(start..end)
+    }
+
+    /// Byte range of a single trace block in the backing store.
+    fn trace_block_range(&self, trace_index: usize) -> Result<Range<usize>, AppError> {
+        if let Some(total_traces) = self.total_traces {
+            if trace_index >= total_traces {
+                return Err(AppError::ValidationError {
+                    message: format!(
+                        "Trace index {} out of range (total {})",
+                        trace_index, total_traces
+                    ),
+                });
+            }
+        }
+
+        let (start, end) = match &self.trace_offsets {
+            Some(offsets) => match (offsets.get(trace_index), offsets.get(trace_index + 1)) {
+                (Some(&start), Some(&end)) => (start, end),
+                _ => {
+                    return Err(AppError::ValidationError {
+                        message: format!("Trace index {} out of range", trace_index),
+                    })
+                }
+            },
+            None => {
+                let trace_block_size = self.config.trace_block_size()?;
+                let start = self.config.calculate_trace_position(trace_index)?;
+                let end = start.checked_add(trace_block_size).ok_or_else(|| {
+                    AppError::ValidationError {
+                        message: "Trace slice end overflow".to_string(),
+                    }
+                })?;
+                (start, end)
+            }
+        };
+
+        if end > self.backing.len() {
+            return Err(AppError::SegyError {
+                message: format!(
+                    "Trace {} exceeds file size (end {} bytes, file has {} bytes)",
+                    trace_index,
+                    end,
+                    self.backing.len()
+                ),
+            });
+        }
+
+        Ok(start..end)
+    }
+}
+
+/// Take the sample count from the first trace header when the binary header
+/// leaves it at zero, as some files only populate the per-trace value.
+///
+/// `config.data_offset` must already point at the first trace.
+fn infer_samples_per_trace(
+    config: &mut SegyFileConfig,
+    store: &TraceStore,
+) -> Result<(), AppError> {
+    if config.samples_per_trace != 0 {
+        return Ok(());
+    }
+    let header_end = (config.data_offset + TRACE_HEADER_SIZE).min(store.len());
+    let first_header = store.slice(config.data_offset.min(header_end)..header_end)?;
+    if let Some(samples) = io::read_first_trace_num_samples(&first_header, config.byte_order) {
+        config.samples_per_trace = samples;
+        config.samples_per_trace_inferred = true;
+    }
+    Ok(())
+}
+
+/// Readers kept open by `SegyReaderState` unless configured otherwise.
+pub const DEFAULT_MAX_OPEN_FILES: usize = 4;
+
+/// Shared, async-safe state that keeps recently used SEG-Y readers open.
+///
+/// Readers are keyed by path. When opening one more would exceed the
+/// capacity, the least recently used reader is dropped; its memory map is
+/// released once in-flight commands holding it finish.
+pub struct SegyReaderState {
+    readers: RwLock<HashMap<String, OpenReader>>,
+    // Overrides per path, kept across eviction so reopened readers match.
+    settings: Mutex<HashMap<String, ReaderSettings>>,
+    // Monotonic use counter; lookups only need a read lock to bump recency.
+    clock: AtomicU64,
+    cache_sample_budget: usize,
+    max_open_files: usize,
+}
+
+struct OpenReader {
+    reader: Arc<SegyReader>,
+    last_used: AtomicU64,
+}
+
+/// User overrides reapplied when an evicted reader is reopened.
+#[derive(Default, Clone)]
+struct ReaderSettings {
+    override_config: Option<SegyFileConfig>,
+    header_overrides: Vec<HeaderFieldSpec>,
+}
+
+impl Default for SegyReaderState {
+    fn default() -> Self {
+        Self::with_limits(DEFAULT_TRACE_CACHE_SAMPLES, DEFAULT_MAX_OPEN_FILES)
+    }
+}
+
+impl SegyReaderState {
+    /// Create a new empty reader state.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create a new empty reader state whose readers cache up to
+    /// `cache_sample_budget` decoded samples.
+    pub fn with_cache_budget(cache_sample_budget: usize) -> Self {
+        Self::with_limits(cache_sample_budget, DEFAULT_MAX_OPEN_FILES)
+    }
+
+    /// Create a new empty reader state keeping up to `max_open_files`
+    /// readers (at least one), each caching up to `cache_sample_budget`
+    /// decoded samples.
+    pub fn with_limits(cache_sample_budget: usize, max_open_files: usize) -> Self {
+        Self {
+            readers: RwLock::new(HashMap::new()),
+            settings: Mutex::new(HashMap::new()),
+            clock: AtomicU64::new(0),
+            cache_sample_budget,
+            max_open_files: max_open_files.max(1),
+        }
+    }
+
+    /// Open a new reader and cache it, replacing any reader for the same path.
+    ///
+    /// `override_config` is passed through to `SegyReader::open` and
+    /// remembered for reopening the file after eviction; header overrides
+    /// set on a previous reader are cleared.
+    pub async fn open(
+        &self,
+        file_path: String,
+        override_config: Option<SegyFileConfig>,
+    ) -> Result<Arc<SegyReader>, AppError> {
+        let reader = SegyReader::open_async(
+            file_path.clone(),
+            self.cache_sample_budget,
+            override_config.clone(),
+        )
+        .await?;
+        let reader = Arc::new(reader);
+
+        let mut readers = self.readers.write().await;
+        self.lock_settings().insert(
+            file_path.clone(),
+            ReaderSettings {
+                override_config,
+                header_overrides: Vec::new(),
+            },
+        );
+        self.insert(&mut readers, file_path, reader.clone());
+        Ok(reader)
+    }
+
+    /// Remap trace header fields for `file_path` (see
+    /// `SegyReader::set_header_overrides`), remembering them for reopening
+    /// the file after eviction.
+    pub async fn set_header_overrides(
+        &self,
+        file_path: String,
+        overrides: Vec<HeaderFieldSpec>,
+    ) -> Result<(), AppError> {
+        let reader = self.get_or_open(file_path.clone()).await?;
+        reader.set_header_overrides(overrides.clone())?;
+        self.lock_settings()
+            .entry(file_path)
+            .or_default()
+            .header_overrides = overrides;
+        Ok(())
+    }
+
+    /// Return the cached reader for the path, otherwise open and cache one.
+    pub async fn get_or_open(&self, file_path: String) -> Result<Arc<SegyReader>, AppError> {
+        if file_path.is_empty() {
+            return Err(AppError::ValidationError {
+                message: "File path cannot be empty".to_string(),
+            });
+        }
+
+        if let Some(reader) = self.lookup(&*self.readers.read().await, &file_path) {
+            return Ok(reader);
+        }
+
+        // Open without holding the lock so other files stay usable meanwhile,
+        // restoring any overrides the file had before it was evicted.
+        let settings = self
+            .lock_settings()
+            .get(&file_path)
+            .cloned()
+            .unwrap_or_default();
+        let reader = SegyReader::open_async(
+            file_path.clone(),
+            self.cache_sample_budget,
+            settings.override_config,
+        )
+        .await?;
+        if !settings.header_overrides.is_empty() {
+            reader.set_header_overrides(settings.header_overrides)?;
+        }
+        let mut readers = self.readers.write().await;
+        // Another command may have opened the same file while we did.
+        if let Some(existing) = self.lookup(&readers, &file_path) {
+            return Ok(existing);
+        }
+        let reader = Arc::new(reader);
+        self.insert(&mut readers, file_path, reader.clone());
+        Ok(reader)
+    }
+
+    /// Drop the reader for `file_path`, returning whether one was open.
+    ///
+    /// The memory map is released once in-flight commands using the reader
+    /// finish.
+    pub async fn close(&self, file_path: &str) -> bool {
+        let mut readers = self.readers.write().await;
+        self.lock_settings().remove(file_path);
+        readers.remove(file_path).is_some()
+    }
+
+    /// Clear the decoded trace caches of all open readers.
+    pub async fn clear_trace_cache(&self) {
+        for open in self.readers.read().await.values() {
+            open.reader.clear_trace_cache();
+        }
+    }
+
+    /// Find a cached reader and mark it as most recently used.
+    fn lookup(
+        &self,
+        readers: &HashMap<String, OpenReader>,
+        file_path: &str,
+    ) -> Option<Arc<SegyReader>> {
+        let open = readers.get(file_path)?;
+        open.last_used.store(self.tick(), Ordering::Relaxed);
+        Some(open.reader.clone())
+    }
+
+    /// Cache `reader`, evicting the least recently used readers over capacity.
+    fn insert(
+        &self,
+        readers: &mut HashMap<String, OpenReader>,
+        file_path: String,
+        reader: Arc<SegyReader>,
+    ) {
+        readers.remove(&file_path);
+        while readers.len() >= self.max_open_files {
+            let Some(oldest) = readers
+                .iter()
+                .min_by_key(|(_, open)| open.last_used.load(Ordering::Relaxed))
+                .map(|(path, _)| path.clone())
+            else {
+                break;
+            };
+            log::debug!("Closing least recently used reader '{}'", oldest);
+            readers.remove(&oldest);
+        }
+        readers.insert(
+            file_path,
+            OpenReader {
+                reader,
+                last_used: AtomicU64::new(self.tick()),
+            },
+        );
+    }
+
+    fn tick(&self) -> u64 {
+        self.clock.fetch_add(1, Ordering::Relaxed)
+    }
+
+    fn lock_settings(&self) -> MutexGuard<'_, HashMap<String, ReaderSettings>> {
+        self.settings
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+/// Apply a sample limit to a trace block, preserving header consistency.
+fn apply_trace_limit(trace: TraceBlock, max_samples: Option<usize>) -> TraceBlock {
+    match max_samples {
+        Some(limit) => trace.downsample(limit, DownsampleMode::Stride),
+        None => trace,
+    }
+}
+
+/// Build a NumPy format 1.0 header for a C-ordered `(rows, cols)` float32 array.
+///
+/// The header is space-padded so the data starts on a 64-byte boundary.
+fn npy_header(rows: usize, cols: usize) -> Vec<u8> {
+    let dict = format!(
+        "{{'descr': '<f4', 'fortran_order': False, 'shape': ({}, {}), }}",
+        rows, cols
+    );
+    // Magic (6) + version (2) + header length (2) + dict + trailing newline.
+    let unpadded = 10 + dict.len() + 1;
+    let padding = (64 - unpadded % 64) % 64;
+    let header_len = (dict.len() + padding + 1) as u16;
+
+    let mut header = b"\x93NUMPY\x01\x00".to_vec();
+    header.extend_from_slice(&header_len.to_le_bytes());
+    header.extend_from_slice(dict.as_bytes());
+    header.resize(header.len() + padding, b' ');
+    header.push(b'\n');
+    header
+}
+
+/// Quote a CSV field when it contains a delimiter, quote, or line break.
+fn csv_field(value: &str) -> Cow<'_, str> {
+    if value.contains([',', '"', '\n', '\r']) {
+        Cow::Owned(format!("\"{}\"", value.replace('"', "\"\"")))
+    } else {
+        Cow::Borrowed(value)
+    }
+}
+
+/// Whether a decoded sample is finite and of a magnitude seismic data can have.
+fn plausible_magnitude(value: f32) -> bool {
+    value.is_finite() && (1.0 / PLAUSIBLE_MAGNITUDE..PLAUSIBLE_MAGNITUDE).contains(&value.abs())
+}
+
+const FNV_OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+
+/// Continue a 64-bit FNV-1a hash over `bytes`.
+fn fnv1a(hash: u64, bytes: &[u8]) -> u64 {
+    bytes.iter().fold(hash, |hash, &byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(FNV_PRIME)
+    })
+}
+
+/// Reject requests that would decode more than `MAX_TOTAL_SAMPLES` samples.
+fn check_sample_budget(count: usize, samples_per_trace: usize) -> Result<(), AppError> {
+    match count.checked_mul(samples_per_trace) {
+        Some(total) if total <= MAX_TOTAL_SAMPLES => Ok(()),
+        _ => Err(AppError::ValidationError {
+            message: format!(
+                "Requested {} traces of {} samples exceeds the limit of {} samples",
+                count, samples_per_trace, MAX_TOTAL_SAMPLES
+            ),
+        }),
+    }
+}
+
+/// Apply a sample limit to raw trace data.
 fn apply_data_limit(data: TraceData, max_samples: Option<usize>) -> TraceData {
     match max_samples {
-        Some(limit) => data.downsample(limit),
+        Some(limit) => data.downsample(limit, DownsampleMode::Stride),
         None => data,
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::segy::synthetic::{make_synthetic_segy, synthetic_trace_samples};
+    use std::path::PathBuf;
+
+    /// Write a big-endian IEEE float SEG-Y file with zeroed traces.
+    fn test_file_bytes(samples: u16, traces: usize, extra: usize) -> Vec<u8> {
+        let mut bytes = vec![0x40u8; constants::TEXTUAL_HEADER_SIZE];
+        let mut binary = vec![0u8; BinaryHeader::SIZE];
+        binary[16..18].copy_from_slice(&4000i16.to_be_bytes());
+        binary[20..22].copy_from_slice(&samples.to_be_bytes());
+        binary[24..26].copy_from_slice(&5i16.to_be_bytes());
+        bytes.extend_from_slice(&binary);
+
+        let mut trace = vec![0u8; TRACE_HEADER_SIZE + usize::from(samples) * 4];
+        trace[28..30].copy_from_slice(&1i16.to_be_bytes());
+        trace[114..116].copy_from_slice(&samples.to_be_bytes());
+        for _ in 0..traces {
+            bytes.extend_from_slice(&trace);
+        }
+        bytes.resize(bytes.len() + extra, 0);
+        bytes
+    }
+
+    fn write_test_file(name: &str, samples: u16, traces: usize, extra: usize) -> PathBuf {
+        let path = std::env::temp_dir().join(name);
+        std::fs::write(&path, test_file_bytes(samples, traces, extra)).unwrap();
+        path
+    }
+
+    #[test]
+    fn test_all_trace_stores_return_identical_traces() {
+        let bytes = make_synthetic_segy(4, 10);
+        let path = std::env::temp_dir().join("tracelens_trace_store_test.sgy");
+        std::fs::write(&path, &bytes).unwrap();
+        let mapped = SegyReader::open(path.to_str().unwrap(), 0, None).unwrap();
+        let seeked = {
+            let mut file = File::open(&path).unwrap();
+            let headers = io::read_headers(&mut file).unwrap();
+            let store = TraceStore::file(file, headers.file_size);
+            SegyReader::from_parts(String::new(), headers, store, None, 0, None).unwrap()
+        };
+        let memory = SegyReader::from_bytes(bytes).unwrap();
+
+        let single = |reader: &SegyReader| reader.load_single_trace(2, None).unwrap();
+        let blocks = |reader: &SegyReader| {
+            reader
+                .load_trace_range(1, 3, Some(5), false)
+                .unwrap()
+                .into_iter()
+                .map(|block| (block.header.num_samples, block.data.to_f32_vec()))
+                .collect::<Vec<_>>()
+        };
+        let data = |reader: &SegyReader| {
+            reader
+                .load_trace_data_range(0, 4, None, None)
+                .unwrap()
+                .iter()
+                .map(TraceData::to_f32_vec)
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            single(&memory).data.to_f32_vec(),
+            synthetic_trace_samples(2, 10)
+        );
+        for reader in [&mapped, &seeked] {
+            assert_eq!(
+                single(reader).data.to_f32_vec(),
+                single(&memory).data.to_f32_vec()
+            );
+            assert_eq!(blocks(reader), blocks(&memory));
+            assert_eq!(data(reader), data(&memory));
+        }
+        assert_eq!(
+            seeked.extract_header_field("num_samples", 0, 4).unwrap(),
+            vec![10; 4]
+        );
+
+        drop((mapped, seeked));
+        std::fs::remove_file(&path).ok();
+    }
+
+    #[test]
+    fn test_skip_aux_traces_leading_each_record() {
+        let mut bytes = test_file_bytes(4, 8, 0);
+        let binary = constants::TEXTUAL_HEADER_SIZE;
+        bytes[binary + 12..binary + 14].copy_from_slice(&3i16.to_be_bytes());
+        bytes[binary + 14..binary + 16].copy_from_slice(&1i16.to_be_bytes());
+        let trace_size = TRACE_HEADER_SIZE + 4 * 4;
+        for trace in 0..8 {
+            let offset = constants::FILE_HEADER_SIZE + trace * trace_size;
+            bytes[offset..offset + 4].copy_from_slice(&(trace as i32).to_be_bytes());
+        }
+        let reader = SegyReader::from_bytes(bytes).unwrap();
+        let sequence = |skip: bool| {
+            reader
+                .load_trace_range(1, 6, None, skip)
+                .unwrap()
+                .iter()
+                .map(|block| block.header.trace_seq_line)
+                .collect::<Vec<_>>()
+        };
+
+        // Records of four traces: aux trace 0 then data 1-3, aux 4 then data 5-7.
+        assert!(reader.is_aux_trace(4) && !reader.is_aux_trace(5));
+        assert_eq!(sequence(true), vec![1, 2, 3, 5, 6]);
+        assert_eq!(sequence(false), vec![1, 2, 3, 4, 5, 6]);
+    }
+
+    #[test]
+    fn test_sample_window_returns_requested_samples() {
+        let reader = SegyReader::from_bytes(make_synthetic_segy(2, 10)).unwrap();
+        let windowed = |fill_cache: bool| {
+            reader
+                .load_trace_data(0, 2, None, Some(3..6), fill_cache)
+                .unwrap()
+                .iter()
+                .map(TraceData::to_f32_vec)
+                .collect::<Vec<_>>()
+        };
+
+        let expected: Vec<Vec<f32>> = (0..2)
+            .map(|trace| synthetic_trace_samples(trace, 10)[3..6].to_vec())
+            .collect();
+        // Decoded straight from the window, then from cached whole traces.
+        assert_eq!(windowed(false), expected);
+        assert!(reader.lock_trace_cache().is_empty());
+        assert_eq!(windowed(true), expected);
+        assert_eq!(reader.lock_trace_cache().len(), 2);
+        assert_eq!(windowed(false), expected);
+        assert!(reader
+            .load_trace_data_range(0, 2, None, Some(5..11))
+            .is_err());
+        assert!(reader
+            .load_trace_data_range(0, 2, None, Some(4..4))
+            .is_err());
+    }
+
+    #[test]
+    fn test_repeated_data_range_reuses_cached_traces() {
+        let path = std::env::temp_dir().join("tracelens_data_range_cache_test.sgy");
+        std::fs::write(&path, make_synthetic_segy(3, 10)).unwrap();
+        let open = |cache_samples| {
+            let mut file = File::open(&path).unwrap();
+            let headers = io::read_headers(&mut file).unwrap();
+            let store = TraceStore::file(file, headers.file_size);
+            SegyReader::from_parts(String::new(), headers, store, None, cache_samples, None)
+                .unwrap()
+        };
+        let (cached, uncached) = (open(DEFAULT_TRACE_CACHE_SAMPLES), open(0));
+        let load = |reader: &SegyReader, window| {
+            reader
+                .load_trace_data_range(0, 3, None, window)
+                .unwrap()
+                .iter()
+                .map(TraceData::to_f32_vec)
+                .collect::<Vec<_>>()
+        };
+        let original = load(&cached, Some(2..5));
+
+        // Zero every sample on disk; only a reader that parses again sees it.
+        let mut zeroed = make_synthetic_segy(3, 10);
+        let trace_size = TRACE_HEADER_SIZE + 10 * 4;
+        for trace in zeroed[constants::FILE_HEADER_SIZE..].chunks_exact_mut(trace_size) {
+            trace[TRACE_HEADER_SIZE..].fill(0);
+        }
+        std::fs::write(&path, zeroed).unwrap();
+        assert_eq!(load(&cached, Some(2..5)), original);
+        assert_eq!(load(&cached, None)[1], synthetic_trace_samples(1, 10));
+        assert_eq!(load(&uncached, Some(2..5)), vec![vec![0.0; 3]; 3]);
+        drop((cached, uncached));
+        std::fs::remove_file(&path).ok();
+    }
+
+    #[test]
+    fn test_header_reads_slice_only_header_bytes() {
+        let reader = SegyReader::from_bytes(make_synthetic_segy(3, 10)).unwrap();
+        let header = reader.trace_header_slice(2, 1, TRACE_HEADER_SIZE).unwrap();
+        assert_eq!(header.len(), TRACE_HEADER_SIZE);
+        assert_eq!(header[..4], 3i32.to_be_bytes());
+
+        // Only the field's own bytes are read for a header field scan.
+        let field = reader.trace_header_field("cdp_ensemble_number").unwrap();
+        let (first, last) = (usize::from(field.byte_start), usize::from(field.byte_end));
+        assert_eq!(reader.trace_header_slice(2, first, last).unwrap().len(), 4);
+        assert_eq!(reader.read_header_field(&field, 2).unwrap(), 3);
+        assert!(reader.trace_header_slice(2, 0, 4).is_err());
+        assert!(reader.trace_header_slice(2, 1, 10_000).is_err());
+    }
+
+    #[test]
+    fn test_from_bytes_matches_file_reader() {
+        let path = write_test_file("tracelens_from_bytes_test.sgy", 10, 3, 20);
+        let file_reader = SegyReader::open(path.to_str().unwrap(), 0, None).unwrap();
+        let file_traces = file_reader.load_trace_data_range(0, 3, None, None).unwrap();
+        std::fs::remove_file(&path).ok();
+
+        let reader = SegyReader::from_bytes(test_file_bytes(10, 3, 20)).unwrap();
+        let data = reader.data();
+        assert_eq!(reader.file_path(), "");
+        assert_eq!(data.total_traces, Some(3));
+        assert_eq!(data.trailing_bytes, 20);
+        let traces = reader.load_trace_data_range(0, 3, None, None).unwrap();
+        assert_eq!(traces.len(), file_traces.len());
+        for (trace, expected) in traces.iter().zip(&file_traces) {
+            assert_eq!(trace.to_f32_vec(), expected.to_f32_vec());
+        }
+    }
+
+    #[test]
+    fn test_trailing_bytes_reported() {
+        let path = write_test_file("tracelens_trailing_test.sgy", 10, 2, 50);
+        let reader = SegyReader::open(path.to_str().unwrap(), 0, None).unwrap();
+        let data = reader.data();
+        std::fs::remove_file(&path).ok();
+
+        assert_eq!(data.total_traces, Some(2));
+        assert_eq!(data.trailing_bytes, 50);
+    }
+
+    #[test]
+    fn test_variable_extended_textual_headers_end_at_stanza() {
+        let plain = test_file_bytes(10, 3, 0);
+        let mut bytes = plain[..constants::FILE_HEADER_SIZE].to_vec();
+        // Rev 1 with a -1 (stanza-terminated) extended textual header count.
+        let binary = constants::TEXTUAL_HEADER_SIZE;
+        bytes[binary + 300..binary + 302].copy_from_slice(&0x0100u16.to_be_bytes());
+        bytes[binary + 302..binary + 304].copy_from_slice(&1i16.to_be_bytes());
+        bytes[binary + 304..binary + 306].copy_from_slice(&(-1i16).to_be_bytes());
+
+        let mut first = b"((SEG: Layer))".to_vec();
+        first.resize(constants::TEXTUAL_HEADER_SIZE, b' ');
+        let mut last = b"((SEG: EndText))".to_vec();
+        last.resize(constants::TEXTUAL_HEADER_SIZE, b' ');
+        bytes.extend_from_slice(&first);
+        bytes.extend_from_slice(&last);
+        bytes.extend_from_slice(&plain[constants::FILE_HEADER_SIZE..]);
+
+        let reader = SegyReader::from_bytes(bytes).unwrap();
+        let data = reader.data();
+
+        assert_eq!(
+            reader.config().data_offset,
+            constants::FILE_HEADER_SIZE + 2 * constants::TEXTUAL_HEADER_SIZE
+        );
+        assert_eq!(data.total_traces, Some(3));
+        assert_eq!(data.trailing_bytes, 0);
+        let header = reader.load_single_trace(2, None).unwrap().header;
+        assert_eq!(header.num_samples, 10);
+    }
+
+    #[test]
+    fn test_count_traces_from_headers() {
+        let path = write_test_file("tracelens_count_test.sgy", 10, 3, 20);
+        let zero_path = write_test_file("tracelens_count_zero_test.sgy", 0, 3, 0);
+        let count = SegyReader::count_traces(path.to_str().unwrap());
+        let zero_count = SegyReader::count_traces(zero_path.to_str().unwrap());
+        std::fs::remove_file(&path).ok();
+        std::fs::remove_file(&zero_path).ok();
+
+        assert_eq!(count.unwrap(), Some(3));
+        // Neither the binary nor the trace headers give a sample count.
+        assert_eq!(zero_count.unwrap(), None);
+    }
+
+    #[test]
+    fn test_load_trace_headers_with_stride() {
+        let path = write_test_file("tracelens_headers_test.sgy", 10, 5, 0);
+        let reader = SegyReader::open(path.to_str().unwrap(), 0, None).unwrap();
+        let headers = reader.load_trace_headers_range(0, 3, 2);
+        let overflow = reader.load_trace_headers_range(1, 3, 2);
+        std::fs::remove_file(&path).ok();
+
+        let headers = headers.unwrap();
+        assert_eq!(headers.len(), 3);
+        assert!(headers.iter().all(|h| h.num_samples == 10));
+        assert!(overflow.is_err());
+    }
+
+    #[test]
+    fn test_header_field_range_and_column() {
+        let path = write_test_file("tracelens_field_range_test.sgy", 10, 4, 0);
+        let mut bytes = std::fs::read(&path).unwrap();
+        let trace_size = TRACE_HEADER_SIZE + 10 * 4;
+        for (trace, cdp) in [7i32, -3, 12, 5].iter().enumerate() {
+            let offset = constants::FILE_HEADER_SIZE + trace * trace_size + 20;
+            bytes[offset..offset + 4].copy_from_slice(&cdp.to_be_bytes());
+        }
+        std::fs::write(&path, bytes).unwrap();
+
+        let reader = SegyReader::open(path.to_str().unwrap(), 0, None).unwrap();
+        let range = reader.header_field_range("cdp_ensemble_number", &|_| {});
+        let column = reader.extract_header_field("cdp_ensemble_number", 1, 3);
+        let unknown = reader.header_field_range("not_a_field", &|_| {});
+        std::fs::remove_file(&path).ok();
+
+        assert_eq!(range.unwrap(), (-3, 12));
+        assert_eq!(column.unwrap(), vec![-3, 12, 5]);
+        assert!(matches!(unknown, Err(AppError::ValidationError { .. })));
+    }
+
+    #[test]
+    fn test_find_trace_by_field_scans_or_bisects() {
+        // CDP numbers run 1..=9, two traces each; field records count down.
+        let mut bytes = make_synthetic_segy(18, 4);
+        let trace_size = TRACE_HEADER_SIZE + 4 * 4;
+        for trace in 0..18 {
+            let offset = constants::FILE_HEADER_SIZE + trace * trace_size;
+            let cdp = (trace / 2) as i32 + 1;
+            let record = 100 - trace as i32;
+            bytes[offset + 20..offset + 24].copy_from_slice(&cdp.to_be_bytes());
+            bytes[offset + 8..offset + 12].copy_from_slice(&record.to_be_bytes());
+        }
+        let reader = SegyReader::from_bytes(bytes).unwrap();
+
+        for sorted in [false, true] {
+            let find =
+                |target| reader.find_trace_by_field("cdp_ensemble_number", target, sorted, &|_| {});
+            assert_eq!(find(1).unwrap(), Some(0));
+            assert_eq!(find(5).unwrap(), Some(8));
+            assert_eq!(find(9).unwrap(), Some(16));
+            assert_eq!(find(0).unwrap(), None);
+            assert_eq!(find(42).unwrap(), None);
+        }
+        assert_eq!(
+            reader
+                .find_trace_by_field("field_record_number", 90, true, &|_| {})
+                .unwrap(),
+            Some(10)
+        );
+        assert!(reader
+            .find_trace_by_field("not_a_field", 1, false, &|_| {})
+            .is_err());
+    }
+
+    #[test]
+    fn test_summary_labels_file_metadata() {
+        let mut bytes = make_synthetic_segy(4, 10);
+        // Trace sorting (bytes 3229-3230) and measurement system (3255-3256).
+        bytes[3228..3230].copy_from_slice(&2i16.to_be_bytes());
+        bytes[3254..3256].copy_from_slice(&1i16.to_be_bytes());
+        let summary = SegyReader::from_bytes(bytes).unwrap().summary().unwrap();
+
+        assert_eq!(summary.revision, "SEG-Y Rev 0 (1975)");
+        assert_eq!(summary.byte_order, ByteOrder::BigEndian);
+        assert_eq!(summary.sample_format, "IEEE float (32-bit)");
+        assert_eq!(summary.samples_per_trace, 10);
+        assert_eq!(summary.sample_interval_ms, 4.0);
+        assert_eq!(summary.total_traces, Some(4));
+        assert_eq!(summary.trailing_bytes, 0);
+        assert_eq!(summary.sort, "CDP ensemble");
+        assert_eq!(summary.measurement_system, "Meters");
+    }
+
+    #[test]
+    fn test_rev2_extended_trace_headers_shift_samples() {
+        // Rev 2 binary header declaring one extra header block per trace.
+        let synthetic = make_synthetic_segy(3, 8);
+        let mut bytes = synthetic[..constants::FILE_HEADER_SIZE].to_vec();
+        let binary = constants::TEXTUAL_HEADER_SIZE;
+        bytes[binary + 300..binary + 302].copy_from_slice(&0x0200u16.to_be_bytes());
+        bytes[binary + 306..binary + 310].copy_from_slice(&1i32.to_be_bytes());
+        let trace_size = TRACE_HEADER_SIZE + 8 * 4;
+        for trace in synthetic[constants::FILE_HEADER_SIZE..].chunks_exact(trace_size) {
+            bytes.extend_from_slice(&trace[..TRACE_HEADER_SIZE]);
+            bytes.extend_from_slice(&[0xAB; TRACE_HEADER_SIZE]);
+            bytes.extend_from_slice(&trace[TRACE_HEADER_SIZE..]);
+        }
+        let plain = SegyReader::from_bytes(synthetic).unwrap();
+        let reader = SegyReader::from_bytes(bytes.clone()).unwrap();
+
+        assert_eq!(reader.config().extended_trace_headers, 1);
+        assert_eq!(reader.data().total_traces, Some(3));
+        assert_eq!(reader.data().trailing_bytes, 0);
+        let block = reader.load_single_trace(2, None).unwrap();
+        assert_eq!(block.header.cdp_ensemble_number, 3);
+        assert_eq!(block.extended_headers, vec![0xAB; TRACE_HEADER_SIZE]);
+        assert_eq!(
+            block.data.to_f32_vec(),
+            plain.load_single_trace(2, None).unwrap().data.to_f32_vec()
+        );
+        let data = reader.load_trace_data_range(0, 3, None, None).unwrap();
+        assert_eq!(
+            data[1].to_f32_vec(),
+            plain.load_trace_data_range(1, 1, None, None).unwrap()[0].to_f32_vec()
+        );
+
+        // Override configs without the field still honor the binary header.
+        let path = std::env::temp_dir().join("tracelens_rev2_extended_test.sgy");
+        std::fs::write(&path, bytes).unwrap();
+        let override_config = SegyFileConfig {
+            extended_trace_headers: 0,
+            ..reader.config().clone()
+        };
+        let overridden = SegyReader::open(path.to_str().unwrap(), 0, Some(override_config));
+        std::fs::remove_file(&path).ok();
+        let overridden = overridden.unwrap();
+        assert_eq!(overridden.config().extended_trace_headers, 1);
+        assert_eq!(overridden.data().total_traces, Some(3));
+        assert_eq!(overridden.data().trailing_bytes, 0);
+    }
+
+    #[test]
+    fn test_samples_inferred_from_trace_header_count_traces() {
+        // Binary header sample count left at zero; trace headers say 10.
+        let mut bytes = make_synthetic_segy(5, 10);
+        let binary = constants::TEXTUAL_HEADER_SIZE;
+        bytes[binary + 20..binary + 22].copy_from_slice(&0u16.to_be_bytes());
+        let path = std::env::temp_dir().join("tracelens_inferred_count_test.sgy");
+        std::fs::write(&path, &bytes).unwrap();
+        let count = SegyReader::count_traces(path.to_str().unwrap());
+        std::fs::remove_file(&path).ok();
+        let reader = SegyReader::from_bytes(bytes).unwrap();
+
+        // The header-only count agrees with the opened reader.
+        assert_eq!(count.unwrap(), Some(5));
+        assert_eq!(reader.config().samples_per_trace, 10);
+        assert!(reader.config().samples_per_trace_inferred);
+        assert_eq!(reader.data().total_traces, Some(5));
+        assert_eq!(reader.data().trailing_bytes, 0);
+        assert_eq!(
+            reader.load_single_trace(4, None).unwrap().data.to_f32_vec(),
+            synthetic_trace_samples(4, 10)
+        );
+    }
+
+    #[test]
+    fn test_variable_length_traces_use_offset_table() {
+        // Rev 1 binary header with the fixed length flag cleared.
+        let mut bytes = test_file_bytes(4, 0, 0);
+        let binary = constants::TEXTUAL_HEADER_SIZE;
+        bytes[binary + 300..binary + 302].copy_from_slice(&0x0100u16.to_be_bytes());
+        for samples in [4u16, 6, 2] {
+            let mut trace = vec![0u8; TRACE_HEADER_SIZE + usize::from(samples) * 4];
+            trace[28..30].copy_from_slice(&1i16.to_be_bytes());
+            trace[114..116].copy_from_slice(&samples.to_be_bytes());
+            for sample in trace[TRACE_HEADER_SIZE..].chunks_exact_mut(4) {
+                sample.copy_from_slice(&f32::from(samples).to_be_bytes());
+            }
+            bytes.extend_from_slice(&trace);
+        }
+        // Too short for another trace header.
+        bytes.extend_from_slice(&[0; 10]);
+        let reader = SegyReader::from_bytes(bytes).unwrap();
+
+        assert!(!reader.config().fixed_length_traces);
+        let start = constants::FILE_HEADER_SIZE;
+        let block = |samples: usize| TRACE_HEADER_SIZE + samples * 4;
+        assert_eq!(
+            reader.build_trace_offset_table().unwrap(),
+            vec![
+                start,
+                start + block(4),
+                start + block(4) + block(6),
+                start + block(4) + block(6) + block(2),
+            ]
+        );
+        assert_eq!(reader.data().total_traces, Some(3));
+        assert_eq!(reader.data().trailing_bytes, 10);
+        assert_eq!(
+            reader.load_single_trace(1, None).unwrap().data.to_f32_vec(),
+            vec![6.0; 6]
+        );
+
+        // Unequal lengths fail the NumPy export without leaving a file.
+        let path = std::env::temp_dir().join("tracelens_ragged_samples_test.npy");
+        assert!(reader.export_samples_npy(&path, 0, 3, &|_| {}).is_err());
+        assert!(!path.exists());
+        assert!(!path.with_extension("npy.partial").exists());
+        assert!(reader
+            .export_samples_npy(&path, usize::MAX, 2, &|_| {})
+            .is_err());
+    }
+
+    #[test]
+    fn test_header_consistency_reports_sample_count_mismatch() {
+        let path = write_test_file("tracelens_consistency_test.sgy", 10, 3, 0);
+        let mut bytes = std::fs::read(&path).unwrap();
+        let offset = constants::FILE_HEADER_SIZE + (TRACE_HEADER_SIZE + 10 * 4) + 114;
+        bytes[offset..offset + 2].copy_from_slice(&11i16.to_be_bytes());
+        std::fs::write(&path, bytes).unwrap();
+
+        let reader = SegyReader::open(path.to_str().unwrap(), 0, None).unwrap();
+        let warnings = reader.check_header_consistency();
+        std::fs::remove_file(&path).ok();
+
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings[0].trace_index, 1);
+        assert_eq!(warnings[0].field_key, "num_samples");
+        assert_eq!(
+            (warnings[0].binary_value, warnings[0].trace_value),
+            (10, 11)
+        );
+    }
+
+    #[test]
+    fn test_override_config_replaces_binary_header_geometry() {
+        let path = write_test_file("tracelens_override_test.sgy", 10, 2, 0);
+        let mut bytes = std::fs::read(&path).unwrap();
+        // Corrupt the binary header sample count (bytes 3221-3222).
+        bytes[3220..3222].copy_from_slice(&7i16.to_be_bytes());
+        std::fs::write(&path, bytes).unwrap();
+
+        let derived = SegyReader::open(path.to_str().unwrap(), 0, None).unwrap();
+        let override_config = SegyFileConfig {
+            samples_per_trace: 10,
+            ..derived.config().clone()
+        };
+        let fixed = SegyReader::open(path.to_str().unwrap(), 0, Some(override_config)).unwrap();
+        let trace = fixed.load_single_trace(1, None);
+        std::fs::remove_file(&path).ok();
+
+        assert_eq!(derived.config().samples_per_trace, 7);
+        assert_ne!(derived.data().trailing_bytes, 0);
+        assert_eq!(fixed.data().total_traces, Some(2));
+        assert_eq!(fixed.data().trailing_bytes, 0);
+        assert_eq!(fixed.binary_header().samples_per_trace, 7);
+        assert_eq!(trace.unwrap().data.len(), 10);
+    }
+
+    #[test]
+    fn test_raw_textual_header_is_untouched() {
+        let path = write_test_file("tracelens_raw_text_test.sgy", 10, 1, 0);
+        let reader = SegyReader::open(path.to_str().unwrap(), 0, None).unwrap();
+        let raw = crate::segy::RawTextualHeader::from(reader.textual_header());
+        std::fs::remove_file(&path).ok();
+
+        assert_eq!(raw.bytes, vec![0x40u8; constants::TEXTUAL_HEADER_SIZE]);
+        assert_eq!(raw.encoding, crate::segy::TextEncoding::Ebcdic);
+    }
+
+    #[test]
+    fn test_raw_binary_header_is_untouched() {
+        let bytes = test_file_bytes(10, 1, 0);
+        let reader = SegyReader::from_bytes(bytes.clone()).unwrap();
+        let raw = reader.raw_binary_header().unwrap();
+
+        assert_eq!(
+            raw.bytes,
+            bytes[constants::TEXTUAL_HEADER_SIZE..constants::FILE_HEADER_SIZE]
+        );
+        assert_eq!(raw.byte_order, ByteOrder::BigEndian);
+    }
+
+    #[test]
+    fn test_diff_trace_headers_far_apart() {
+        let mut bytes = test_file_bytes(4, 2000, 0);
+        let trace_size = TRACE_HEADER_SIZE + 4 * 4;
+        let last = constants::FILE_HEADER_SIZE + 1999 * trace_size;
+        bytes[last + 8..last + 12].copy_from_slice(&42i32.to_be_bytes());
+        let reader = SegyReader::from_bytes(bytes).unwrap();
+
+        let diffs = reader.diff_trace_headers(0, 1999).unwrap();
+        assert_eq!(diffs.len(), 1);
+        assert_eq!(diffs[0].field_key, "field_record_number");
+        assert_eq!((diffs[0].value_a, diffs[0].value_b), (0, 42));
+        assert!(reader.diff_trace_headers(1, 1).unwrap().is_empty());
+        assert!(reader.diff_trace_headers(0, 2000).is_err());
+    }
+
+    #[test]
+    fn test_header_overrides_remap_field_reads() {
+        let mut bytes = test_file_bytes(4, 3, 0);
+        let trace_size = TRACE_HEADER_SIZE + 4 * 4;
+        for trace in 0..3 {
+            let offset = constants::FILE_HEADER_SIZE + trace * trace_size;
+            bytes[offset + 8..offset + 12].copy_from_slice(&(500 + trace as i32).to_be_bytes());
+        }
+        let reader = SegyReader::from_bytes(bytes).unwrap();
+        let inline = HeaderFieldSpec {
+            name: "Inline number".to_string(),
+            field_key: "inline_number".to_string(),
+            byte_start: 9,
+            byte_end: 12,
+            data_type: "int32".to_string(),
+            description: "Inline stored in the field record bytes".to_string(),
+            required: false,
+            code_mapping: None,
+        };
+
+        assert!(reader.extract_header_field("inline_number", 0, 3).is_err());
+        reader.set_header_overrides(vec![inline.clone()]).unwrap();
+        assert_eq!(
+            reader.extract_header_field("inline_number", 0, 3).unwrap(),
+            vec![500, 501, 502]
+        );
+        let fields = reader
+            .effective_trace_spec()
+            .unwrap()
+            .get_trace_header_fields();
+        assert!(!fields.iter().any(|f| f.field_key == "field_record_number"));
+
+        let invalid = HeaderFieldSpec {
+            byte_end: 400,
+            ..inline
+        };
+        assert!(reader.set_header_overrides(vec![invalid]).is_err());
+        assert!(reader.extract_header_field("inline_number", 0, 1).is_ok());
+        reader.set_header_overrides(Vec::new()).unwrap();
+        assert!(reader
+            .extract_header_field("field_record_number", 0, 1)
+            .is_ok());
+    }
+
+    #[test]
+    fn test_export_headers_csv() {
+        let mut bytes = test_file_bytes(4, 3, 0);
+        let trace_size = TRACE_HEADER_SIZE + 4 * 4;
+        for trace in 0..3 {
+            let offset = constants::FILE_HEADER_SIZE + trace * trace_size;
+            bytes[offset + 20..offset + 24].copy_from_slice(&(100 + trace as i32).to_be_bytes());
+        }
+        let reader = SegyReader::from_bytes(bytes).unwrap();
+        let path = std::env::temp_dir().join("tracelens_headers_test.csv");
+        let keys = ["cdp_ensemble_number".to_string(), "num_samples".to_string()];
+
+        reader
+            .export_headers_csv(&path, &keys, 1, 2, &|_| {})
+            .unwrap();
+        let csv = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).ok();
+
+        let lines: Vec<_> = csv.lines().collect();
+        assert_eq!(lines.len(), 3);
+        assert_eq!(lines[0].split(',').count(), 2);
+        assert_eq!(&lines[1..], ["101,4", "102,4"]);
+        assert!(reader
+            .export_headers_csv(&path, &["bogus".to_string()], 0, 1, &|_| {})
+            .is_err());
+        assert_eq!(csv_field("a,b \"c\""), "\"a,b \"\"c\"\"\"");
+    }
+
+    #[test]
+    fn test_chunked_scan_releases_pages_without_losing_data() {
+        let path = write_test_file("tracelens_chunked_scan_test.sgy", 10, 25, 0);
+        let reader = SegyReader::open(path.to_str().unwrap(), 0, None).unwrap();
+        let samples = |start: usize, count: usize| {
+            reader
+                .load_trace_data_range(start, count, None, None)
+                .unwrap()
+                .iter()
+                .map(TraceData::to_f32_vec)
+                .collect::<Vec<_>>()
+        };
+
+        let mut chunks = Vec::new();
+        let mut scanned = Vec::new();
+        reader
+            .scan_traces_chunked(10, |chunk| {
+                scanned.extend(samples(chunk.start, chunk.len()));
+                chunks.push(chunk);
+                Ok(ControlFlow::Continue(()))
+            })
+            .unwrap();
+        assert_eq!(chunks, vec![0..10, 10..20, 20..25]);
+        assert!(reader
+            .scan_traces_chunked(0, |_| Ok(ControlFlow::Continue(())))
+            .is_err());
+
+        let mut visited = Vec::new();
+        reader
+            .scan_traces_chunked(10, |chunk| {
+                visited.push(chunk);
+                Ok(ControlFlow::Break(()))
+            })
+            .unwrap();
+        assert_eq!(visited, vec![0..10]);
+
+        // Released pages are read back from the file on the next access.
+        assert_eq!(samples(0, 25), scanned);
+        drop(reader);
+        std::fs::remove_file(&path).ok();
+    }
+
+    #[test]
+    fn test_scans_report_progress_in_intervals() {
+        let count = SCAN_PROGRESS_INTERVAL + 10;
+        let reader = SegyReader::from_bytes(test_file_bytes(1, count, 0)).unwrap();
+        let reports = Mutex::new(Vec::new());
+        let record = |progress: ScanProgress| reports.lock().unwrap().push(progress);
+
+        reader.header_field_range("num_samples", &record).unwrap();
+        let path = std::env::temp_dir().join("tracelens_progress_test.csv");
+        let keys = ["num_samples".to_string()];
+        reader
+            .export_headers_csv(&path, &keys, 5, count - 5, &record)
+            .unwrap();
+        std::fs::remove_file(&path).ok();
+        // No trace matches, so the whole file is scanned.
+        reader
+            .find_trace_by_field("num_samples", 2, false, &record)
+            .unwrap();
+        reader.amplitude_histogram(0, 2000, 4, &record).unwrap();
+
+        let progress = |processed, total| ScanProgress { processed, total };
+        assert_eq!(
+            reports.into_inner().unwrap(),
+            vec![
+                progress(SCAN_PROGRESS_INTERVAL, count),
+                progress(count, count),
+                progress(SCAN_PROGRESS_INTERVAL, count - 5),
+                progress(count - 5, count - 5),
+                progress(SCAN_PROGRESS_INTERVAL, count),
+                progress(count, count),
+                progress(SAMPLE_CHUNK_TRACES, 4000),
+                progress(2000, 4000),
+                progress(2000 + SAMPLE_CHUNK_TRACES, 4000),
+                progress(4000, 4000),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_export_samples_npy() {
+        let reader = SegyReader::from_bytes(make_synthetic_segy(2, 3)).unwrap();
+        let path = std::env::temp_dir().join("tracelens_samples_test.npy");
+
+        reader.export_samples_npy(&path, 0, 2, &|_| {}).unwrap();
+        let npy = std::fs::read(&path).unwrap();
+        std::fs::remove_file(&path).ok();
+
+        assert_eq!(&npy[..8], b"\x93NUMPY\x01\x00");
+        let data_start = 10 + u16::from_le_bytes([npy[8], npy[9]]) as usize;
+        assert_eq!(data_start % 64, 0);
+        let header = std::str::from_utf8(&npy[10..data_start]).unwrap();
+        assert!(header.contains("'shape': (2, 3)") && header.ends_with('\n'));
+        let values: Vec<f32> = npy[data_start..]
+            .chunks_exact(4)
+            .map(|b| f32::from_le_bytes(b.try_into().unwrap()))
+            .collect();
+        let expected = [synthetic_trace_samples(0, 3), synthetic_trace_samples(1, 3)].concat();
+        assert_eq!(values, expected);
+        assert!(reader.export_samples_npy(&path, 0, 0, &|_| {}).is_err());
+    }
+
+    #[test]
+    fn test_oversized_range_exceeds_sample_budget() {
+        let reader = SegyReader::from_bytes(test_file_bytes(1000, 2, 0)).unwrap();
+        let oversized = MAX_TOTAL_SAMPLES / 1000 + 1;
+
+        for result in [
+            reader
+                .load_trace_range(0, oversized, None, false)
+                .map(|_| ()),
+            reader
+                .load_trace_data_range(0, oversized, None, None)
+                .map(|_| ()),
+        ] {
+            match result {
+                Err(AppError::ValidationError { message }) => {
+                    assert!(message.contains("exceeds the limit"), "{}", message)
+                }
+                other => panic!("expected a sample budget error, got {:?}", other),
+            }
+        }
+        // Downsampling and windowing shrink the budget needed per trace.
+        assert!(reader.load_trace_range(0, 2, Some(10), false).is_ok());
+        assert!(matches!(
+            reader.load_trace_data_range(0, oversized, None, Some(0..10)),
+            Err(AppError::ValidationError { message }) if message.contains("exceeds total traces")
+        ));
+    }
+
+    #[test]
+    fn test_amplitude_histogram_streams_samples() {
+        let mut bytes = test_file_bytes(4, 3, 0);
+        let trace_size = TRACE_HEADER_SIZE + 4 * 4;
+        let zeros = SegyReader::from_bytes(bytes.clone()).unwrap();
+        for (index, value) in [-2.0f32, -1.0, 0.0, 1.0, 2.0, 2.0].iter().enumerate() {
+            let offset = constants::FILE_HEADER_SIZE
+                + (index / 4) * trace_size
+                + TRACE_HEADER_SIZE
+                + (index % 4) * 4;
+            bytes[offset..offset + 4].copy_from_slice(&value.to_be_bytes());
+        }
+        let reader = SegyReader::from_bytes(bytes).unwrap();
+
+        let histogram = reader.amplitude_histogram(0, 3, 4, &|_| {}).unwrap();
+        assert_eq!(histogram.edges, vec![-2.0, -1.0, 0.0, 1.0, 2.0]);
+        assert_eq!(histogram.counts, vec![1, 1, 7, 3]);
+
+        let flat = zeros.amplitude_histogram(0, 3, 4, &|_| {}).unwrap();
+        assert_eq!((flat.edges, flat.counts), (vec![0.0, 0.0], vec![12]));
+        assert!(reader.amplitude_histogram(0, 3, 0, &|_| {}).is_err());
+    }
+
+    #[test]
+    fn test_content_hash_tracks_header_and_file_changes() {
+        let path = write_test_file("tracelens_content_hash_test.sgy", 10, 2, 0);
+        let reader = SegyReader::open(path.to_str().unwrap(), 0, None).unwrap();
+        let first = reader.content_hash().unwrap();
+        assert_eq!(reader.content_hash().unwrap(), first);
+        assert_eq!(fnv1a(FNV_OFFSET_BASIS, b"a"), 0xaf63_dc4c_8601_ec8c);
+
+        let mut bytes = std::fs::read(&path).unwrap();
+        bytes.extend_from_slice(&[0; 4]);
+        std::fs::write(&path, &bytes).unwrap();
+        let grown = reader.content_hash().unwrap();
+        std::fs::remove_file(&path).ok();
+        assert_ne!(grown, first);
+
+        let memory = SegyReader::from_bytes(test_file_bytes(10, 2, 0)).unwrap();
+        let mut edited = test_file_bytes(10, 2, 0);
+        edited[constants::TEXTUAL_HEADER_SIZE + 16] = 0x20;
+        let edited = SegyReader::from_bytes(edited).unwrap();
+        assert_ne!(
+            memory.content_hash().unwrap(),
+            edited.content_hash().unwrap()
+        );
+    }
+
+    #[test]
+    fn test_reader_state_evicts_least_recently_used() {
+        let paths: Vec<String> = (0..3)
+            .map(|i| {
+                let name = format!("tracelens_reader_state_{}.sgy", i);
+                write_test_file(&name, 10, 2, 0)
+                    .to_string_lossy()
+                    .into_owned()
+            })
+            .collect();
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap();
+
+        runtime.block_on(async {
+            let state = SegyReaderState::with_limits(0, 2);
+            let first = state.get_or_open(paths[0].clone()).await.unwrap();
+            state.get_or_open(paths[1].clone()).await.unwrap();
+            // Touch the first file so the second becomes least recently used.
+            let again = state.get_or_open(paths[0].clone()).await.unwrap();
+            assert!(Arc::ptr_eq(&first, &again));
+
+            state.get_or_open(paths[2].clone()).await.unwrap();
+            let open = state.readers.read().await;
+            assert!(open.contains_key(&paths[0]) && open.contains_key(&paths[2]));
+            assert!(!open.contains_key(&paths[1]));
+            drop(open);
+
+            state.close(&paths[0]).await;
+            let reopened = state.get_or_open(paths[0].clone()).await.unwrap();
+            assert!(!Arc::ptr_eq(&first, &reopened));
+        });
+        for path in &paths {
+            std::fs::remove_file(path).ok();
+        }
+    }
+
+    #[test]
+    fn test_evicted_reader_reopens_with_overrides() {
+        let paths: Vec<String> = (0..2)
+            .map(|i| {
+                let name = format!("tracelens_reader_overrides_{}.sgy", i);
+                write_test_file(&name, 10, 2, 0)
+                    .to_string_lossy()
+                    .into_owned()
+            })
+            .collect();
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap();
+
+        runtime.block_on(async {
+            let state = SegyReaderState::with_limits(0, 1);
+            let derived = state.get_or_open(paths[0].clone()).await.unwrap();
+            let override_config = SegyFileConfig {
+                samples_per_trace: 5,
+                ..derived.config().clone()
+            };
+            state
+                .open(paths[0].clone(), Some(override_config))
+                .await
+                .unwrap();
+            let inline = HeaderFieldSpec {
+                name: "Inline number".to_string(),
+                field_key: "inline_number".to_string(),
+                byte_start: 9,
+                byte_end: 12,
+                data_type: "int32".to_string(),
+                description: "Inline stored in the field record bytes".to_string(),
+                required: false,
+                code_mapping: None,
+            };
+            state
+                .set_header_overrides(paths[0].clone(), vec![inline])
+                .await
+                .unwrap();
+
+            // Opening the second file evicts the first.
+            state.get_or_open(paths[1].clone()).await.unwrap();
+            let reopened = state.get_or_open(paths[0].clone()).await.unwrap();
+            assert_eq!(reopened.config().samples_per_trace, 5);
+            let spec = reopened.effective_trace_spec().unwrap();
+            let fields = spec.get_trace_header_fields();
+            let inline = fields.iter().find(|f| f.field_key == "inline_number");
+            assert_eq!(inline.unwrap().byte_start, 9);
+
+            // Closing forgets the overrides.
+            state.close(&paths[0]).await;
+            let fresh = state.get_or_open(paths[0].clone()).await.unwrap();
+            assert_eq!(fresh.config().samples_per_trace, 10);
+        });
+        for path in &paths {
+            std::fs::remove_file(path).ok();
+        }
+    }
+
+    #[test]
+    fn test_close_releases_reader() {
+        let path = write_test_file("tracelens_close_test.sgy", 10, 2, 0)
+            .to_string_lossy()
+            .into_owned();
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap();
+
+        runtime.block_on(async {
+            let state = SegyReaderState::new();
+            let reader = state.get_or_open(path.clone()).await.unwrap();
+            assert_eq!(Arc::strong_count(&reader), 2);
+
+            assert!(state.close(&path).await);
+            assert!(state.readers.read().await.get(&path).is_none());
+            // Only this handle is left, so dropping it unmaps the file.
+            assert_eq!(Arc::strong_count(&reader), 1);
+            assert!(!state.close(&path).await);
+            assert!(!state.close("not_open.sgy").await);
+        });
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_detect_sample_format_spots_mislabeled_ibm() {
+        // The test file declares IEEE floats (code 5).
+        let samples = 50;
+        let with_words = |word: &dyn Fn(usize) -> u32| {
+            let mut bytes = test_file_bytes(samples as u16, 4, 0);
+            let trace_size = TRACE_HEADER_SIZE + samples * 4;
+            for trace in 0..4 {
+                for sample in 0..samples {
+                    let offset = constants::FILE_HEADER_SIZE
+                        + trace * trace_size
+                        + TRACE_HEADER_SIZE
+                        + sample * 4;
+                    let value = word(trace * samples + sample);
+                    bytes[offset..offset + 4].copy_from_slice(&value.to_be_bytes());
+                }
+            }
+            SegyReader::from_bytes(bytes).unwrap()
+        };
+
+        let ieee = with_words(&|i| ((i as f32 * 0.3).sin() * 250.0).to_bits());
+        let suggestion = ieee.detect_sample_format().unwrap();
+        assert_eq!((suggestion.declared, suggestion.suggested), (5, 5));
+        // sin(0) is a zero word, which says nothing about the format.
+        assert_eq!(suggestion.samples_checked, 199);
+        assert!(suggestion.ibm_implausible > suggestion.ieee_implausible);
+
+        // Normalized IBM words: sign, exponent 0x41-0x43, leading hex digit 1-15.
+        let ibm = with_words(&|i| {
+            let sign = if i % 2 == 0 { 0 } else { 0x8000_0000 };
+            let exponent = 0x41 + (i % 3) as u32;
+            let fraction = (1 + (i % 15) as u32) << 20 | (i as u32 * 2_654_435) & 0xF_FFFF;
+            sign | exponent << 24 | fraction
+        });
+        let suggestion = ibm.detect_sample_format().unwrap();
+        assert_eq!((suggestion.declared, suggestion.suggested), (5, 1));
+        assert_eq!(suggestion.ibm_implausible, 0.0);
+
+        let zeros = SegyReader::from_bytes(test_file_bytes(10, 4, 0)).unwrap();
+        let suggestion = zeros.detect_sample_format().unwrap();
+        assert_eq!((suggestion.suggested, suggestion.samples_checked), (5, 0));
+    }
+
+    #[test]
+    fn test_overview_reads_strided_downsampled_traces() {
+        let samples = 40;
+        let mut bytes = test_file_bytes(samples as u16, 10, 0);
+        let trace_size = TRACE_HEADER_SIZE + samples * 4;
+        for trace in 0..10 {
+            let offset = constants::FILE_HEADER_SIZE + trace * trace_size + TRACE_HEADER_SIZE;
+            for sample in 0..samples {
+                let value = (trace * 100) as f32;
+                bytes[offset + sample * 4..offset + sample * 4 + 4]
+                    .copy_from_slice(&value.to_be_bytes());
+            }
+        }
+        let reader = SegyReader::from_bytes(bytes).unwrap();
+
+        // 10 traces into 4 columns: stride 3 keeps traces 0, 3, 6, and 9.
+        let traces = reader.load_overview_traces(4, 8).unwrap();
+        let firsts: Vec<f32> = traces.iter().map(|t| t.to_f32_vec()[0]).collect();
+        assert_eq!(firsts, vec![0.0, 300.0, 600.0, 900.0]);
+        assert!(traces.iter().all(|t| t.len() <= 8));
+
+        assert_eq!(reader.load_overview_traces(50, 100).unwrap().len(), 10);
+        assert!(reader.load_overview_traces(0, 8).is_err());
+    }
+
+    #[test]
+    fn test_read_bytes_is_bounds_checked() {
+        let bytes = test_file_bytes(10, 2, 0);
+        let reader = SegyReader::from_bytes(bytes.clone()).unwrap();
+        let trace_header = constants::FILE_HEADER_SIZE as u64;
+
+        assert_eq!(
+            reader.read_bytes(trace_header, TRACE_HEADER_SIZE).unwrap(),
+            bytes[constants::FILE_HEADER_SIZE..constants::FILE_HEADER_SIZE + TRACE_HEADER_SIZE]
+        );
+        assert_eq!(
+            reader.read_bytes(bytes.len() as u64, 0).unwrap(),
+            Vec::<u8>::new()
+        );
+        assert!(reader.read_bytes(bytes.len() as u64 - 1, 2).is_err());
+        assert!(reader.read_bytes(u64::MAX, 1).is_err());
+        assert!(matches!(
+            reader.read_bytes(0, MAX_READ_BYTES + 1),
+            Err(AppError::ValidationError { .. })
+        ));
+    }
+
+    #[test]
+    fn test_open_truncated_file() {
+        let path = std::env::temp_dir().join("tracelens_truncated_test.sgy");
+        std::fs::write(&path, [0u8; 10]).unwrap();
+
+        let result = SegyReader::open(path.to_str().unwrap(), 0, None);
+        std::fs::remove_file(&path).ok();
+
+        match result {
+            Err(AppError::SegyError { message }) => assert!(message.contains("too small")),
+            Err(other) => panic!("Unexpected error: {}", other),
+            Ok(_) => panic!("Truncated file should not open"),
+        }
+    }
+
+    #[test]
+    fn test_open_empty_file() {
+        let path = std::env::temp_dir().join("tracelens_empty_test.sgy");
+        std::fs::write(&path, []).unwrap();
+
+        let result = SegyReader::open(path.to_str().unwrap(), 0, None);
+        std::fs::remove_file(&path).ok();
+
+        match result {
+            Err(AppError::SegyError { message }) => assert_eq!(message, "File is empty"),
+            Err(other) => panic!("Unexpected error: {}", other),
+            Ok(_) => panic!("Empty file should not open"),
+        }
+    }
+}
@@ -0,0 +1,172 @@
+//! Horizontal trace axis with tick labels.
+//!
+//! The axis is rendered as its own image strip so the frontend can place it
+//! under a section of the same width. Labels use a built-in 5x7 digit font,
+//! which keeps the renderer free of font dependencies. Tick spacing is picked
+//! from 1-2-5 steps so the widest label never overlaps its neighbours.
+
+use super::{encode_png, PngCompression, RenderedImage};
+use crate::error::AppError;
+use image::{Rgb, RgbImage};
+
+/// Height of the rendered axis strip in pixels.
+pub const TRACE_AXIS_HEIGHT: u32 = 20;
+
+const TICK_LENGTH: u32 = 5;
+const GLYPH_WIDTH: u32 = 5;
+const GLYPH_HEIGHT: u32 = 7;
+const GLYPH_ADVANCE: u32 = GLYPH_WIDTH + 1;
+/// Minimum horizontal gap between neighbouring labels.
+const LABEL_PADDING: u32 = 12;
+const LABEL_TOP: u32 = TICK_LENGTH + 3;
+
+const BACKGROUND: Rgb<u8> = Rgb([255, 255, 255]);
+const FOREGROUND: Rgb<u8> = Rgb([0, 0, 0]);
+
+/// Render a trace axis and return it with the `(label, x)` of every tick.
+///
+/// Trace `i` is centered at `(i + 0.5) * width / trace_count`. Ticks are
+/// labeled with the trace number `start_trace + i`, or with `label_field[i]`
+/// (e.g. CDP numbers from `extract_header_field`) when given, in which case
+/// it must hold one value per trace.
+pub fn render_trace_axis(
+    width: u32,
+    start_trace: usize,
+    trace_count: usize,
+    label_field: Option<Vec<i64>>,
+) -> Result<(RenderedImage, Vec<(i64, u32)>), AppError> {
+    if width == 0 || trace_count == 0 {
+        return Err(AppError::ValidationError {
+            message: "Trace axis needs a non-zero width and trace count".to_string(),
+        });
+    }
+    if let Some(labels) = &label_field {
+        if labels.len() != trace_count {
+            return Err(AppError::ValidationError {
+                message: format!(
+                    "Trace axis got {} labels for {} traces",
+                    labels.len(),
+                    trace_count
+                ),
+            });
+        }
+    }
+
+    let label_for = |i: usize| match &label_field {
+        Some(labels) => labels[i],
+        None => (start_trace + i) as i64,
+    };
+    let widest_label = (0..trace_count)
+        .map(|i| text_width(&label_for(i).to_string()))
+        .max()
+        .unwrap_or(0);
+    let trace_width = width as f64 / trace_count as f64;
+    let step = nice_step((widest_label + LABEL_PADDING) as f64 / trace_width);
+
+    // Trace numbers tick on round values; attribute labels every `step` traces.
+    let ticks: Vec<(i64, u32)> = (0..trace_count)
+        .filter(|&i| match label_field {
+            Some(_) => i % step == 0,
+            None => (start_trace + i) % step == 0,
+        })
+        .map(|i| (label_for(i), ((i as f64 + 0.5) * trace_width) as u32))
+        .collect();
+
+    let mut img = RgbImage::from_pixel(width, TRACE_AXIS_HEIGHT, BACKGROUND);
+    for x in 0..width {
+        img.put_pixel(x, 0, FOREGROUND);
+    }
+    for &(label, x) in &ticks {
+        for y in 0..TICK_LENGTH {
+            img.put_pixel(x, y, FOREGROUND);
+        }
+        let text = label.to_string();
+        let half = text_width(&text) / 2;
+        let left = x
+            .saturating_sub(half)
+            .min(width.saturating_sub(text_width(&text)));
+        draw_text(&mut img, &text, left, LABEL_TOP);
+    }
+
+    Ok((encode_png(img, PngCompression::Fast)?, ticks))
+}
+
+/// Smallest 1-2-5 step (1, 2, 5, 10, 20, ...) of at least `min_traces`.
+fn nice_step(min_traces: f64) -> usize {
+    let mut magnitude = 1usize;
+    loop {
+        for multiple in [1, 2, 5] {
+            if (multiple * magnitude) as f64 >= min_traces {
+                return multiple * magnitude;
+            }
+        }
+        magnitude *= 10;
+    }
+}
+
+fn text_width(text: &str) -> u32 {
+    (text.len() as u32 * GLYPH_ADVANCE).saturating_sub(1)
+}
+
+/// Draw digits and minus signs with the top-left corner at `(left, top)`.
+fn draw_text(img: &mut RgbImage, text: &str, left: u32, top: u32) {
+    for (index, ch) in text.chars().enumerate() {
+        let Some(rows) = glyph(ch) else {
+            continue;
+        };
+        let glyph_left = left + index as u32 * GLYPH_ADVANCE;
+        for (row, bits) in rows.iter().enumerate() {
+            for col in 0..GLYPH_WIDTH {
+                let (x, y) = (glyph_left + col, top + row as u32);
+                if bits & (1 << (GLYPH_WIDTH - 1 - col)) != 0 && x < img.width() {
+                    img.put_pixel(x, y, FOREGROUND);
+                }
+            }
+        }
+    }
+}
+
+/// Row bitmaps (most significant of 5 bits is the left column).
+fn glyph(ch: char) -> Option<[u8; GLYPH_HEIGHT as usize]> {
+    Some(match ch {
+        '0' => [0x0E, 0x11, 0x13, 0x15, 0x19, 0x11, 0x0E],
+        '1' => [0x04, 0x0C, 0x04, 0x04, 0x04, 0x04, 0x0E],
+        '2' => [0x0E, 0x11, 0x01, 0x02, 0x04, 0x08, 0x1F],
+        '3' => [0x1F, 0x02, 0x04, 0x02, 0x01, 0x11, 0x0E],
+        '4' => [0x02, 0x06, 0x0A, 0x12, 0x1F, 0x02, 0x02],
+        '5' => [0x1F, 0x10, 0x1E, 0x01, 0x01, 0x11, 0x0E],
+        '6' => [0x06, 0x08, 0x10, 0x1E, 0x11, 0x11, 0x0E],
+        '7' => [0x1F, 0x01, 0x02, 0x04, 0x08, 0x08, 0x08],
+        '8' => [0x0E, 0x11, 0x11, 0x0E, 0x11, 0x11, 0x0E],
+        '9' => [0x0E, 0x11, 0x11, 0x0F, 0x01, 0x02, 0x0C],
+        '-' => [0x00, 0x00, 0x00, 0x1F, 0x00, 0x00, 0x00],
+        _ => return None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_trace_number_ticks_do_not_overlap() {
+        let (image, ticks) = render_trace_axis(400, 1000, 200, None).unwrap();
+
+        assert_eq!((image.width, image.height), (400, TRACE_AXIS_HEIGHT));
+        // Four-digit labels are 23 px wide; with padding that needs 18 traces
+        // of 2 px each, rounded up to a step of 20.
+        assert_eq!(ticks.len(), 10);
+        assert_eq!(ticks[0], (1000, 1));
+        assert!(ticks.windows(2).all(|pair| pair[1].1 - pair[0].1 >= 35));
+    }
+
+    #[test]
+    fn test_attribute_labels_follow_label_field() {
+        let cdps: Vec<i64> = (0..10).map(|i| 5000 + i * 2).collect();
+        let (_, ticks) = render_trace_axis(1000, 0, 10, Some(cdps)).unwrap();
+
+        assert_eq!(ticks.len(), 10);
+        assert_eq!(ticks[3], (5006, 350));
+        assert!(render_trace_axis(1000, 0, 10, Some(vec![1, 2])).is_err());
+    }
+}
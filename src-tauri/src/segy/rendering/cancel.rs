@@ -0,0 +1,88 @@
+//! Cooperative cancellation for long-running renders.
+//!
+//! `RenderState` holds a generation counter shared with every in-flight
+//! render. Each render captures the generation when it starts; cancelling
+//! bumps the counter, so every render started before the cancel observes the
+//! change at its next checkpoint and bails out, while renders started
+//! afterwards are unaffected.
+
+use crate::error::AppError;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+/// Error message returned by a render that was cancelled.
+pub const RENDER_CANCELLED: &str = "cancelled";
+
+/// Shared render cancellation state managed by Tauri.
+#[derive(Debug, Default)]
+pub struct RenderState {
+    generation: Arc<AtomicU64>,
+}
+
+impl RenderState {
+    /// Create a render state with no pending cancellation.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Issue a token for a render that is about to start.
+    pub fn token(&self) -> RenderCancelToken {
+        RenderCancelToken {
+            started: self.generation.load(Ordering::Acquire),
+            generation: Some(self.generation.clone()),
+        }
+    }
+
+    /// Cancel every render whose token was issued before this call.
+    pub fn cancel(&self) {
+        self.generation.fetch_add(1, Ordering::AcqRel);
+    }
+}
+
+/// Handle checked by renderers between units of work.
+#[derive(Debug, Clone, Default)]
+pub struct RenderCancelToken {
+    started: u64,
+    // `None` for renders that can never be cancelled.
+    generation: Option<Arc<AtomicU64>>,
+}
+
+impl RenderCancelToken {
+    /// A token that is never cancelled.
+    pub fn none() -> Self {
+        Self::default()
+    }
+
+    /// Check whether the render owning this token has been cancelled.
+    pub fn is_cancelled(&self) -> bool {
+        self.generation
+            .as_ref()
+            .is_some_and(|generation| generation.load(Ordering::Acquire) != self.started)
+    }
+
+    /// Return a `RenderError` if the render has been cancelled.
+    pub fn check(&self) -> Result<(), AppError> {
+        if self.is_cancelled() {
+            return Err(AppError::render(RENDER_CANCELLED));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cancel_only_affects_earlier_tokens() {
+        let state = RenderState::new();
+        let before = state.token();
+        state.cancel();
+        let after = state.token();
+
+        assert!(before.is_cancelled());
+        assert!(before.check().is_err());
+        assert!(!after.is_cancelled());
+        assert!(!RenderCancelToken::none().is_cancelled());
+    }
+}
@@ -1,4 +1,8 @@
 //! Colormap implementations for variable density rendering.
+//!
+//! Every map spans normalized amplitudes -1..1 with its midpoint at zero.
+//! A `ColormapContext` can move that midpoint to another amplitude, such as
+//! the mean of asymmetric data, by wrapping the map.
 
 use super::types::ColormapType;
 
@@ -89,12 +93,85 @@ impl Colormap for ViridisColormap {
     }
 }
 
+/// Settings shared by every colormap.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct ColormapContext {
+    /// Normalized amplitude drawn with the map's midpoint color. Zero (the
+    /// default) keeps each map unchanged; values are clamped to
+    /// `±MAX_COLORMAP_CENTER`.
+    pub center: f32,
+}
+
+/// Largest `ColormapContext::center` magnitude, keeping both halves of the
+/// map at least 1% of the amplitude range wide.
+pub const MAX_COLORMAP_CENTER: f32 = 0.99;
+
+/// Wrapper that moves another colormap's midpoint to `center`.
+///
+/// Amplitudes from -1 to `center` are stretched onto the inner map's -1 to 0
+/// and those from `center` to 1 onto 0 to 1, so both extremes keep their
+/// colors and only the zero crossing moves.
+pub struct CenteredColormap {
+    inner: Box<dyn Colormap>,
+    center: f32,
+}
+
+impl CenteredColormap {
+    /// Wrap `inner` so `center` maps to its midpoint.
+    pub fn new(inner: Box<dyn Colormap>, center: f32) -> Self {
+        Self {
+            inner,
+            center: center.clamp(-MAX_COLORMAP_CENTER, MAX_COLORMAP_CENTER),
+        }
+    }
+}
+
+impl Colormap for CenteredColormap {
+    fn to_rgb(&self, normalized: f32) -> [u8; 3] {
+        let shifted = if normalized < self.center {
+            (normalized - self.center) / (1.0 + self.center)
+        } else {
+            (normalized - self.center) / (1.0 - self.center)
+        };
+        self.inner.to_rgb(shifted)
+    }
+}
+
 /// Factory function to create a colormap from a public enum.
-pub fn create_colormap(colormap_type: ColormapType) -> Box<dyn Colormap> {
-    match colormap_type {
+///
+/// A zero `context.center` returns the plain map, exactly as before centers
+/// existed; anything else wraps it in a `CenteredColormap`.
+pub fn create_colormap(colormap_type: ColormapType, context: ColormapContext) -> Box<dyn Colormap> {
+    let colormap: Box<dyn Colormap> = match colormap_type {
         ColormapType::Seismic => Box::new(SeismicColormap),
         ColormapType::Grayscale => Box::new(GrayscaleColormap::new(false)),
         ColormapType::GrayscaleInverted => Box::new(GrayscaleColormap::new(true)),
         ColormapType::Viridis => Box::new(ViridisColormap::new()),
+    };
+    if context.center == 0.0 {
+        colormap
+    } else {
+        Box::new(CenteredColormap::new(colormap, context.center))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_center_moves_midpoint_and_keeps_extremes() {
+        let plain = create_colormap(ColormapType::Seismic, ColormapContext::default());
+        let centered = create_colormap(ColormapType::Seismic, ColormapContext { center: 0.5 });
+
+        assert_eq!(centered.to_rgb(0.5), plain.to_rgb(0.0));
+        assert_eq!(centered.to_rgb(-0.25), plain.to_rgb(-0.5));
+        assert_eq!(centered.to_rgb(0.75), plain.to_rgb(0.5));
+        for extreme in [-1.0, 1.0] {
+            assert_eq!(centered.to_rgb(extreme), plain.to_rgb(extreme));
+        }
+        // A zero center leaves the map untouched.
+        let zero = create_colormap(ColormapType::Seismic, ColormapContext { center: 0.0 });
+        assert_eq!(zero.to_rgb(0.3), plain.to_rgb(0.3));
     }
 }
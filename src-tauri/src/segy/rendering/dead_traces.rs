@@ -0,0 +1,44 @@
+//! Blanking of dead traces.
+//!
+//! Dead traces would otherwise render as solid colormap-zero columns that
+//! look like real data. A trace is dead when its header identification code
+//! says so, or when every sample is zero. Like gather gaps, blanking runs on
+//! the rendered image, so every render mode gets it.
+
+use crate::segy::{TraceData, TraceHeader, TraceIdentificationCode};
+use image::{Rgb, RgbImage};
+
+/// Flag each trace that is marked dead in its header or has only zero samples.
+///
+/// `headers` is optional; without it only the all-zero check is applied.
+pub fn dead_trace_mask(traces: &[TraceData], headers: Option<&[TraceHeader]>) -> Vec<bool> {
+    traces
+        .iter()
+        .enumerate()
+        .map(|(idx, trace)| {
+            let marked_dead = headers
+                .and_then(|headers| headers.get(idx))
+                .is_some_and(|header| header.trace_id_code == TraceIdentificationCode::Dead);
+            marked_dead || trace.to_f32_vec().iter().all(|&v| v == 0.0)
+        })
+        .collect()
+}
+
+/// Paint the columns of every dead trace with `color`.
+///
+/// Trace `i` spans columns `i * width / n` up to `(i + 1) * width / n`,
+/// matching how the renderers lay out traces.
+pub fn blank_dead_columns(img: &mut RgbImage, dead: &[bool], color: [u8; 3]) {
+    let trace_count = dead.len() as u64;
+    let (width, height) = img.dimensions();
+    for (trace, _) in dead.iter().enumerate().filter(|(_, &is_dead)| is_dead) {
+        let trace = trace as u64;
+        let start = (trace * width as u64 / trace_count) as u32;
+        let end = ((trace + 1) * width as u64 / trace_count) as u32;
+        for x in start..end {
+            for y in 0..height {
+                img.put_pixel(x, y, Rgb(color));
+            }
+        }
+    }
+}
@@ -0,0 +1,208 @@
+//! Writing rendered sections to image files on disk.
+//!
+//! Renders are always encoded as PNG by the pipeline. PNG exports write those
+//! bytes unchanged; JPEG and WebP exports decode them once and re-encode in
+//! the format implied by the output extension.
+//!
+//! For scientific use, amplitudes can instead be written as a 16-bit
+//! grayscale PNG at one pixel per sample, keeping far more resolution than
+//! the 8-bit color display render.
+
+use super::normalizer::normalize_traces;
+use super::{resolve_scaling, AmplitudeScaling, RenderedImage};
+use crate::error::AppError;
+use crate::segy::TraceData;
+use image::ImageFormat;
+use std::io::Cursor;
+use std::path::Path;
+
+/// Pick the output encoder from the file extension (case-insensitive).
+///
+/// Supports `.png`, `.jpg`/`.jpeg`, and `.webp`; anything else is a
+/// `ValidationError`.
+pub fn export_format(output_path: &Path) -> Result<ImageFormat, AppError> {
+    let extension = output_path
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .map(str::to_ascii_lowercase);
+    match extension.as_deref() {
+        Some("png") => Ok(ImageFormat::Png),
+        Some("jpg" | "jpeg") => Ok(ImageFormat::Jpeg),
+        Some("webp") => Ok(ImageFormat::WebP),
+        _ => Err(AppError::ValidationError {
+            message: format!(
+                "Unsupported image extension for '{}'; use .png, .jpg, or .webp",
+                output_path.display()
+            ),
+        }),
+    }
+}
+
+/// Write a rendered image to `output_path` in the format its extension names.
+pub fn save_rendered_image(image: &RenderedImage, output_path: &Path) -> Result<(), AppError> {
+    let bytes = match export_format(output_path)? {
+        ImageFormat::Png => image.data.clone(),
+        format => {
+            let decoded = image::load_from_memory_with_format(&image.data, ImageFormat::Png)
+                .map_err(|e| AppError::render(format!("Failed to decode render: {}", e)))?
+                .to_rgb8();
+            let mut encoded = Cursor::new(Vec::new());
+            decoded.write_to(&mut encoded, format).map_err(|e| {
+                AppError::render(format!("Failed to encode {:?} image: {}", format, e))
+            })?;
+            encoded.into_inner()
+        }
+    };
+
+    write_file(output_path, &bytes)
+}
+
+/// Write traces to `output_path` as a 16-bit grayscale PNG.
+///
+/// Each trace is one pixel column and each sample one row, with no resizing.
+/// Amplitudes are normalized with `scaling` like the display render, then
+/// -1 maps to black (0), zero to mid gray, and +1 to white (65535); values
+/// beyond full scale are clipped. Non-finite samples, and rows past the end
+/// of shorter traces, are written as zero amplitude.
+pub fn save_amplitude_png16(
+    traces: &[TraceData],
+    scaling: &AmplitudeScaling,
+    output_path: &Path,
+) -> Result<(), AppError> {
+    let normalized = normalize_traces(traces, &resolve_scaling(scaling, traces));
+    let width = normalized.len();
+    let height = normalized.iter().map(Vec::len).max().unwrap_or(0);
+    let mut values = vec![amplitude_to_gray16(0.0); width * height];
+    for (x, trace) in normalized.iter().enumerate() {
+        for (y, &amplitude) in trace.iter().enumerate() {
+            values[y * width + x] = amplitude_to_gray16(amplitude);
+        }
+    }
+    let bytes = encode_png_gray16(&values, width as u32, height as u32)?;
+    write_file(output_path, &bytes)
+}
+
+/// Map a normalized amplitude in `[-1, 1]` onto the full 16-bit range.
+fn amplitude_to_gray16(amplitude: f32) -> u16 {
+    if !amplitude.is_finite() {
+        return amplitude_to_gray16(0.0);
+    }
+    ((amplitude.clamp(-1.0, 1.0) + 1.0) / 2.0 * f32::from(u16::MAX)).round() as u16
+}
+
+/// Encode row-major gray levels as a 16-bit grayscale PNG.
+pub fn encode_png_gray16(values: &[u16], width: u32, height: u32) -> Result<Vec<u8>, AppError> {
+    if values.len() != width as usize * height as usize {
+        return Err(AppError::ValidationError {
+            message: format!(
+                "{} gray values do not fill a {}x{} image",
+                values.len(),
+                width,
+                height
+            ),
+        });
+    }
+    if width == 0 || height == 0 {
+        return Err(AppError::ValidationError {
+            message: "Cannot encode an empty 16-bit image".to_string(),
+        });
+    }
+
+    let mut png_bytes = Vec::new();
+    let mut encoder = png::Encoder::new(Cursor::new(&mut png_bytes), width, height);
+    encoder.set_color(png::ColorType::Grayscale);
+    encoder.set_depth(png::BitDepth::Sixteen);
+    encoder.set_compression(png::Compression::High);
+    let mut writer = encoder
+        .write_header()
+        .map_err(|e| AppError::render(format!("PNG header write failed: {}", e)))?;
+    // PNG stores 16-bit samples big-endian.
+    let raw: Vec<u8> = values
+        .iter()
+        .flat_map(|value| value.to_be_bytes())
+        .collect();
+    writer
+        .write_image_data(&raw)
+        .map_err(|e| AppError::render(format!("PNG encoding failed: {}", e)))?;
+    drop(writer);
+    Ok(png_bytes)
+}
+
+fn write_file(output_path: &Path, bytes: &[u8]) -> Result<(), AppError> {
+    std::fs::write(output_path, bytes).map_err(|e| AppError::IoError {
+        message: format!("Failed to write '{}': {}", output_path.display(), e),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::segy::rendering::{encode_png, PngCompression};
+    use image::{Rgb, RgbImage};
+
+    #[test]
+    fn test_export_format_follows_extension() {
+        assert!(matches!(
+            export_format(Path::new("section.JPG")),
+            Ok(ImageFormat::Jpeg)
+        ));
+        assert!(matches!(
+            export_format(Path::new("section.bmp")),
+            Err(AppError::ValidationError { .. })
+        ));
+        assert!(export_format(Path::new("section")).is_err());
+    }
+
+    #[test]
+    fn test_save_rendered_image_reencodes() {
+        let img = RgbImage::from_pixel(8, 4, Rgb([200, 30, 30]));
+        let rendered = encode_png(img, PngCompression::Fast).unwrap();
+        let dir = std::env::temp_dir();
+
+        for name in [
+            "tracelens_export.png",
+            "tracelens_export.jpg",
+            "tracelens_export.webp",
+        ] {
+            let path = dir.join(name);
+            save_rendered_image(&rendered, &path).unwrap();
+            let written = std::fs::read(&path).unwrap();
+            std::fs::remove_file(&path).ok();
+
+            assert_eq!(
+                image::guess_format(&written).unwrap(),
+                export_format(&path).unwrap()
+            );
+            let decoded = image::load_from_memory(&written).unwrap();
+            assert_eq!((decoded.width(), decoded.height()), (8, 4));
+        }
+    }
+
+    #[test]
+    fn test_gray16_png_keeps_sixteen_bit_samples() {
+        let traces = vec![
+            TraceData::IeeeFloat32(vec![-1.0, 0.0, 1.0]),
+            TraceData::IeeeFloat32(vec![0.5, f32::NAN, 2.0]),
+        ];
+        let path = std::env::temp_dir().join("tracelens_gray16_export.png");
+        save_amplitude_png16(&traces, &AmplitudeScaling::Manual { scale: 1.0 }, &path).unwrap();
+        let written = std::fs::read(&path).unwrap();
+        std::fs::remove_file(&path).ok();
+
+        let decoder = png::Decoder::new(Cursor::new(written));
+        let mut reader = decoder.read_info().unwrap();
+        let mut buffer = vec![0u8; reader.output_buffer_size().unwrap()];
+        let info = reader.next_frame(&mut buffer).unwrap();
+        assert_eq!((info.width, info.height), (2, 3));
+        assert_eq!(info.color_type, png::ColorType::Grayscale);
+        assert_eq!(info.bit_depth, png::BitDepth::Sixteen);
+
+        let values: Vec<u16> = buffer[..info.buffer_size()]
+            .chunks_exact(2)
+            .map(|pair| u16::from_be_bytes([pair[0], pair[1]]))
+            .collect();
+        // Rows are samples, columns traces; NaN is mid gray and 2.0 clips.
+        assert_eq!(values, vec![0, 49151, 32768, 32768, 65535, 65535]);
+        assert!(encode_png_gray16(&[0; 3], 2, 2).is_err());
+    }
+}
@@ -0,0 +1,66 @@
+//! Blank separators between trace ensembles.
+//!
+//! When sorted data is displayed, a gap column makes gather boundaries easy
+//! to see. Boundaries are found where a chosen trace header field changes
+//! from one trace to the next, and gaps are inserted into the rendered image
+//! after the mode-specific renderer has run, so every render mode gets them.
+
+use super::types::GatherGapConfig;
+use crate::error::AppError;
+use crate::segy::TraceHeader;
+use image::{Rgb, RgbImage};
+
+/// Indices of traces whose `field` value differs from the previous trace.
+pub fn gap_boundaries(headers: &[TraceHeader], field: &str) -> Result<Vec<usize>, AppError> {
+    let values = headers
+        .iter()
+        .map(|header| {
+            header
+                .field_value(field)
+                .ok_or_else(|| AppError::ValidationError {
+                    message: format!("Unknown trace header field: {}", field),
+                })
+        })
+        .collect::<Result<Vec<_>, AppError>>()?;
+
+    Ok((1..values.len())
+        .filter(|&idx| values[idx] != values[idx - 1])
+        .collect())
+}
+
+/// Insert `config.width` gap columns before each boundary trace.
+///
+/// Trace `i` is assumed to start at column `i * image_width / trace_count`,
+/// matching how the renderers lay out traces. The result is wider than the
+/// input by `boundaries.len() * config.width` columns.
+pub fn insert_gap_columns(
+    img: RgbImage,
+    trace_count: usize,
+    boundaries: &[usize],
+    config: &GatherGapConfig,
+) -> RgbImage {
+    if boundaries.is_empty() || config.width == 0 || trace_count == 0 {
+        return img;
+    }
+
+    let (width, height) = img.dimensions();
+    let gap_columns: Vec<u32> = boundaries
+        .iter()
+        .map(|&trace| (trace as u64 * width as u64 / trace_count as u64) as u32)
+        .collect();
+    let out_width = width + gap_columns.len() as u32 * config.width;
+
+    let mut out = RgbImage::from_pixel(out_width, height, Rgb(config.color));
+    let mut shift = 0;
+    let mut next_gap = gap_columns.iter().peekable();
+    for x in 0..width {
+        while next_gap.next_if(|&&gap_x| gap_x == x).is_some() {
+            shift += config.width;
+        }
+        for y in 0..height {
+            out.put_pixel(x + shift, y, *img.get_pixel(x, y));
+        }
+    }
+
+    out
+}
@@ -0,0 +1,140 @@
+//! Columns for traces dropped from a line.
+//!
+//! Lines with dropped traces skip CDP ensemble numbers, and drawing the
+//! remaining traces side by side squeezes the geometry around each hole.
+//! Every skipped number gets a column of its own, blank or interpolated from
+//! the traces on either side. Like the band-pass filter, this runs before
+//! any other processing, so every later step sees the full grid.
+
+use super::types::MissingTraceFill;
+use crate::error::AppError;
+use crate::segy::{TraceData, TraceHeader};
+
+/// Most traces that may be inserted into one render.
+pub const MAX_MISSING_TRACES: usize = 100_000;
+
+/// Lay traces out on the CDP grid, with `None` for each skipped number.
+///
+/// A gap is any jump of more than one between the CDP numbers of
+/// neighbouring traces, in either direction; repeated numbers (gathers) are
+/// not gaps, and traces with a non-positive CDP number are treated as
+/// unnumbered and never border one. Each inserted trace gets a copy of the
+/// preceding header with its CDP number filled in, so header-driven steps
+/// keep lining up with the traces.
+pub fn expand_missing_traces(
+    traces: Vec<TraceData>,
+    headers: &[TraceHeader],
+) -> Result<(Vec<Option<TraceData>>, Vec<TraceHeader>), AppError> {
+    if headers.len() != traces.len() {
+        return Err(AppError::ValidationError {
+            message: format!(
+                "Filling missing traces needs one header per trace ({} headers, {} traces)",
+                headers.len(),
+                traces.len()
+            ),
+        });
+    }
+
+    let missing: usize = headers
+        .windows(2)
+        .map(|pair| skipped_numbers(pair).len())
+        .sum();
+    if missing > MAX_MISSING_TRACES {
+        return Err(AppError::ValidationError {
+            message: format!(
+                "CDP numbering skips {} traces; at most {} can be filled",
+                missing, MAX_MISSING_TRACES
+            ),
+        });
+    }
+
+    let mut grid = Vec::with_capacity(traces.len() + missing);
+    let mut grid_headers = Vec::with_capacity(traces.len() + missing);
+    for (i, trace) in traces.into_iter().enumerate() {
+        if i > 0 {
+            for cdp in skipped_numbers(&headers[i - 1..=i]) {
+                let mut header = headers[i - 1].clone();
+                header.cdp_ensemble_number = cdp;
+                grid.push(None);
+                grid_headers.push(header);
+            }
+        }
+        grid.push(Some(trace));
+        grid_headers.push(headers[i].clone());
+    }
+    Ok((grid, grid_headers))
+}
+
+/// CDP numbers skipped between a pair of neighbouring headers, in order.
+fn skipped_numbers(pair: &[TraceHeader]) -> Vec<i32> {
+    let (from, to) = (pair[0].cdp_ensemble_number, pair[1].cdp_ensemble_number);
+    if from <= 0 || to <= 0 {
+        return Vec::new();
+    }
+    if from < to {
+        (from + 1..to).collect()
+    } else {
+        (to + 1..from).rev().collect()
+    }
+}
+
+/// Replace each `None` in `grid`, returning the traces and which were filled.
+///
+/// `Blank` inserts zero traces that the caller paints as background; the
+/// mask flags them. `Interpolate` blends the traces on either side linearly
+/// by position, and leaves the mask clear.
+pub fn fill_missing_traces(
+    grid: Vec<Option<TraceData>>,
+    fill: MissingTraceFill,
+) -> (Vec<TraceData>, Vec<bool>) {
+    let mask: Vec<bool> = grid.iter().map(Option::is_none).collect();
+    let mut traces = Vec::with_capacity(grid.len());
+    let mut gap = 0;
+    for slot in grid.into_iter() {
+        match slot {
+            None => gap += 1,
+            Some(trace) => {
+                if gap > 0 {
+                    let right = trace.to_f32_vec();
+                    fill_gap(&mut traces, gap, Some(&right), fill);
+                    gap = 0;
+                }
+                traces.push(trace);
+            }
+        }
+    }
+    // Gaps only open between real traces; a trailing one just repeats the
+    // last trace.
+    if gap > 0 {
+        fill_gap(&mut traces, gap, None, fill);
+    }
+    let blanked = match fill {
+        MissingTraceFill::Blank => mask,
+        MissingTraceFill::Interpolate => Vec::new(),
+    };
+    (traces, blanked)
+}
+
+/// Append `len` traces bridging the last trace in `traces` and `right`.
+fn fill_gap(
+    traces: &mut Vec<TraceData>,
+    len: usize,
+    right: Option<&[f32]>,
+    fill: MissingTraceFill,
+) {
+    let left = traces.last().map(TraceData::to_f32_vec).unwrap_or_default();
+    let right = right.unwrap_or(&left);
+    for k in 1..=len {
+        let samples = match fill {
+            MissingTraceFill::Blank => vec![0.0; left.len()],
+            MissingTraceFill::Interpolate => {
+                let weight = k as f32 / (len + 1) as f32;
+                left.iter()
+                    .zip(right)
+                    .map(|(&l, &r)| l + (r - l) * weight)
+                    .collect()
+            }
+        };
+        traces.push(TraceData::IeeeFloat32(samples));
+    }
+}
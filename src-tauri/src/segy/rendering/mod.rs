@@ -3,55 +3,508 @@
 //! This module converts trace sample data into raster images using different
 //! visualization modes and encodes the result as PNG for the frontend.
 
+mod axis;
+mod cancel;
 mod colormap;
+mod dead_traces;
+mod export;
+mod gather_gaps;
+mod missing_traces;
 mod normalizer;
 pub mod types;
 mod vd_renderer;
 mod wiggle_renderer;
 
 // Re-exports - only expose high-level rendering function and types
+pub use axis::{render_trace_axis, TRACE_AXIS_HEIGHT};
+pub use cancel::{RenderCancelToken, RenderState, RENDER_CANCELLED};
+pub use export::{encode_png_gray16, export_format, save_amplitude_png16, save_rendered_image};
 pub use types::*;
 
-use crate::segy::TraceData;
-use colormap::create_colormap;
+use crate::error::AppError;
+use crate::segy::{analysis, TraceData, TraceHeader};
+use colormap::{create_colormap, ColormapContext};
+use dead_traces::{blank_dead_columns, dead_trace_mask};
+use gather_gaps::{gap_boundaries, insert_gap_columns};
 use image::RgbImage;
-use normalizer::normalize_traces;
-use vd_renderer::render_variable_density;
-use wiggle_renderer::{render_wiggle, render_wiggle_vd};
+use missing_traces::{expand_missing_traces, fill_missing_traces};
+use normalizer::{normalize_traces, percentile_clip, AUTO_CLIP_PERCENTILE};
+use rayon::prelude::*;
+use vd_renderer::{pad_to_width, render_variable_density, DEFAULT_BACKGROUND as VD_BACKGROUND};
+use wiggle_renderer::{
+    box_downsample, needs_envelope, render_wiggle, render_wiggle_envelope, render_wiggle_vd,
+    DEFAULT_BACKGROUND as WIGGLE_BACKGROUND,
+};
 
 /// Render traces for a given mode and encode the result as PNG bytes.
+///
+/// This is CPU-bound; callers on an async runtime should run it on a
+/// blocking thread. `cancel` is checked between stages and trace columns, and
+/// a cancelled render returns `AppError::RenderError` with `RENDER_CANCELLED`.
+///
+/// When `config.gather_gap` is set, `headers` must hold one header per trace;
+/// gap columns are inserted wherever the chosen field changes, widening the
+/// image beyond `viewport.width`. With `config.hide_dead_traces`, traces that
+/// are all zero, or marked dead in `headers` when given, are painted with the
+/// background color. `sample_interval_us` is only used by `config.filter`.
+/// `config.trace_binning` only affects variable density renders with more
+/// traces than `viewport.width`. `config.align_delays` also needs `headers`;
+/// it shifts each variable density column down by its trace's delay beyond
+/// the smallest delay, leaving background above and below. Samples equal
+/// to `config.null_value` are painted as background in variable density and
+/// break the wiggle line and fills. `config.missing_traces` also needs
+/// `headers`; it adds a column for every CDP number the line skips.
+///
+/// `traces` must already be cut to the viewport's sample window (see
+/// `ViewportConfig::sample_window`); every mode maps that span onto the full
+/// output height, so zooming into a time gate shows its samples at full
+/// resolution.
 pub fn render_traces(
     traces: Vec<TraceData>,
-    viewport: &ViewportConfig,
-    colormap_type: ColormapType,
-    scaling: &AmplitudeScaling,
-    render_mode: RenderMode,
-    wiggle_config: Option<WiggleConfig>,
-) -> Result<RenderedImage, String> {
-    match render_mode {
+    headers: Option<&[TraceHeader]>,
+    sample_interval_us: i16,
+    config: &RenderConfig,
+    compression: PngCompression,
+    cancel: &RenderCancelToken,
+) -> Result<RenderedImage, AppError> {
+    let viewport = &config.viewport;
+    let wiggle_config = config.wiggle_config.clone();
+    // Widen to the full CDP grid first; later steps index the grid headers.
+    let (mut traces, grid_headers, missing) = match (config.missing_traces, headers) {
+        (None, _) => (traces, None, Vec::new()),
+        (Some(fill), Some(headers)) => {
+            let (grid, grid_headers) = expand_missing_traces(traces, headers)?;
+            let (traces, missing) = fill_missing_traces(grid, fill);
+            (traces, Some(grid_headers), missing)
+        }
+        (Some(_), None) => {
+            return Err(AppError::ValidationError {
+                message: "Filling missing traces requires trace headers".to_string(),
+            })
+        }
+    };
+    let trace_count = traces.len();
+    // Inserted columns widen the section beyond the requested trace range.
+    let grid_viewport;
+    let viewport = match grid_headers {
+        Some(_) if trace_count != viewport.trace_count => {
+            grid_viewport = ViewportConfig {
+                trace_count,
+                ..viewport.clone()
+            };
+            &grid_viewport
+        }
+        _ => viewport,
+    };
+    let headers = grid_headers.as_deref().or(headers);
+    // Detect dead traces on the raw samples, before filtering can alter zeros.
+    let mut dead = if config.hide_dead_traces {
+        dead_trace_mask(&traces, headers)
+    } else {
+        Vec::new()
+    };
+    // Blank missing traces are painted over like dead ones.
+    if missing.contains(&true) {
+        dead.resize(missing.len(), false);
+        for (dead, &missing) in dead.iter_mut().zip(&missing) {
+            *dead |= missing;
+        }
+    }
+    // Nulls are zeroed through filtering and attributes, then blanked after.
+    let mut nulls = match config.null_value {
+        Some(null_value) => take_null_samples(&mut traces, null_value),
+        None => Vec::new(),
+    };
+    let traces = apply_filter(traces, config.filter, sample_interval_us)?;
+    let mut traces = apply_phase_rotation(traces, config.phase_rotation_deg)?;
+    // Flip display order once here so every render mode sees reversed traces.
+    if viewport.flip_traces {
+        traces.reverse();
+        dead.reverse();
+        nulls.reverse();
+    }
+    let mut row_offsets = match (config.align_delays, headers) {
+        (false, _) => Vec::new(),
+        (true, Some(headers)) => delay_offsets(headers, sample_interval_us)?,
+        (true, None) => {
+            return Err(AppError::ValidationError {
+                message: "Aligning trace delays requires trace headers".to_string(),
+            })
+        }
+    };
+    if viewport.flip_traces {
+        row_offsets.reverse();
+    }
+    let mut traces = apply_attribute(traces, config.attribute);
+    restore_null_samples(&mut traces, &nulls);
+    let scaling = config
+        .scaling
+        .for_sample_window(viewport.start_sample.unwrap_or(0));
+    let scaling = &resolve_scaling(&scaling, &traces);
+    cancel.check()?;
+
+    let mut img = match config.render_mode {
         RenderMode::VariableDensity => {
-            let colormap = create_colormap(colormap_type);
-            render_variable_density(traces, viewport, colormap.as_ref(), scaling)
+            let colormap = create_colormap(
+                config.colormap_type,
+                ColormapContext {
+                    center: config.colormap_center,
+                },
+            );
+            // Padding renders at true trace width; the rest is filled at the end.
+            let padded;
+            let vd_viewport = match viewport.fit {
+                FitMode::Pad if (1..viewport.width as usize).contains(&trace_count) => {
+                    padded = ViewportConfig {
+                        width: trace_count as u32,
+                        ..viewport.clone()
+                    };
+                    &padded
+                }
+                _ => viewport,
+            };
+            render_variable_density(
+                traces,
+                vd_viewport,
+                colormap.as_ref(),
+                scaling,
+                config.trace_binning,
+                config.interpolate_samples,
+                &row_offsets,
+                cancel,
+            )?
         }
         RenderMode::Wiggle => {
-            let normalized = normalize_traces(&traces, scaling);
             let config = wiggle_config.unwrap_or_else(|| default_wiggle_config(RenderMode::Wiggle));
-            let img = render_wiggle(viewport, &config, &normalized)?;
-            encode_png_fast(img)
+            supersampled(viewport, &config, |viewport, config| {
+                let max_samples = traces.iter().map(TraceData::len).max().unwrap_or(0);
+                // Far more samples than rows: draw min/max envelopes so peaks survive.
+                if needs_envelope(max_samples, viewport.height) {
+                    // Gain the full traces first so windowed and per-trace gains
+                    // see the real samples, not the interleaved envelope.
+                    let rows = viewport.height as usize;
+                    let envelopes: Vec<Vec<f32>> = normalize_traces(&traces, scaling)
+                        .into_par_iter()
+                        .map(|samples| TraceData::IeeeFloat32(samples).downsample_minmax(rows))
+                        .collect();
+                    cancel.check()?;
+                    render_wiggle_envelope(viewport, config, &envelopes)
+                } else {
+                    let normalized = normalize_traces(&traces, scaling);
+                    cancel.check()?;
+                    render_wiggle(viewport, config, &normalized)
+                }
+            })?
         }
         RenderMode::WiggleVariableDensity => {
             let normalized = normalize_traces(&traces, scaling);
-            let colormap = create_colormap(colormap_type);
+            let colormap = create_colormap(
+                config.colormap_type,
+                ColormapContext {
+                    center: config.colormap_center,
+                },
+            );
             let config = wiggle_config
                 .unwrap_or_else(|| default_wiggle_config(RenderMode::WiggleVariableDensity));
-            let img = render_wiggle_vd(viewport, colormap.as_ref(), &config, &normalized)?;
-            encode_png_fast(img)
+            cancel.check()?;
+            supersampled(viewport, &config, |viewport, config| {
+                render_wiggle_vd(viewport, colormap.as_ref(), config, &normalized, cancel)
+            })?
+        }
+    };
+
+    if dead.contains(&true) {
+        let background = viewport
+            .background_color
+            .unwrap_or(match config.render_mode {
+                RenderMode::Wiggle => WIGGLE_BACKGROUND,
+                _ => VD_BACKGROUND,
+            });
+        blank_dead_columns(&mut img, &dead, background);
+    }
+
+    let img = match &config.gather_gap {
+        Some(gap) => {
+            let headers = headers.ok_or_else(|| AppError::ValidationError {
+                message: "Gather gaps require trace headers".to_string(),
+            })?;
+            let mut boundaries = gap_boundaries(headers, &gap.header_field)?;
+            if viewport.flip_traces {
+                // Trace `b` moves to `n - 1 - b`, so its group now starts at `n - b`.
+                boundaries = boundaries.iter().rev().map(|&b| trace_count - b).collect();
+            }
+            insert_gap_columns(img, trace_count, &boundaries, gap)
+        }
+        None => img,
+    };
+    let img = if img.width() < viewport.width {
+        let background = viewport.background_color.unwrap_or(VD_BACKGROUND);
+        pad_to_width(img, viewport.width, background)
+    } else {
+        img
+    };
+
+    cancel.check()?;
+    let mut image = encode_png(img, compression)?;
+    image.applied_scale = full_scale_amplitude(scaling);
+    image.scaling_mode = scaling_mode_name(&config.scaling).to_string();
+    Ok(image)
+}
+
+/// Relative tolerance for matching samples to `RenderConfig::null_value`.
+const NULL_TOLERANCE: f32 = 1e-6;
+
+/// Whether `value` equals the null sentinel up to float rounding.
+fn is_null_sample(value: f32, null_value: f32) -> bool {
+    (value - null_value).abs() <= NULL_TOLERANCE * null_value.abs().max(1.0)
+}
+
+/// Zero samples matching `null_value`, returning where they were per trace.
+fn take_null_samples(traces: &mut [TraceData], null_value: f32) -> Vec<Vec<bool>> {
+    traces
+        .iter_mut()
+        .map(|trace| {
+            let mut samples = trace.to_f32_vec();
+            let mask: Vec<bool> = samples
+                .iter()
+                .map(|&value| is_null_sample(value, null_value))
+                .collect();
+            if mask.contains(&true) {
+                for (sample, _) in samples.iter_mut().zip(&mask).filter(|(_, &null)| null) {
+                    *sample = 0.0;
+                }
+                *trace = TraceData::IeeeFloat32(samples);
+            }
+            mask
+        })
+        .collect()
+}
+
+/// Turn the samples found by `take_null_samples` into NaN, which
+/// normalization ignores and the renderers leave blank.
+fn restore_null_samples(traces: &mut [TraceData], nulls: &[Vec<bool>]) {
+    for (trace, mask) in traces.iter_mut().zip(nulls) {
+        if !mask.contains(&true) {
+            continue;
+        }
+        let mut samples = trace.to_f32_vec();
+        for (sample, _) in samples.iter_mut().zip(mask).filter(|(_, &null)| null) {
+            *sample = f32::NAN;
         }
+        *trace = TraceData::IeeeFloat32(samples);
     }
 }
 
-/// Encode an RGB image as PNG with fast compression settings.
-pub(crate) fn encode_png_fast(img: RgbImage) -> Result<RenderedImage, String> {
+/// Reject wiggle settings the renderers cannot draw sensibly.
+fn validate_wiggle_config(config: &WiggleConfig) -> Result<(), AppError> {
+    if !(1..=MAX_WIGGLE_SUPERSAMPLE).contains(&config.supersample) {
+        return Err(AppError::ValidationError {
+            message: format!(
+                "Wiggle supersample factor must be between 1 and {}, got {}",
+                MAX_WIGGLE_SUPERSAMPLE, config.supersample
+            ),
+        });
+    }
+    if !(config.clip >= 0.0 && config.clip.is_finite()) {
+        return Err(AppError::ValidationError {
+            message: format!("Wiggle clip must not be negative, got {}", config.clip),
+        });
+    }
+    if let Some(excursion) = config.excursion {
+        if !(excursion > 0.0 && excursion <= MAX_WIGGLE_EXCURSION) {
+            return Err(AppError::ValidationError {
+                message: format!(
+                    "Wiggle excursion must be above 0 and at most {} trace spacings, got {}",
+                    MAX_WIGGLE_EXCURSION, excursion
+                ),
+            });
+        }
+    }
+    Ok(())
+}
+
+/// Run a wiggle render at `config.supersample` times the viewport size, with
+/// line width scaled to match, and box-filter the result back down. The
+/// config is validated first, and the enlarged canvas may hold at most
+/// `MAX_SUPERSAMPLED_PIXELS`.
+fn supersampled(
+    viewport: &ViewportConfig,
+    config: &WiggleConfig,
+    render: impl FnOnce(&ViewportConfig, &WiggleConfig) -> Result<RgbImage, AppError>,
+) -> Result<RgbImage, AppError> {
+    validate_wiggle_config(config)?;
+    let factor = config.supersample;
+    if factor == 1 {
+        return render(viewport, config);
+    }
+    let too_large = || AppError::ValidationError {
+        message: format!(
+            "Supersampling a {}x{} viewport by {} exceeds {} pixels",
+            viewport.width, viewport.height, factor, MAX_SUPERSAMPLED_PIXELS
+        ),
+    };
+    let width = viewport.width.checked_mul(factor).ok_or_else(too_large)?;
+    let height = viewport.height.checked_mul(factor).ok_or_else(too_large)?;
+    if u64::from(width) * u64::from(height) > MAX_SUPERSAMPLED_PIXELS {
+        return Err(too_large());
+    }
+    let viewport = ViewportConfig {
+        width,
+        height,
+        ..viewport.clone()
+    };
+    let config = WiggleConfig {
+        line_width: config.line_width * factor as f32,
+        ..config.clone()
+    };
+    Ok(box_downsample(&render(&viewport, &config)?, factor))
+}
+
+/// Samples each trace starts below the earliest one, from delay recording
+/// times in milliseconds, rounded to the nearest sample.
+fn delay_offsets(headers: &[TraceHeader], sample_interval_us: i16) -> Result<Vec<usize>, AppError> {
+    if sample_interval_us <= 0 {
+        return Err(AppError::ValidationError {
+            message: format!(
+                "Aligning trace delays needs a positive sample interval, got {} us",
+                sample_interval_us
+            ),
+        });
+    }
+    let interval = i64::from(sample_interval_us);
+    let earliest = headers
+        .iter()
+        .map(|header| header.delay_recording_time)
+        .min()
+        .unwrap_or(0);
+    Ok(headers
+        .iter()
+        .map(|header| {
+            let delay_us = (i64::from(header.delay_recording_time) - i64::from(earliest)) * 1000;
+            ((delay_us + interval / 2) / interval) as usize
+        })
+        .collect())
+}
+
+/// Compute percentile clips (`Percentile`, `Auto`, and uncached
+/// `GlobalPercentile`) from `traces` so they can be reported with the image.
+fn resolve_scaling(scaling: &AmplitudeScaling, traces: &[TraceData]) -> AmplitudeScaling {
+    match scaling {
+        AmplitudeScaling::Percentile { percentile } => AmplitudeScaling::GlobalPercentile {
+            percentile: *percentile,
+            cached_clip: Some(percentile_clip(traces, *percentile)),
+        },
+        AmplitudeScaling::Auto => AmplitudeScaling::GlobalPercentile {
+            percentile: AUTO_CLIP_PERCENTILE,
+            cached_clip: Some(percentile_clip(traces, AUTO_CLIP_PERCENTILE)),
+        },
+        AmplitudeScaling::GlobalPercentile {
+            percentile,
+            cached_clip: None,
+        } => AmplitudeScaling::GlobalPercentile {
+            percentile: *percentile,
+            cached_clip: Some(percentile_clip(traces, *percentile)),
+        },
+        other => other.clone(),
+    }
+}
+
+/// Amplitude mapped to full display scale (±1) by a resolved scaling.
+///
+/// `None` when traces are gained individually and no single scale applies.
+fn full_scale_amplitude(scaling: &AmplitudeScaling) -> Option<f32> {
+    match scaling {
+        AmplitudeScaling::Global { max_amplitude } => Some(*max_amplitude),
+        AmplitudeScaling::GlobalPercentile { cached_clip, .. } => *cached_clip,
+        AmplitudeScaling::Manual { scale } if *scale != 0.0 => Some(1.0 / scale),
+        AmplitudeScaling::Manual { .. }
+        | AmplitudeScaling::PerTrace { .. }
+        | AmplitudeScaling::RmsBalance { .. }
+        | AmplitudeScaling::Percentile { .. }
+        | AmplitudeScaling::Auto => None,
+    }
+}
+
+/// Serialized `type` tag of a scaling mode, e.g. `per-trace`.
+fn scaling_mode_name(scaling: &AmplitudeScaling) -> &'static str {
+    match scaling {
+        AmplitudeScaling::Global { .. } => "global",
+        AmplitudeScaling::PerTrace { .. } => "per-trace",
+        AmplitudeScaling::Percentile { .. } => "percentile",
+        AmplitudeScaling::GlobalPercentile { .. } => "global-percentile",
+        AmplitudeScaling::Manual { .. } => "manual",
+        AmplitudeScaling::Auto => "auto",
+        AmplitudeScaling::RmsBalance { .. } => "rms-balance",
+    }
+}
+
+/// Band-pass every trace when a filter is configured.
+fn apply_filter(
+    traces: Vec<TraceData>,
+    filter: Option<BandpassConfig>,
+    sample_interval_us: i16,
+) -> Result<Vec<TraceData>, AppError> {
+    let Some(filter) = filter else {
+        return Ok(traces);
+    };
+    if !(filter.low_hz >= 0.0 && filter.low_hz < filter.high_hz) {
+        return Err(AppError::ValidationError {
+            message: format!(
+                "Invalid band-pass corners: {} Hz to {} Hz",
+                filter.low_hz, filter.high_hz
+            ),
+        });
+    }
+
+    Ok(traces
+        .par_iter()
+        .map(|trace| {
+            TraceData::IeeeFloat32(analysis::bandpass(
+                &trace.to_f32_vec(),
+                sample_interval_us,
+                filter.low_hz,
+                filter.high_hz,
+            ))
+        })
+        .collect())
+}
+
+/// Rotate the phase of every trace by a constant angle, if requested.
+fn apply_phase_rotation(
+    traces: Vec<TraceData>,
+    degrees: Option<f32>,
+) -> Result<Vec<TraceData>, AppError> {
+    let Some(degrees) = degrees else {
+        return Ok(traces);
+    };
+    if !degrees.is_finite() {
+        return Err(AppError::ValidationError {
+            message: format!("Invalid phase rotation: {} degrees", degrees),
+        });
+    }
+
+    Ok(traces
+        .par_iter()
+        .map(|trace| TraceData::IeeeFloat32(analysis::phase_rotate(&trace.to_f32_vec(), degrees)))
+        .collect())
+}
+
+/// Replace raw amplitudes with the requested trace attribute.
+fn apply_attribute(traces: Vec<TraceData>, attribute: TraceAttribute) -> Vec<TraceData> {
+    match attribute {
+        TraceAttribute::Amplitude => traces,
+        TraceAttribute::Envelope => traces
+            .par_iter()
+            .map(|trace| TraceData::IeeeFloat32(analysis::envelope(&trace.to_f32_vec())))
+            .collect(),
+    }
+}
+
+/// Encode an RGB image as PNG at the requested compression level.
+pub(crate) fn encode_png(
+    img: RgbImage,
+    compression: PngCompression,
+) -> Result<RenderedImage, AppError> {
     let (width, height) = img.dimensions();
     let raw_pixels = img.into_raw();
 
@@ -59,15 +512,19 @@ pub(crate) fn encode_png_fast(img: RgbImage) -> Result<RenderedImage, String> {
     let mut encoder = png::Encoder::new(std::io::Cursor::new(&mut png_bytes), width, height);
     encoder.set_color(png::ColorType::Rgb);
     encoder.set_depth(png::BitDepth::Eight);
-    encoder.set_compression(png::Compression::Fast);
+    encoder.set_compression(match compression {
+        PngCompression::Default => png::Compression::default(),
+        PngCompression::Fast => png::Compression::Fast,
+        PngCompression::Best => png::Compression::High,
+    });
 
     let mut writer = encoder
         .write_header()
-        .map_err(|e| format!("PNG header write failed: {}", e))?;
+        .map_err(|e| AppError::render(format!("PNG header write failed: {}", e)))?;
 
     writer
         .write_image_data(&raw_pixels)
-        .map_err(|e| format!("PNG encoding failed: {}", e))?;
+        .map_err(|e| AppError::render(format!("PNG encoding failed: {}", e)))?;
 
     // Ensure the encoder flushes before returning the bytes.
     drop(writer);
@@ -77,9 +534,67 @@ pub(crate) fn encode_png_fast(img: RgbImage) -> Result<RenderedImage, String> {
         height,
         data: png_bytes,
         format: ImageFormat::Png,
+        applied_scale: None,
+        scaling_mode: String::new(),
+        data_trace_count: None,
     })
 }
 
+/// Number of synthetic traces drawn by `render_wiggle_preview`.
+const PREVIEW_TRACES: usize = 5;
+/// Samples per synthetic preview trace.
+const PREVIEW_SAMPLES: usize = 64;
+
+/// Render synthetic Ricker-wavelet traces with a wiggle configuration.
+///
+/// Used as a live swatch while editing wiggle settings; no file is read.
+/// Wavelets step down slightly from trace to trace so line and fill styles
+/// are visible on both lobes.
+pub fn render_wiggle_preview(
+    config: &WiggleConfig,
+    width: u32,
+    height: u32,
+) -> Result<RenderedImage, AppError> {
+    if width == 0 || height == 0 {
+        return Err(AppError::ValidationError {
+            message: "Preview size must be non-zero".to_string(),
+        });
+    }
+
+    let traces: Vec<TraceData> = (0..PREVIEW_TRACES)
+        .map(|trace| {
+            let center = PREVIEW_SAMPLES as f32 * (0.35 + 0.06 * trace as f32);
+            TraceData::IeeeFloat32(
+                (0..PREVIEW_SAMPLES)
+                    .map(|sample| ricker((sample as f32 - center) / 6.0))
+                    .collect(),
+            )
+        })
+        .collect();
+    let viewport = ViewportConfig {
+        start_trace: 0,
+        trace_count: PREVIEW_TRACES,
+        width,
+        height,
+        flip_traces: false,
+        background_color: None,
+        start_sample: None,
+        end_sample: None,
+        fit: FitMode::Stretch,
+    };
+    let normalized = normalize_traces(&traces, &AmplitudeScaling::Manual { scale: 1.0 });
+    encode_png(
+        render_wiggle(&viewport, config, &normalized)?,
+        PngCompression::Fast,
+    )
+}
+
+/// Ricker wavelet with unit peak, `t` in units of the wavelet width.
+fn ricker(t: f32) -> f32 {
+    let arg = t * t;
+    (1.0 - 2.0 * arg) * (-arg).exp()
+}
+
 /// Provide a default wiggle configuration tuned per render mode.
 fn default_wiggle_config(render_mode: RenderMode) -> WiggleConfig {
     match render_mode {
@@ -90,6 +605,11 @@ fn default_wiggle_config(render_mode: RenderMode) -> WiggleConfig {
             fill_negative: false,
             positive_fill_color: [0, 0, 0],
             negative_fill_color: [255, 0, 0],
+            clip: 1.0,
+            excursion: None,
+            variable_area: false,
+            fill_threshold: 0.0,
+            supersample: 1,
         },
         RenderMode::WiggleVariableDensity => WiggleConfig {
             line_width: 1.0,
@@ -98,6 +618,11 @@ fn default_wiggle_config(render_mode: RenderMode) -> WiggleConfig {
             fill_negative: false,
             positive_fill_color: [0, 0, 0],
             negative_fill_color: [255, 0, 0],
+            clip: 1.0,
+            excursion: None,
+            variable_area: false,
+            fill_threshold: 0.0,
+            supersample: 1,
         },
         RenderMode::VariableDensity => WiggleConfig {
             line_width: 1.0,
@@ -106,6 +631,626 @@ fn default_wiggle_config(render_mode: RenderMode) -> WiggleConfig {
             fill_negative: false,
             positive_fill_color: [0, 0, 0],
             negative_fill_color: [255, 0, 0],
+            clip: 1.0,
+            excursion: None,
+            variable_area: false,
+            fill_threshold: 0.0,
+            supersample: 1,
         },
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::segy::synthetic::make_trace_header;
+    use image::Rgb;
+
+    /// Variable density render of `trace_count` traces at `width` x `height`
+    /// with unit manual scaling and every optional stage off.
+    fn vd_config(trace_count: usize, width: u32, height: u32) -> RenderConfig {
+        RenderConfig::variable_density(
+            ViewportConfig::new(trace_count, width, height),
+            ColormapType::Seismic,
+            AmplitudeScaling::Manual { scale: 1.0 },
+        )
+    }
+
+    #[test]
+    fn test_best_compression_is_smaller_than_fast() {
+        let img = RgbImage::from_fn(256, 256, |x, y| {
+            let v = (((x as f32 * 0.1).sin() + (y as f32 * 0.07).cos()) * 60.0 + 128.0) as u8;
+            Rgb([v, v / 2, 255 - v])
+        });
+
+        let fast = encode_png(img.clone(), PngCompression::Fast).unwrap();
+        let best = encode_png(img, PngCompression::Best).unwrap();
+        assert!(best.data.len() < fast.data.len());
+    }
+
+    #[test]
+    fn test_gather_gaps_widen_image() {
+        let headers: Vec<TraceHeader> = (0..10i32)
+            .map(|idx| {
+                // CDP at bytes 21-24.
+                make_trace_header(|bytes| {
+                    bytes[20..24].copy_from_slice(&(1 + idx / 5).to_be_bytes())
+                })
+            })
+            .collect();
+        let traces = vec![TraceData::IeeeFloat32(vec![0.5; 4]); 10];
+        let mut config = vd_config(10, 10, 4);
+        let cancel = RenderCancelToken::none();
+
+        let plain = render_traces(
+            traces.clone(),
+            Some(&headers),
+            4000,
+            &config,
+            PngCompression::Fast,
+            &cancel,
+        )
+        .unwrap();
+        config.gather_gap = Some(GatherGapConfig {
+            header_field: "cdp_ensemble_number".to_string(),
+            width: 3,
+            color: [255, 255, 255],
+        });
+        let gapped = render_traces(
+            traces,
+            Some(&headers),
+            4000,
+            &config,
+            PngCompression::Fast,
+            &cancel,
+        )
+        .unwrap();
+
+        assert_eq!(gapped.width, plain.width + 3);
+        assert_eq!(gapped.height, plain.height);
+    }
+
+    #[test]
+    fn test_pad_fit_keeps_one_column_per_trace() {
+        let traces = vec![TraceData::IeeeFloat32(vec![0.8; 20]); 10];
+        let mut config = vd_config(10, 500, 40);
+        config.viewport.fit = FitMode::Pad;
+        let cancel = RenderCancelToken::none();
+        // (columns with no background pixel, columns of only background)
+        let column_kinds = |config: &RenderConfig| {
+            let rendered = render_traces(
+                traces.clone(),
+                None,
+                4000,
+                config,
+                PngCompression::Fast,
+                &cancel,
+            )
+            .unwrap();
+            let img = image::load_from_memory(&rendered.data).unwrap().to_rgb8();
+            assert_eq!(img.dimensions(), (500, 40));
+            let count = |is_background: bool| {
+                (0..img.width())
+                    .filter(|&x| {
+                        (0..img.height())
+                            .all(|y| (img.get_pixel(x, y).0 == VD_BACKGROUND) == is_background)
+                    })
+                    .count()
+            };
+            (count(false), count(true))
+        };
+
+        assert_eq!(column_kinds(&config), (10, 490));
+        config.viewport.fit = FitMode::Stretch;
+        assert_eq!(column_kinds(&config), (500, 0));
+    }
+
+    #[test]
+    fn test_align_delays_lines_up_common_event() {
+        // At 4 ms sampling, an event at 400 ms is sample 100 with no delay and
+        // sample 50 with a 200 ms delay.
+        let headers: Vec<TraceHeader> = [0i16, 200]
+            .iter()
+            .map(|delay| {
+                make_trace_header(|bytes| bytes[108..110].copy_from_slice(&delay.to_be_bytes()))
+            })
+            .collect();
+        let spike = |len: usize, at: usize| {
+            TraceData::IeeeFloat32((0..len).map(|i| if i == at { 1.0 } else { 0.0 }).collect())
+        };
+        let traces = vec![spike(150, 100), spike(100, 50)];
+        let mut config = vd_config(2, 2, 150);
+        config.align_delays = true;
+        let cancel = RenderCancelToken::none();
+        let rendered = render_traces(
+            traces.clone(),
+            Some(&headers),
+            4000,
+            &config,
+            PngCompression::Fast,
+            &cancel,
+        )
+        .unwrap();
+        let img = image::load_from_memory(&rendered.data).unwrap().to_rgb8();
+        let event_row = |x: u32| (0..img.height()).find(|&y| img.get_pixel(x, y).0 == [0, 0, 255]);
+
+        assert_eq!(event_row(0), Some(100));
+        assert_eq!(event_row(1), Some(100));
+        // The delayed trace starts 50 samples down, on background.
+        assert_eq!(img.get_pixel(1, 49).0, VD_BACKGROUND);
+        assert_eq!(img.get_pixel(1, 50).0, [255, 255, 255]);
+
+        // Delays come from the headers, so they are required.
+        config.viewport.trace_count = 1;
+        assert!(render_traces(
+            vec![spike(10, 0)],
+            None,
+            4000,
+            &config,
+            PngCompression::Fast,
+            &cancel
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_flip_traces_swaps_display_sides() {
+        // Seismic colormap: first trace (negative) is red, last (positive) is blue.
+        let traces = vec![
+            TraceData::IeeeFloat32(vec![-1.0; 4]),
+            TraceData::IeeeFloat32(vec![1.0; 4]),
+        ];
+        let mut config = vd_config(2, 2, 4);
+        let cancel = RenderCancelToken::none();
+        let render = |config: &RenderConfig| {
+            let rendered = render_traces(
+                traces.clone(),
+                None,
+                4000,
+                config,
+                PngCompression::Fast,
+                &cancel,
+            )
+            .unwrap();
+            image::load_from_memory(&rendered.data).unwrap().to_rgb8()
+        };
+
+        let plain = render(&config);
+        config.viewport.flip_traces = true;
+        let flipped = render(&config);
+
+        let (left, right) = (*plain.get_pixel(0, 1), *plain.get_pixel(1, 1));
+        assert!(left[0] > left[2] && right[2] > right[0]);
+        assert_eq!(*flipped.get_pixel(0, 1), right);
+        assert_eq!(*flipped.get_pixel(1, 1), left);
+    }
+
+    #[test]
+    fn test_wiggle_preview_uses_fill_color() {
+        let mut config = default_wiggle_config(RenderMode::Wiggle);
+        config.positive_fill_color = [0, 128, 0];
+        let rendered = render_wiggle_preview(&config, 100, 80).unwrap();
+        let img = image::load_from_memory(&rendered.data).unwrap().to_rgb8();
+
+        assert_eq!(img.dimensions(), (100, 80));
+        assert!(img.pixels().any(|pixel| pixel.0 == [0, 128, 0]));
+        assert!(render_wiggle_preview(&config, 0, 80).is_err());
+    }
+
+    #[test]
+    fn test_wiggle_background_color() {
+        let gray = [128, 128, 128];
+        let mut config = vd_config(2, 20, 8);
+        config.viewport.background_color = Some(gray);
+        config.render_mode = RenderMode::Wiggle;
+        let traces = vec![TraceData::IeeeFloat32(vec![0.0; 8]); 2];
+
+        let rendered = render_traces(
+            traces,
+            None,
+            4000,
+            &config,
+            PngCompression::Fast,
+            &RenderCancelToken::none(),
+        )
+        .unwrap();
+        let img = image::load_from_memory(&rendered.data).unwrap().to_rgb8();
+
+        // Flat traces only touch their axis columns; the rest is background.
+        let background = img.pixels().filter(|pixel| pixel.0 == gray).count();
+        assert!(background > img.pixels().len() / 2);
+        assert!(img.pixels().all(|pixel| pixel.0 != [255, 255, 255]));
+    }
+
+    #[test]
+    fn test_wiggle_rejects_bad_clip_and_excursion() {
+        let trace = TraceData::IeeeFloat32((0..40).map(|i| (i as f32 * 0.4).sin()).collect());
+        for mode in [RenderMode::Wiggle, RenderMode::WiggleVariableDensity] {
+            let render = |clip: f32, excursion: Option<f32>| {
+                let mut wiggle = default_wiggle_config(mode);
+                wiggle.clip = clip;
+                wiggle.excursion = excursion;
+                let mut config = vd_config(3, 30, 40);
+                config.render_mode = mode;
+                config.wiggle_config = Some(wiggle);
+                render_traces(
+                    vec![trace.clone(); 3],
+                    None,
+                    4000,
+                    &config,
+                    PngCompression::Fast,
+                    &RenderCancelToken::none(),
+                )
+            };
+
+            assert!(render(1.0, Some(MAX_WIGGLE_EXCURSION)).is_ok());
+            assert!(render(-1.0, None).is_err());
+            assert!(render(f32::NAN, None).is_err());
+            for excursion in [0.0, -0.5, MAX_WIGGLE_EXCURSION + 1.0, f32::INFINITY] {
+                assert!(render(1.0, Some(excursion)).is_err(), "{}", excursion);
+            }
+        }
+    }
+
+    #[test]
+    fn test_supersampled_wiggle_keeps_viewport_size() {
+        let mut wiggle = default_wiggle_config(RenderMode::Wiggle);
+        wiggle.supersample = 4;
+        let mut config = vd_config(3, 30, 40);
+        config.render_mode = RenderMode::Wiggle;
+        config.wiggle_config = Some(wiggle);
+        let trace = TraceData::IeeeFloat32((0..40).map(|i| (i as f32 * 0.4).sin()).collect());
+        let render = |config: &RenderConfig| {
+            render_traces(
+                vec![trace.clone(); 3],
+                None,
+                4000,
+                config,
+                PngCompression::Fast,
+                &RenderCancelToken::none(),
+            )
+        };
+
+        let rendered = render(&config).unwrap();
+        let img = image::load_from_memory(&rendered.data).unwrap().to_rgb8();
+        assert_eq!(img.dimensions(), (30, 40));
+        // Edges are averaged into grays between the black ink and white paper.
+        assert!(img
+            .pixels()
+            .any(|pixel| pixel.0 != [0, 0, 0] && pixel.0 != [255, 255, 255]));
+
+        config.wiggle_config.as_mut().unwrap().supersample = MAX_WIGGLE_SUPERSAMPLE + 1;
+        assert!(render(&config).is_err());
+
+        // An enlarged canvas that overflows or exceeds the pixel budget is
+        // rejected before anything is allocated.
+        config.wiggle_config.as_mut().unwrap().supersample = MAX_WIGGLE_SUPERSAMPLE;
+        for (width, height) in [(u32::MAX, 40), (30, u32::MAX), (8192, 8192)] {
+            config.viewport.width = width;
+            config.viewport.height = height;
+            assert!(render(&config).is_err(), "{}x{}", width, height);
+        }
+    }
+
+    #[test]
+    fn test_dead_trace_renders_as_background() {
+        let headers: Vec<TraceHeader> = [1u8, 2, 1]
+            .iter()
+            .map(|&id| make_trace_header(|bytes| bytes[29] = id))
+            .collect();
+        let traces = vec![TraceData::IeeeFloat32(vec![0.5; 4]); 3];
+        let mut config = vd_config(3, 3, 4);
+        config.viewport.background_color = Some([10, 20, 30]);
+        config.hide_dead_traces = true;
+
+        let rendered = render_traces(
+            traces,
+            Some(&headers),
+            4000,
+            &config,
+            PngCompression::Fast,
+            &RenderCancelToken::none(),
+        )
+        .unwrap();
+        let img = image::load_from_memory(&rendered.data).unwrap().to_rgb8();
+
+        let background_columns: Vec<u32> = (0..3)
+            .filter(|&x| (0..4).all(|y| img.get_pixel(x, y).0 == [10, 20, 30]))
+            .collect();
+        assert_eq!(background_columns, vec![1]);
+    }
+
+    #[test]
+    fn test_skipped_cdps_leave_gap_columns() {
+        let headers: Vec<TraceHeader> = [98i32, 99, 105, 106]
+            .iter()
+            .map(|&cdp| {
+                make_trace_header(|bytes| bytes[20..24].copy_from_slice(&cdp.to_be_bytes()))
+            })
+            .collect();
+        let traces: Vec<TraceData> = [-1.0, -1.0, 1.0, 1.0]
+            .iter()
+            .map(|&amplitude| TraceData::IeeeFloat32(vec![amplitude; 4]))
+            .collect();
+        let mut config = vd_config(4, 9, 4);
+        config.viewport.background_color = Some([10, 20, 30]);
+        config.missing_traces = Some(MissingTraceFill::Blank);
+        let colormap = create_colormap(ColormapType::Seismic, ColormapContext::default());
+        let (negative, positive) = (colormap.to_rgb(-1.0), colormap.to_rgb(1.0));
+        let columns = |config: &RenderConfig| {
+            let rendered = render_traces(
+                traces.clone(),
+                Some(&headers),
+                4000,
+                config,
+                PngCompression::Fast,
+                &RenderCancelToken::none(),
+            )
+            .unwrap();
+            let img = image::load_from_memory(&rendered.data).unwrap().to_rgb8();
+            assert_eq!(img.dimensions(), (9, 4));
+            (0..img.width())
+                .map(|x| {
+                    let color = img.get_pixel(x, 0).0;
+                    assert!((0..4).all(|y| img.get_pixel(x, y).0 == color));
+                    color
+                })
+                .collect::<Vec<[u8; 3]>>()
+        };
+
+        // CDPs 100-104 get one blank column each; 105 and 106 keep their own.
+        let blank = columns(&config);
+        assert_eq!(blank[..2], [negative, negative]);
+        assert!(blank[2..7].iter().all(|&color| color == [10, 20, 30]));
+        assert_eq!(blank[7..], [positive, positive]);
+
+        config.missing_traces = Some(MissingTraceFill::Interpolate);
+        let interpolated = columns(&config);
+        assert_eq!(interpolated[..2], [negative, negative]);
+        assert!(interpolated[2..7]
+            .iter()
+            .all(|&color| color != [10, 20, 30]));
+        assert_eq!(interpolated[4], colormap.to_rgb(0.0));
+        assert_eq!(interpolated[7..], [positive, positive]);
+    }
+
+    #[test]
+    fn test_null_samples_render_as_background() {
+        let mut samples = vec![-999.25f32; 8];
+        samples[2] = 1.0;
+        samples[5] = -0.5;
+        let mut config = vd_config(1, 1, 8);
+        config.viewport.background_color = Some([10, 20, 30]);
+        config.scaling = AmplitudeScaling::Auto;
+        config.null_value = Some(-999.25);
+
+        let rendered = render_traces(
+            vec![TraceData::IeeeFloat32(samples)],
+            None,
+            4000,
+            &config,
+            PngCompression::Fast,
+            &RenderCancelToken::none(),
+        )
+        .unwrap();
+        let img = image::load_from_memory(&rendered.data).unwrap().to_rgb8();
+
+        let blank_rows: Vec<u32> = (0..8)
+            .filter(|&y| img.get_pixel(0, y).0 == [10, 20, 30])
+            .collect();
+        assert_eq!(blank_rows, vec![0, 1, 3, 4, 6, 7]);
+        // The clip comes from the real samples alone.
+        assert!(rendered.applied_scale.unwrap() <= 1.0);
+    }
+
+    #[test]
+    fn test_auto_scaling_reports_applied_clip() {
+        let traces: Vec<TraceData> = (0..4)
+            .map(|trace| TraceData::IeeeFloat32((0..50).map(|i| (trace * 50 + i) as f32).collect()))
+            .collect();
+        let mut config = vd_config(4, 4, 50);
+        config.scaling = AmplitudeScaling::Auto;
+
+        let rendered = render_traces(
+            traces,
+            None,
+            4000,
+            &config,
+            PngCompression::Fast,
+            &RenderCancelToken::none(),
+        )
+        .unwrap();
+
+        // 200 magnitudes 0..199: the 99th percentile index is 198.
+        assert_eq!(rendered.applied_scale, Some(198.0));
+        assert_eq!(rendered.scaling_mode, "auto");
+    }
+
+    #[test]
+    fn test_applied_scale_per_mode() {
+        let cases = [
+            (
+                AmplitudeScaling::Global { max_amplitude: 5.0 },
+                Some(5.0),
+                "global",
+            ),
+            (
+                AmplitudeScaling::Manual { scale: 4.0 },
+                Some(0.25),
+                "manual",
+            ),
+            (
+                AmplitudeScaling::Percentile { percentile: 0.5 },
+                Some(3.0),
+                "percentile",
+            ),
+            (
+                AmplitudeScaling::GlobalPercentile {
+                    percentile: 0.5,
+                    cached_clip: None,
+                },
+                Some(3.0),
+                "global-percentile",
+            ),
+            (
+                AmplitudeScaling::PerTrace {
+                    window_size: None,
+                    window_exclude: None,
+                },
+                None,
+                "per-trace",
+            ),
+            (
+                AmplitudeScaling::RmsBalance { reference_rms: 0.5 },
+                None,
+                "rms-balance",
+            ),
+        ];
+        for (scaling, expected_scale, expected_mode) in cases {
+            let resolved = resolve_scaling(
+                &scaling,
+                &[TraceData::IeeeFloat32(vec![1.0, 2.0, 3.0, 4.0])],
+            );
+            assert_eq!(full_scale_amplitude(&resolved), expected_scale);
+            assert_eq!(scaling_mode_name(&scaling), expected_mode);
+            assert_eq!(
+                serde_json::to_value(&scaling).unwrap()["type"],
+                expected_mode
+            );
+        }
+    }
+
+    #[test]
+    fn test_cancelled_render_returns_render_error() {
+        let state = RenderState::new();
+        let cancel = state.token();
+        state.cancel();
+
+        let config = vd_config(2, 2, 4);
+        let traces = vec![TraceData::IeeeFloat32(vec![0.0; 4]); 2];
+
+        let result = render_traces(traces, None, 4000, &config, PngCompression::Fast, &cancel);
+        assert!(matches!(
+            result,
+            Err(AppError::RenderError { message }) if message == RENDER_CANCELLED
+        ));
+    }
+
+    #[test]
+    fn test_wiggle_envelope_uses_source_trace_gain() {
+        // Loud first half, quiet second half; windowed AGC evens them out.
+        let samples: Vec<f32> = (0..4000)
+            .map(|i| {
+                let amplitude = if i < 2000 { 100.0 } else { 1.0 };
+                amplitude * (i as f32 * 0.5).sin()
+            })
+            .collect();
+        let mut config = vd_config(1, 40, 40);
+        config.render_mode = RenderMode::Wiggle;
+        config.scaling = AmplitudeScaling::PerTrace {
+            window_size: Some(51),
+            window_exclude: None,
+        };
+
+        let rendered = render_traces(
+            vec![TraceData::IeeeFloat32(samples)],
+            None,
+            4000,
+            &config,
+            PngCompression::Fast,
+            &RenderCancelToken::none(),
+        )
+        .unwrap();
+        let img = image::load_from_memory(&rendered.data).unwrap().to_rgb8();
+        let ink = |y: u32| {
+            (0..img.width())
+                .filter(|&x| img.get_pixel(x, y).0 != WIGGLE_BACKGROUND)
+                .count()
+        };
+
+        // Both halves swing across most of the trace's wiggle width.
+        assert!(ink(10) > 10, "loud half spans {} pixels", ink(10));
+        assert!(ink(30) > 10, "quiet half spans {} pixels", ink(30));
+    }
+
+    #[test]
+    fn test_window_exclude_counts_from_trace_start() {
+        // A huge first break at samples 100..200, unit-peak data after it.
+        let samples: Vec<f32> = (0..300)
+            .map(|i| match i {
+                100..=199 => 1000.0,
+                _ => (i as f32 * 0.4).sin(),
+            })
+            .collect();
+        let trace = TraceData::IeeeFloat32(samples);
+        let mut config = vd_config(1, 1, 200);
+        config.viewport.start_sample = Some(100);
+        config.viewport.end_sample = Some(300);
+        config.scaling = AmplitudeScaling::PerTrace {
+            window_size: None,
+            window_exclude: None,
+        };
+        let window = config.viewport.sample_window(300).unwrap().unwrap();
+        // Seismic maps strong amplitudes away from white; count strong rows
+        // in the data below the first break.
+        let strong_deep_rows = |config: &RenderConfig| {
+            let rendered = render_traces(
+                vec![trace.window(window.clone())],
+                None,
+                4000,
+                config,
+                PngCompression::Fast,
+                &RenderCancelToken::none(),
+            )
+            .unwrap();
+            let img = image::load_from_memory(&rendered.data).unwrap().to_rgb8();
+            (100..200)
+                .filter(|&y| img.get_pixel(0, y).0.iter().any(|&c| c < 128))
+                .count()
+        };
+
+        assert_eq!(strong_deep_rows(&config), 0);
+        config.scaling = AmplitudeScaling::PerTrace {
+            window_size: None,
+            window_exclude: Some((100, 200)),
+        };
+        assert!(strong_deep_rows(&config) > 20);
+    }
+
+    #[test]
+    fn test_sample_window_fills_output_height() {
+        // A 5-sample event at 1950..1955 of a 2000-sample trace.
+        let mut samples = vec![0.0f32; 2000];
+        samples[1950..1955].fill(1.0);
+        let trace = TraceData::IeeeFloat32(samples);
+        let mut config = vd_config(1, 1, 2000);
+        let cancel = RenderCancelToken::none();
+        // Seismic maps positive amplitudes to blue, so count rows losing red.
+        let event_rows = |trace: TraceData, config: &RenderConfig| {
+            let rendered = render_traces(
+                vec![trace],
+                None,
+                4000,
+                config,
+                PngCompression::Fast,
+                &cancel,
+            )
+            .unwrap();
+            let img = image::load_from_memory(&rendered.data).unwrap().to_rgb8();
+            assert_eq!(img.height(), 2000);
+            img.pixels().filter(|pixel| pixel[0] < 128).count()
+        };
+
+        let full = event_rows(trace.clone(), &config);
+        config.viewport.start_sample = Some(1900);
+        config.viewport.end_sample = Some(2000);
+        let window = config.viewport.sample_window(2000).unwrap().unwrap();
+        let zoomed = event_rows(trace.window(window), &config);
+
+        assert_eq!(full, 5);
+        assert!(
+            (95..=105).contains(&zoomed),
+            "zoomed event spans {zoomed} rows"
+        );
+    }
+}
@@ -3,6 +3,10 @@
 //! Normalization maps trace samples to a consistent range so rendering modes
 //! can assume values in approximately [-1.0, 1.0].
 //!
+//! Files with invalid IBM float encodings can contain NaN or Inf samples.
+//! Non-finite values are ignored when deriving gains, and infinities are
+//! clamped to the display range so they cannot dominate a render.
+//!
 //! All normalization functions use `rayon` for parallel processing across traces,
 //! providing significant speedup on multi-core systems.
 
@@ -10,86 +14,216 @@ use super::types::AmplitudeScaling;
 use crate::segy::TraceData;
 use rayon::prelude::*;
 
+/// Percentile used to derive the clip for `AmplitudeScaling::Auto`.
+pub const AUTO_CLIP_PERCENTILE: f32 = 0.99;
+
 /// Normalize trace amplitudes to the [-1.0, 1.0] range.
 ///
+/// Returns one vector per trace. The variable density renderer avoids this
+/// nested allocation by normalizing through a `TraceNormalizer` directly.
+///
 /// # Parallelization
 /// Uses `rayon::par_iter()` to process traces in parallel, automatically
 /// utilizing available CPU cores. Each trace is converted and normalized
 /// independently with no shared state.
 pub fn normalize_traces(traces: &[TraceData], scaling: &AmplitudeScaling) -> Vec<Vec<f32>> {
-    match scaling {
-        AmplitudeScaling::Global { max_amplitude } => normalize_global(traces, *max_amplitude),
-        AmplitudeScaling::PerTrace { window_size } => normalize_per_trace(traces, *window_size),
-        AmplitudeScaling::Percentile { percentile } => normalize_percentile(traces, *percentile),
-        AmplitudeScaling::Manual { scale } => normalize_manual(traces, *scale),
-    }
-}
-
-/// Global normalization: all traces scaled by the same factor.
-fn normalize_global(traces: &[TraceData], max_amplitude: f32) -> Vec<Vec<f32>> {
+    let normalizer = TraceNormalizer::new(traces, scaling);
     traces
         .par_iter()
         .map(|trace| {
-            trace_to_f32_slice(trace)
-                .iter()
-                .map(|&v| v / max_amplitude)
-                .collect()
+            let mut samples = Vec::with_capacity(trace.len());
+            normalizer.normalize_into(trace, &mut samples);
+            samples
         })
         .collect()
 }
 
-/// Per-trace AGC: each trace independently normalized.
-fn normalize_per_trace(traces: &[TraceData], window_size: Option<usize>) -> Vec<Vec<f32>> {
-    traces
-        .par_iter()
-        .map(|trace| {
-            let samples = trace_to_f32_slice(trace);
+/// Normalizes one trace at a time with any panel-wide statistics resolved.
+///
+/// Percentile clips are computed once in `new`, so `normalize_into` only
+/// touches the trace it is given and can run on many threads at once.
+pub struct TraceNormalizer {
+    gain: Gain,
+}
 
-            match window_size {
-                Some(window) if window > 0 => {
-                    // Windowed AGC: sliding window normalization
-                    apply_windowed_agc(&samples, window)
-                }
-                _ => {
-                    // Full-trace AGC: normalize by maximum amplitude
-                    let max_abs = samples
-                        .iter()
-                        .map(|&v| v.abs())
-                        .max_by(|a, b| a.partial_cmp(b).unwrap())
-                        .unwrap_or(1.0);
-
-                    samples.iter().map(|&v| v / max_abs).collect()
-                }
+/// Gain strategy with global statistics already computed.
+enum Gain {
+    /// Divide by a fixed amplitude, clamping infinities.
+    Global(f32),
+    /// Per-trace AGC, windowed or by maximum amplitude, ignoring an
+    /// excluded `[start, end)` sample range when measuring.
+    PerTrace(Option<usize>, Option<(usize, usize)>),
+    /// Divide by a clip level and clamp to [-1, 1].
+    Clipped(f32),
+    /// Multiply by a fixed scale, clamping infinities.
+    Manual(f32),
+    /// Scale each trace to a reference RMS.
+    RmsBalance(f32),
+}
+
+impl TraceNormalizer {
+    /// Resolve `scaling` against the panel, computing percentile clips
+    /// across all `traces` when the scaling does not carry one.
+    pub fn new(traces: &[TraceData], scaling: &AmplitudeScaling) -> Self {
+        let gain = match scaling {
+            AmplitudeScaling::Global { max_amplitude } => Gain::Global(*max_amplitude),
+            AmplitudeScaling::PerTrace {
+                window_size,
+                window_exclude,
+            } => Gain::PerTrace(*window_size, *window_exclude),
+            AmplitudeScaling::Percentile { percentile } => {
+                Gain::Clipped(percentile_clip(traces, *percentile))
             }
-        })
-        .collect()
+            AmplitudeScaling::GlobalPercentile {
+                percentile,
+                cached_clip,
+            } => Gain::Clipped(cached_clip.unwrap_or_else(|| percentile_clip(traces, *percentile))),
+            AmplitudeScaling::Manual { scale } => Gain::Manual(*scale),
+            AmplitudeScaling::Auto => Gain::Clipped(percentile_clip(traces, AUTO_CLIP_PERCENTILE)),
+            AmplitudeScaling::RmsBalance { reference_rms } => Gain::RmsBalance(*reference_rms),
+        };
+        Self { gain }
+    }
+
+    /// Replace the contents of `out` with the normalized samples of `trace`,
+    /// reusing its allocation.
+    pub fn normalize_into(&self, trace: &TraceData, out: &mut Vec<f32>) {
+        match self.gain {
+            Gain::Global(max_amplitude) => {
+                trace.copy_f32_into(out);
+                out.iter_mut()
+                    .for_each(|v| *v = clamp_infinite(*v / max_amplitude));
+            }
+            Gain::PerTrace(Some(window), exclude) if window > 0 => {
+                // The sliding window reads samples on both sides of the one
+                // being written, so it needs the raw samples separately.
+                apply_windowed_agc(&trace.to_f32_vec(), window, exclude, out);
+            }
+            Gain::PerTrace(_, exclude) => {
+                // Full-trace AGC: normalize by maximum finite amplitude
+                trace.copy_f32_into(out);
+                let max_abs = out
+                    .iter()
+                    .enumerate()
+                    .filter(|&(i, v)| v.is_finite() && !is_excluded(exclude, i))
+                    .map(|(_, &v)| v.abs())
+                    .reduce(f32::max)
+                    .filter(|&max| max > 0.0)
+                    .unwrap_or(1.0);
+                out.iter_mut()
+                    .for_each(|v| *v = clamp_infinite(*v / max_abs));
+            }
+            Gain::Clipped(clip) => {
+                trace.copy_f32_into(out);
+                out.iter_mut()
+                    .for_each(|v| *v = (*v / clip).clamp(-1.0, 1.0));
+            }
+            Gain::Manual(scale) => {
+                trace.copy_f32_into(out);
+                out.iter_mut().for_each(|v| *v = clamp_infinite(*v * scale));
+            }
+            Gain::RmsBalance(reference_rms) => {
+                // Unlike per-trace AGC, dead traces (near-zero RMS) keep unit
+                // gain instead of having their noise boosted to full scale.
+                trace.copy_f32_into(out);
+                let rms = compute_rms(out);
+                let gain = if rms > 1e-10 {
+                    reference_rms / rms
+                } else {
+                    1.0
+                };
+                out.iter_mut()
+                    .for_each(|v| *v = (*v * gain).clamp(-1.0, 1.0));
+            }
+        }
+    }
 }
 
 /// Apply windowed AGC normalization to a trace.
 ///
 /// For each sample, computes the RMS (root mean square) amplitude in a window
-/// centered on that sample, then normalizes by that local RMS value.
-fn apply_windowed_agc(samples: &[f32], window_size: usize) -> Vec<f32> {
+/// centered on that sample, then normalizes by that local RMS value. The
+/// window's sum of squares is maintained incrementally, so the cost is O(n)
+/// regardless of window size. Non-finite samples are excluded, as in
+/// `compute_rms`, and so are samples in the `exclude` range.
+fn apply_windowed_agc(
+    samples: &[f32],
+    window_size: usize,
+    exclude: Option<(usize, usize)>,
+    normalized: &mut Vec<f32>,
+) {
     let n = samples.len();
     let half_window = window_size / 2;
-    let mut normalized = Vec::with_capacity(n);
-
-    for i in 0..n {
-        // Determine window bounds (clamped to array bounds)
-        let start = i.saturating_sub(half_window);
-        let end = (i + half_window + 1).min(n);
+    normalized.clear();
+    // Excluded samples never enter the window, as if they were NaN.
+    let measured = |i: usize| {
+        if is_excluded(exclude, i) {
+            f32::NAN
+        } else {
+            samples[i]
+        }
+    };
 
-        // Compute RMS amplitude in the window
-        let window_samples = &samples[start..end];
-        let rms = compute_rms(window_samples);
+    // Window for sample 0 is [0, half_window]
+    let mut window = RunningSquares::default();
+    for i in 0..(half_window + 1).min(n) {
+        window.add(measured(i));
+    }
 
-        // Normalize by RMS (avoid division by zero)
+    for (i, &sample) in samples.iter().enumerate() {
+        // Normalize by window RMS (avoid division by zero)
+        let rms = window.rms();
         let gain = if rms > 1e-10 { 1.0 / rms } else { 1.0 };
-        normalized.push(samples[i] * gain);
+        // Clamp to [-1, 1] to prevent extreme values
+        normalized.push((sample * gain).clamp(-1.0, 1.0));
+
+        // Slide the window: drop the sample leaving, add the one entering
+        if i >= half_window {
+            window.remove(measured(i - half_window));
+        }
+        if i + half_window + 1 < n {
+            window.add(measured(i + half_window + 1));
+        }
     }
+}
+
+/// Whether sample `index` falls in the excluded `[start, end)` range.
+#[inline]
+fn is_excluded(exclude: Option<(usize, usize)>, index: usize) -> bool {
+    exclude.is_some_and(|(start, end)| (start..end).contains(&index))
+}
 
-    // Clamp to [-1, 1] to prevent extreme values
-    normalized.iter().map(|&v| v.clamp(-1.0, 1.0)).collect()
+/// Sum of squares over a sliding window of finite samples.
+///
+/// Accumulates in f64 so adding and removing many samples does not drift.
+#[derive(Default)]
+struct RunningSquares {
+    sum: f64,
+    count: usize,
+}
+
+impl RunningSquares {
+    fn add(&mut self, value: f32) {
+        if value.is_finite() {
+            self.sum += (value as f64) * (value as f64);
+            self.count += 1;
+        }
+    }
+
+    fn remove(&mut self, value: f32) {
+        if value.is_finite() {
+            self.sum -= (value as f64) * (value as f64);
+            self.count -= 1;
+        }
+    }
+
+    /// RMS of the window, or 1.0 when it holds no finite samples.
+    fn rms(&self) -> f32 {
+        if self.count == 0 {
+            return 1.0;
+        }
+        (self.sum.max(0.0) / self.count as f64).sqrt() as f32
+    }
 }
 
 /// Compute root mean square (RMS) of samples.
@@ -99,65 +233,243 @@ fn compute_rms(samples: &[f32]) -> f32 {
         return 1.0;
     }
 
-    let sum_squares: f32 = samples.iter().map(|&v| v * v).sum();
-    (sum_squares / samples.len() as f32).sqrt()
+    let (sum_squares, count) = samples
+        .iter()
+        .filter(|v| v.is_finite())
+        .fold((0.0f32, 0usize), |(sum, count), &v| {
+            (sum + v * v, count + 1)
+        });
+    if count == 0 {
+        return 1.0;
+    }
+    (sum_squares / count as f32).sqrt()
 }
 
-/// Percentile clipping: robust to outliers (computed globally across all traces).
-fn normalize_percentile(traces: &[TraceData], percentile: f32) -> Vec<Vec<f32>> {
-    // Collect all samples from all traces
-    let all_samples: Vec<f32> = traces.iter().flat_map(trace_to_f32_slice).collect();
-
-    // Sort by absolute value to find the percentile
-    let mut sorted: Vec<f32> = all_samples.iter().map(|&v| v.abs()).collect();
-    sorted.sort_by(|a, b| a.partial_cmp(b).unwrap());
+/// Map infinities to the edge of the display range, leaving other values as-is.
+#[inline]
+fn clamp_infinite(value: f32) -> f32 {
+    if value.is_infinite() {
+        value.signum()
+    } else {
+        value
+    }
+}
 
-    // Find the percentile value
-    let idx = ((sorted.len() as f32) * percentile).min((sorted.len() - 1) as f32) as usize;
-    let p_value = sorted.get(idx).copied().unwrap_or(1.0).max(1e-10); // Avoid division by zero
+/// Absolute amplitude at `percentile` across all traces, ignoring non-finite samples.
+pub fn percentile_clip(traces: &[TraceData], percentile: f32) -> f32 {
+    // Collect finite absolute values from all traces; NaN/Inf would break the sort
+    let mut sorted: Vec<f32> = traces
+        .iter()
+        .flat_map(TraceData::to_f32_vec)
+        .filter(|v| v.is_finite())
+        .map(f32::abs)
+        .collect();
+    sorted.sort_by(f32::total_cmp);
 
-    // Normalize all traces using the global percentile value
-    traces
-        .par_iter()
-        .map(|trace| {
-            trace_to_f32_slice(trace)
-                .iter()
-                .map(|&v| (v / p_value).clamp(-1.0, 1.0))
-                .collect()
-        })
-        .collect()
+    // Find the percentile value, clipping at 1.0 when nothing finite remains
+    match sorted.len() {
+        0 => 1.0,
+        len => {
+            let idx = ((len as f32) * percentile).min((len - 1) as f32) as usize;
+            sorted[idx].max(1e-10) // Avoid division by zero
+        }
+    }
 }
 
-/// Manual scaling.
-fn normalize_manual(traces: &[TraceData], scale: f32) -> Vec<Vec<f32>> {
-    traces
-        .par_iter()
-        .map(|trace| {
-            trace_to_f32_slice(trace)
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_per_trace_ignores_non_finite_samples() {
+        let traces = vec![
+            TraceData::IeeeFloat32(vec![1.0, f32::NAN, -4.0, f32::INFINITY, 2.0]),
+            TraceData::IeeeFloat32(vec![f32::NAN, f32::NEG_INFINITY]),
+        ];
+        let normalized = normalize_traces(
+            &traces,
+            &AmplitudeScaling::PerTrace {
+                window_size: None,
+                window_exclude: None,
+            },
+        );
+
+        assert_eq!(normalized[0][0], 0.25);
+        assert!(normalized[0][1].is_nan());
+        assert_eq!(normalized[0][2], -1.0);
+        assert_eq!(normalized[0][3], 1.0);
+        // Entirely non-finite trace falls back to unit gain.
+        assert!(normalized[1][0].is_nan());
+        assert_eq!(normalized[1][1], -1.0);
+    }
+
+    #[test]
+    fn test_per_trace_exclude_zone_keeps_deep_data() {
+        // A huge first break over the first 100 samples, then unit-peak data.
+        let samples: Vec<f32> = (0..300)
+            .map(|i| {
+                if i < 100 {
+                    1000.0
+                } else {
+                    (i as f32 * 0.4).sin()
+                }
+            })
+            .collect();
+        let traces = vec![TraceData::IeeeFloat32(samples)];
+        let deep_peak = |window_size, window_exclude| {
+            let scaling = AmplitudeScaling::PerTrace {
+                window_size,
+                window_exclude,
+            };
+            normalize_traces(&traces, &scaling)[0][150..]
                 .iter()
-                .map(|&v| v * scale)
-                .collect()
-        })
-        .collect()
-}
+                .fold(0.0f32, |max, v| max.max(v.abs()))
+        };
 
-/// Convert TraceData enum to an owned `Vec<f32>`.
-///
-/// This allocates a new buffer because trace data can be stored in multiple
-/// concrete formats.
-fn trace_to_f32_slice(trace: &TraceData) -> Vec<f32> {
-    match trace {
-        TraceData::IbmFloat32(samples) => samples.clone(),
-        TraceData::IeeeFloat32(samples) => samples.clone(),
-        TraceData::Int32(samples) => samples.iter().map(|&v| v as f32).collect(),
-        TraceData::Int16(samples) => samples.iter().map(|&v| v as f32).collect(),
-        TraceData::Int8(samples) => samples.iter().map(|&v| v as f32).collect(),
-        TraceData::FixedPointWithGain(samples) => samples
-            .iter()
-            .map(|&(gain, value)| {
-                let scale = 2.0_f32.powi(gain as i32);
-                (value as f32) * scale
+        assert!(deep_peak(None, None) < 0.01);
+        assert!((deep_peak(None, Some((0, 100))) - 1.0).abs() < 1e-3);
+        // Windowed AGC near the mute edge no longer sees the first break.
+        let windowed = normalize_traces(
+            &traces,
+            &AmplitudeScaling::PerTrace {
+                window_size: Some(101),
+                window_exclude: Some((0, 100)),
+            },
+        );
+        assert!(windowed[0][100..130].iter().any(|v| v.abs() > 0.9));
+        assert!(deep_peak(Some(101), None) > 0.9);
+    }
+
+    #[test]
+    fn test_percentile_skips_embedded_nan() {
+        let traces = vec![
+            TraceData::IeeeFloat32(vec![f32::NAN, 1.0, 2.0, f32::NAN]),
+            TraceData::IeeeFloat32(vec![3.0, f32::NAN, -4.0]),
+        ];
+        let normalized =
+            normalize_traces(&traces, &AmplitudeScaling::Percentile { percentile: 0.5 });
+
+        // Finite magnitudes are [1, 2, 3, 4]; the median index picks 3.0.
+        assert!((normalized[0][2] - 2.0 / 3.0).abs() < 1e-6);
+        assert_eq!(normalized[1][2], -1.0);
+        assert!(normalized[0][0].is_nan());
+
+        let all_nan = vec![TraceData::IeeeFloat32(vec![f32::NAN; 3])];
+        let normalized =
+            normalize_traces(&all_nan, &AmplitudeScaling::Percentile { percentile: 0.9 });
+        assert_eq!(normalized[0].len(), 3);
+    }
+
+    #[test]
+    fn test_global_percentile_reuses_cached_clip() {
+        let traces = vec![TraceData::IeeeFloat32(vec![1.0, 2.0, 3.0, 4.0])];
+        let computed = normalize_traces(
+            &traces,
+            &AmplitudeScaling::GlobalPercentile {
+                percentile: 0.5,
+                cached_clip: None,
+            },
+        );
+        assert_eq!(percentile_clip(&traces, 0.5), 3.0);
+        assert!((computed[0][1] - 2.0 / 3.0).abs() < 1e-6);
+
+        let cached = normalize_traces(
+            &traces,
+            &AmplitudeScaling::GlobalPercentile {
+                percentile: 0.5,
+                cached_clip: Some(8.0),
+            },
+        );
+        assert_eq!(cached[0], vec![0.125, 0.25, 0.375, 0.5]);
+    }
+
+    #[test]
+    fn test_rms_balance_equalizes_loud_and_quiet_traces() {
+        let traces = vec![
+            TraceData::IeeeFloat32(vec![100.0, -100.0, 100.0, -100.0]),
+            TraceData::IeeeFloat32(vec![0.01, -0.01, 0.01, -0.01]),
+            TraceData::IeeeFloat32(vec![0.0; 4]),
+        ];
+        let normalized = normalize_traces(
+            &traces,
+            &AmplitudeScaling::RmsBalance { reference_rms: 0.5 },
+        );
+
+        assert!(normalized[0].iter().all(|v| (v.abs() - 0.5).abs() < 1e-4));
+        assert!(normalized[1].iter().all(|v| (v.abs() - 0.5).abs() < 1e-4));
+        assert!(normalized[2].iter().all(|&v| v == 0.0));
+    }
+
+    /// Windowed AGC recomputing each window's RMS from scratch, O(n·w).
+    fn naive_windowed_agc(samples: &[f32], window_size: usize) -> Vec<f32> {
+        let half = window_size / 2;
+        (0..samples.len())
+            .map(|i| {
+                let start = i.saturating_sub(half);
+                let end = (i + half + 1).min(samples.len());
+                let rms = compute_rms(&samples[start..end]);
+                let gain = if rms > 1e-10 { 1.0 / rms } else { 1.0 };
+                (samples[i] * gain).clamp(-1.0, 1.0)
             })
-            .collect(),
+            .collect()
+    }
+
+    fn chirp(len: usize) -> Vec<f32> {
+        (0..len)
+            .map(|i| (i as f32 * 0.05).sin() * (1.0 + i as f32 * 0.01))
+            .collect()
+    }
+
+    #[test]
+    fn test_windowed_agc_matches_direct_rms() {
+        let samples = chirp(2_000);
+
+        let mut agc = Vec::new();
+        apply_windowed_agc(&samples, 101, None, &mut agc);
+        let expected = naive_windowed_agc(&samples, 101);
+        for (i, (&value, &expected)) in agc.iter().zip(&expected).enumerate() {
+            assert!((value - expected).abs() < 1e-4, "sample {}", i);
+        }
+    }
+
+    #[test]
+    fn test_windowed_agc_running_sum_holds_over_long_trace() {
+        // Running sums accumulate rounding; check it stays within tolerance
+        // across a long trace and wide window.
+        let samples = chirp(10_000);
+        let window = 500;
+
+        let mut running = Vec::new();
+        apply_windowed_agc(&samples, window, None, &mut running);
+        let naive = naive_windowed_agc(&samples, window);
+        assert_eq!(running.len(), naive.len());
+        for (i, (&a, &b)) in running.iter().zip(&naive).enumerate() {
+            assert!((a - b).abs() < 1e-4, "sample {}", i);
+        }
+    }
+
+    #[test]
+    fn test_infinity_clamped_in_every_mode() {
+        let traces = vec![TraceData::IeeeFloat32(vec![0.5, f32::INFINITY, -1.0])];
+        let modes = [
+            AmplitudeScaling::Global { max_amplitude: 2.0 },
+            AmplitudeScaling::PerTrace {
+                window_size: None,
+                window_exclude: None,
+            },
+            AmplitudeScaling::PerTrace {
+                window_size: Some(3),
+                window_exclude: None,
+            },
+            AmplitudeScaling::Percentile { percentile: 0.5 },
+            AmplitudeScaling::Manual { scale: 1.0 },
+            AmplitudeScaling::RmsBalance { reference_rms: 0.5 },
+            AmplitudeScaling::Auto,
+        ];
+
+        for scaling in &modes {
+            let normalized = normalize_traces(&traces, scaling);
+            assert_eq!(normalized[0][1], 1.0, "{:?}", scaling);
+        }
     }
 }
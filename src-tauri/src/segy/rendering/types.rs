@@ -1,6 +1,9 @@
 //! Data types shared by the rendering pipeline and frontend.
 
+use crate::error::AppError;
+use crate::segy::io;
 use serde::{Deserialize, Serialize};
+use std::ops::Range;
 
 /// Viewport configuration for rendering
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -14,6 +17,66 @@ pub struct ViewportConfig {
     pub width: u32,
     /// Output image height in pixels
     pub height: u32,
+    /// Display traces right-to-left (e.g. for lines shot in reverse)
+    #[serde(default)]
+    pub flip_traces: bool,
+    /// RGB background; defaults to white for wiggles and black for VD
+    #[serde(default)]
+    pub background_color: Option<[u8; 3]>,
+    /// First sample to render (0-based); defaults to the start of the trace
+    #[serde(default)]
+    pub start_sample: Option<usize>,
+    /// End of the sample window (exclusive); defaults to the end of the trace
+    #[serde(default)]
+    pub end_sample: Option<usize>,
+    /// How variable density fills a width wider than the trace count
+    #[serde(default)]
+    pub fit: FitMode,
+}
+
+/// How a variable density render with fewer traces than pixel columns fills
+/// the viewport width
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum FitMode {
+    /// Upscale the traces across the full width
+    #[default]
+    Stretch,
+    /// Draw one column per trace from the left and leave the rest as background
+    Pad,
+}
+
+impl ViewportConfig {
+    /// Viewport over the first `trace_count` traces at `width` x `height`,
+    /// with every display option at its default.
+    pub fn new(trace_count: usize, width: u32, height: u32) -> Self {
+        Self {
+            start_trace: 0,
+            trace_count,
+            width,
+            height,
+            flip_traces: false,
+            background_color: None,
+            start_sample: None,
+            end_sample: None,
+            fit: FitMode::default(),
+        }
+    }
+
+    /// Resolve the requested sample window against the trace length.
+    ///
+    /// Returns `None` when neither bound is set, so callers decode full traces.
+    pub fn sample_window(
+        &self,
+        samples_per_trace: usize,
+    ) -> Result<Option<Range<usize>>, AppError> {
+        if self.start_sample.is_none() && self.end_sample.is_none() {
+            return Ok(None);
+        }
+        let window = self.start_sample.unwrap_or(0)..self.end_sample.unwrap_or(samples_per_trace);
+        io::validate_sample_window(&window, samples_per_trace)?;
+        Ok(Some(window))
+    }
 }
 
 /// Colormap types
@@ -43,11 +106,56 @@ pub enum AmplitudeScaling {
     PerTrace {
         #[serde(rename = "windowSize")]
         window_size: Option<usize>,
+        /// Samples `[start, end)` left out of the gain statistics, e.g. a
+        /// loud first break; they are still scaled and drawn. Indices count
+        /// from the start of the trace, not of the viewport's sample window.
+        #[serde(rename = "windowExclude", default)]
+        window_exclude: Option<(usize, usize)>,
     },
     /// Percentile clipping (robust to outliers)
     Percentile { percentile: f32 },
+    /// Percentile clipping with a clip level held fixed across renders.
+    ///
+    /// With `cached_clip` unset the clip is computed from the rendered traces
+    /// and returned in `RenderedImage::applied_scale` so the caller can persist it and
+    /// pass it back while panning, avoiding brightness flicker.
+    GlobalPercentile {
+        percentile: f32,
+        #[serde(rename = "cachedClip", default)]
+        cached_clip: Option<f32>,
+    },
     /// Manual scale factor
     Manual { scale: f32 },
+    /// Automatic percentile clip computed from the rendered window
+    ///
+    /// The clip is reported back in `RenderedImage::applied_scale`.
+    Auto,
+    /// Trace equalization: scale each trace to a common RMS amplitude
+    RmsBalance {
+        #[serde(rename = "referenceRms")]
+        reference_rms: f32,
+    },
+}
+
+impl AmplitudeScaling {
+    /// Rebase `window_exclude` onto traces cut to start at sample `window_start`.
+    ///
+    /// Other modes, and per-trace scaling without an exclusion, are unchanged.
+    pub fn for_sample_window(&self, window_start: usize) -> Self {
+        match *self {
+            Self::PerTrace {
+                window_size,
+                window_exclude: Some((start, end)),
+            } => Self::PerTrace {
+                window_size,
+                window_exclude: Some((
+                    start.saturating_sub(window_start),
+                    end.saturating_sub(window_start),
+                )),
+            },
+            ref other => other.clone(),
+        }
+    }
 }
 
 /// Rendering mode
@@ -70,6 +178,19 @@ pub enum ImageFormat {
     Png,
 }
 
+/// PNG compression level
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum PngCompression {
+    /// Balanced speed and size
+    Default,
+    /// Fastest encode, larger output (interactive renders)
+    #[default]
+    Fast,
+    /// Smallest output, slowest encode (exports)
+    Best,
+}
+
 /// Rendered image result
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RenderedImage {
@@ -81,6 +202,17 @@ pub struct RenderedImage {
     pub data: Vec<u8>,
     /// Encoding format of `data`.
     pub format: ImageFormat,
+    /// Amplitude mapped to full color/deflection (the global max, percentile
+    /// clip, or inverse manual scale); `None` for per-trace gain modes.
+    #[serde(default)]
+    pub applied_scale: Option<f32>,
+    /// Scaling mode tag that produced the image, e.g. `percentile`.
+    #[serde(default)]
+    pub scaling_mode: String,
+    /// Number of traces drawn when auxiliary traces were skipped or traces
+    /// were excluded by identification code.
+    #[serde(default)]
+    pub data_trace_count: Option<usize>,
 }
 
 /// Wiggle rendering configuration
@@ -99,6 +231,46 @@ pub struct WiggleConfig {
     pub positive_fill_color: [u8; 3], // RGB
     /// RGB fill color for negative amplitudes.
     pub negative_fill_color: [u8; 3], // RGB
+    /// Normalized amplitudes beyond `±clip` are clamped, so deflection never
+    /// exceeds `clip` times the full wiggle width. Must not be negative.
+    #[serde(default = "default_wiggle_clip")]
+    pub clip: f32,
+    /// Full wiggle width in trace spacings, replacing the per-mode default;
+    /// values above 0.5 let wiggles overlap their neighbours on purpose. Must
+    /// be positive and at most `MAX_WIGGLE_EXCURSION`.
+    #[serde(default)]
+    pub excursion: Option<f32>,
+    /// Variable-area display: skip the line stroke and fill only peaks, using
+    /// `positive_fill_color`. Keeps dense sections readable.
+    #[serde(default)]
+    pub variable_area: bool,
+    /// Fraction of full scale a peak must exceed to be filled in
+    /// variable-area mode; fill starts at the threshold, not the centerline.
+    #[serde(default)]
+    pub fill_threshold: f32,
+    /// Render at this many times the viewport size, with line width scaled
+    /// to match, then box-filter back down for anti-aliased export. 1 (the
+    /// default) renders directly; at most `MAX_WIGGLE_SUPERSAMPLE`.
+    #[serde(default = "default_wiggle_supersample")]
+    pub supersample: u32,
+}
+
+/// Largest accepted `WiggleConfig::supersample` factor.
+pub const MAX_WIGGLE_SUPERSAMPLE: u32 = 8;
+
+/// Largest accepted `WiggleConfig::excursion`, in trace spacings.
+pub const MAX_WIGGLE_EXCURSION: f32 = 20.0;
+
+/// Most pixels a supersampled wiggle canvas may hold before downsampling
+/// (64 megapixels, 192 MiB as RGB).
+pub const MAX_SUPERSAMPLED_PIXELS: u64 = 64 * 1024 * 1024;
+
+fn default_wiggle_clip() -> f32 {
+    1.0
+}
+
+fn default_wiggle_supersample() -> u32 {
+    1
 }
 
 /// Complete rendering configuration combining all rendering parameters
@@ -115,4 +287,138 @@ pub struct RenderConfig {
     pub render_mode: RenderMode,
     /// Optional wiggle overlay settings.
     pub wiggle_config: Option<WiggleConfig>,
+    /// Optional gap columns between trace ensembles.
+    #[serde(default)]
+    pub gather_gap: Option<GatherGapConfig>,
+    /// Trace attribute displayed instead of raw amplitude.
+    #[serde(default)]
+    pub attribute: TraceAttribute,
+    /// Optional band-pass filter applied before normalization.
+    #[serde(default)]
+    pub filter: Option<BandpassConfig>,
+    /// Paint dead (header-flagged or all-zero) traces with the background.
+    #[serde(default)]
+    pub hide_dead_traces: bool,
+    /// Combine adjacent traces into one column each when there are more
+    /// traces than output pixels (variable density only).
+    #[serde(default)]
+    pub trace_binning: Option<TraceBinning>,
+    /// Trace identification codes (bytes 29-30) to leave out of the render,
+    /// e.g. `[3, 7]` to drop dummy and timing traces.
+    #[serde(default)]
+    pub excluded_trace_ids: Vec<i16>,
+    /// Blend linearly between samples when the output is taller than the
+    /// traces (variable density only), instead of repeating rows.
+    #[serde(default)]
+    pub interpolate_samples: bool,
+    /// Shift traces down by their delay recording time (bytes 109-110) so
+    /// equal times line up across traces (variable density only; needs headers).
+    #[serde(default)]
+    pub align_delays: bool,
+    /// Sample value marking missing data (e.g. `-999.25`); matching samples
+    /// are left blank and kept out of amplitude statistics.
+    #[serde(default)]
+    pub null_value: Option<f32>,
+    /// Give CDP numbers skipped by the line their own columns, blank or
+    /// interpolated (needs headers).
+    #[serde(default)]
+    pub missing_traces: Option<MissingTraceFill>,
+    /// Normalized amplitude (-1..1) drawn with the colormap's midpoint color,
+    /// e.g. the data mean for asymmetric amplitudes; 0 keeps the usual map.
+    #[serde(default)]
+    pub colormap_center: f32,
+    /// Constant phase rotation in degrees applied to every trace before
+    /// normalization, e.g. 90 to check polarity.
+    #[serde(default)]
+    pub phase_rotation_deg: Option<f32>,
+}
+
+impl RenderConfig {
+    /// Plain variable density render of `viewport`: amplitude attribute, no
+    /// filtering, and every optional stage off.
+    pub fn variable_density(
+        viewport: ViewportConfig,
+        colormap_type: ColormapType,
+        scaling: AmplitudeScaling,
+    ) -> Self {
+        Self {
+            viewport,
+            colormap_type,
+            scaling,
+            render_mode: RenderMode::VariableDensity,
+            wiggle_config: None,
+            gather_gap: None,
+            attribute: TraceAttribute::default(),
+            filter: None,
+            hide_dead_traces: false,
+            trace_binning: None,
+            excluded_trace_ids: Vec::new(),
+            interpolate_samples: false,
+            align_delays: false,
+            null_value: None,
+            missing_traces: None,
+            colormap_center: 0.0,
+            phase_rotation_deg: None,
+        }
+    }
+}
+
+/// How columns for traces missing from the CDP numbering are drawn
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum MissingTraceFill {
+    /// Paint them with the background
+    Blank,
+    /// Blend the traces on either side linearly
+    Interpolate,
+}
+
+/// How traces sharing an output column are combined
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum TraceBinning {
+    /// Average the normalized samples
+    Mean,
+    /// Keep the sample with the largest magnitude, preserving its sign
+    MaxAbs,
+    /// Show only the trace at the center of each column's bin
+    Nearest,
+}
+
+/// Zero-phase Butterworth band-pass corners
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BandpassConfig {
+    /// Low-cut corner in Hz; zero disables the high-pass stage.
+    pub low_hz: f32,
+    /// High-cut corner in Hz; at or above Nyquist disables the low-pass stage.
+    pub high_hz: f32,
+}
+
+/// Trace attribute computed before normalization
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum TraceAttribute {
+    /// Raw sample amplitude
+    #[default]
+    Amplitude,
+    /// Instantaneous amplitude (Hilbert envelope); always non-negative
+    Envelope,
+}
+
+/// Gap inserted between traces where a trace header field changes
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GatherGapConfig {
+    /// Trace header field key to watch, e.g. `cdp_ensemble_number`.
+    pub header_field: String,
+    /// Gap width in pixels.
+    pub width: u32,
+    /// RGB gap color.
+    #[serde(default = "default_gap_color")]
+    pub color: [u8; 3], // RGB
+}
+
+fn default_gap_color() -> [u8; 3] {
+    [255, 255, 255]
 }
@@ -4,58 +4,82 @@
 //! a 2D heatmap visualization where x-axis represents trace number and y-axis
 //! represents sample depth/time.
 
-use super::{colormap::Colormap, encode_png_fast, normalizer, types::*};
+use super::{cancel::RenderCancelToken, colormap::Colormap, normalizer::TraceNormalizer, types::*};
+use crate::error::AppError;
 use crate::segy::TraceData;
 use image::{ImageBuffer, Rgb, RgbImage};
 use rayon::prelude::*;
 
-/// Render a variable density image from normalized traces.
+/// Fill for pixels without trace data when the viewport does not set one.
+pub(super) const DEFAULT_BACKGROUND: [u8; 3] = [0, 0, 0];
+
+/// Pixel columns colored per parallel pass before copying into the image.
+const COLUMN_BATCH: usize = 256;
+
+/// Render a variable density image from raw traces.
 ///
 /// # Arguments
 /// * `traces` - Raw trace data to be normalized and rendered
 /// * `viewport` - Output dimensions and trace range
 /// * `colormap` - Color mapping function for amplitudes
 /// * `scaling` - Normalization strategy
+/// * `binning` - How to combine traces when there are more than `viewport.width`
+/// * `interpolate` - Blend linearly between samples when `viewport.height`
+///   has more rows than the traces have samples
+/// * `row_offsets` - Samples to shift each trace down by (e.g. from
+///   `delay_offsets`); empty for no shift
+/// * `cancel` - Cancellation token checked between trace columns
 ///
 /// # Returns
-/// PNG-encoded image with variable density visualization
+/// Variable density image scaled to the viewport size
 ///
 /// # Parallelization
-/// Uses `rayon::par_bridge()` to parallelize pixel generation across all CPU cores.
-/// Each pixel is computed independently, allowing near-linear speedup.
+/// Trace columns are normalized and colorized in parallel with `rayon`, so
+/// work scales with available CPU cores.
+#[allow(clippy::too_many_arguments)]
 pub fn render_variable_density(
     traces: Vec<TraceData>,
     viewport: &ViewportConfig,
     colormap: &dyn Colormap,
     scaling: &AmplitudeScaling,
-) -> Result<RenderedImage, String> {
-    // 1. Normalize amplitudes
-    let normalized = normalizer::normalize_traces(&traces, scaling);
-
-    // 2. Create image buffer - always use full trace height
-    let width = viewport.trace_count as u32;
-    let height = if !normalized.is_empty() {
-        normalized[0].len() as u32
-    } else {
-        0
+    binning: Option<TraceBinning>,
+    interpolate: bool,
+    row_offsets: &[usize],
+    cancel: &RenderCancelToken,
+) -> Result<RgbImage, AppError> {
+    // 1-3. Normalize and colorize one pixel column per trace (or per bin of
+    // traces) - one row per windowed sample, or per output row when
+    // interpolating
+    let normalizer = TraceNormalizer::new(&traces, scaling);
+    let span = sample_span(&traces, row_offsets);
+    let rows =
+        (interpolate && span > 1 && viewport.height as usize > span).then_some(viewport.height);
+    cancel.check()?;
+    let img = match binning {
+        Some(binning) if viewport.trace_count > viewport.width as usize => bin_and_render(
+            &traces,
+            &normalizer,
+            binning,
+            viewport.width,
+            rows,
+            row_offsets,
+            colormap,
+            viewport.background_color,
+            cancel,
+        )?,
+        _ => normalize_and_render(
+            &traces,
+            &normalizer,
+            viewport.trace_count as u32,
+            rows,
+            row_offsets,
+            colormap,
+            viewport.background_color,
+            cancel,
+        )?,
     };
-    let mut img: RgbImage = ImageBuffer::new(width, height);
-
-    // 3. Parallel pixel generation
-    img.enumerate_pixels_mut()
-        .par_bridge()
-        .for_each(|(x, y, pixel)| {
-            let trace_idx = x as usize;
-            let sample_idx = y as usize;
-
-            if trace_idx < normalized.len() && sample_idx < normalized[trace_idx].len() {
-                let amplitude = normalized[trace_idx][sample_idx];
-                let rgb = colormap.to_rgb(amplitude);
-                *pixel = Rgb(rgb);
-            } else {
-                *pixel = Rgb([0, 0, 0]); // Black for out-of-bounds
-            }
-        });
+    drop(traces);
+    let (width, height) = img.dimensions();
 
     // 4. Scale to output dimensions if needed
     let img = if width != viewport.width || height != viewport.height {
@@ -69,6 +93,551 @@ pub fn render_variable_density(
         img
     };
 
-    // 5. Encode with fast PNG settings
-    encode_png_fast(img)
+    Ok(img)
+}
+
+/// Widen `img` to `width` by filling new columns on the right with `background`.
+pub(super) fn pad_to_width(img: RgbImage, width: u32, background: [u8; 3]) -> RgbImage {
+    let mut padded = ImageBuffer::from_pixel(width, img.height(), Rgb(background));
+    image::imageops::replace(&mut padded, &img, 0, 0);
+    padded
+}
+
+/// Normalize and colorize traces into an image with one pixel column per
+/// trace, without materializing the normalized panel.
+///
+/// Produces the same image as `normalize_traces` followed by
+/// `rasterize_columns`, but the only panel-sized allocations are 3 bytes per
+/// pixel rather than a heap vector of f32 per trace plus a color vector per
+/// column (see `paint_columns`). With `rows`, the image is that tall and
+/// samples are interpolated onto it. Trace `i` starts `row_offsets[i]`
+/// samples down, with background above it.
+#[allow(clippy::too_many_arguments)]
+pub(super) fn normalize_and_render(
+    traces: &[TraceData],
+    normalizer: &TraceNormalizer,
+    width: u32,
+    rows: Option<u32>,
+    row_offsets: &[usize],
+    colormap: &dyn Colormap,
+    background: Option<[u8; 3]>,
+    cancel: &RenderCancelToken,
+) -> Result<RgbImage, AppError> {
+    let span = sample_span(traces, row_offsets);
+    let columns = traces.len().min(width as usize);
+    paint_columns(
+        width,
+        rows.unwrap_or(span as u32),
+        rows.map(|_| span),
+        columns,
+        colormap,
+        background,
+        cancel,
+        |x, _: &mut (), samples| {
+            normalizer.normalize_into(&traces[x], samples);
+            row_offset(row_offsets, x)
+        },
+    )
+}
+
+/// Render `width` columns, each combining an equal share of adjacent traces.
+///
+/// Column `x` covers traces `x * n / width` up to `(x + 1) * n / width`, the
+/// same layout the other renderers use, and is built from their normalized
+/// samples before any pixels are generated. Unlike resizing a one-column-
+/// per-trace image, every trace contributes to exactly one column. NaN
+/// samples are left out of each bin. `rows` and `row_offsets` work as in
+/// `normalize_and_render`; shifted traces are binned at their shifted rows.
+#[allow(clippy::too_many_arguments)]
+pub(super) fn bin_and_render(
+    traces: &[TraceData],
+    normalizer: &TraceNormalizer,
+    binning: TraceBinning,
+    width: u32,
+    rows: Option<u32>,
+    row_offsets: &[usize],
+    colormap: &dyn Colormap,
+    background: Option<[u8; 3]>,
+    cancel: &RenderCancelToken,
+) -> Result<RgbImage, AppError> {
+    let span = sample_span(traces, row_offsets);
+    let trace_count = traces.len();
+    let columns = trace_count.min(width as usize);
+    paint_columns(
+        width,
+        rows.unwrap_or(span as u32),
+        rows.map(|_| span),
+        columns,
+        colormap,
+        background,
+        cancel,
+        |x, scratch: &mut (Vec<f32>, Vec<u32>), column| {
+            let (samples, counts) = scratch;
+            column.clear();
+            counts.clear();
+            let (start, end) = match (x * trace_count / columns, (x + 1) * trace_count / columns) {
+                (start, end) if binning == TraceBinning::Nearest => {
+                    let center = (start + end) / 2;
+                    (center, center + 1)
+                }
+                bin => bin,
+            };
+            // The column begins at the shallowest trace in the bin.
+            let first_row = (start..end)
+                .map(|i| row_offset(row_offsets, i))
+                .min()
+                .unwrap_or(0);
+            for (i, trace) in traces.iter().enumerate().take(end).skip(start) {
+                normalizer.normalize_into(trace, samples);
+                let shift = row_offset(row_offsets, i) - first_row;
+                if column.len() < shift + samples.len() {
+                    column.resize(shift + samples.len(), 0.0);
+                    counts.resize(shift + samples.len(), 0);
+                }
+                for ((value, count), &sample) in column[shift..]
+                    .iter_mut()
+                    .zip(counts[shift..].iter_mut())
+                    .zip(samples.iter())
+                {
+                    if sample.is_nan() {
+                        continue;
+                    }
+                    match binning {
+                        TraceBinning::Mean => *value += sample,
+                        TraceBinning::MaxAbs if sample.abs() > value.abs() => *value = sample,
+                        TraceBinning::MaxAbs => {}
+                        TraceBinning::Nearest => *value = sample,
+                    }
+                    *count += 1;
+                }
+            }
+            for (value, &count) in column.iter_mut().zip(counts.iter()) {
+                match count {
+                    0 => *value = f32::NAN,
+                    _ if binning == TraceBinning::Mean => *value /= count as f32,
+                    _ => {}
+                }
+            }
+            first_row
+        },
+    )
+}
+
+/// Rows needed to hold every trace below its offset.
+fn sample_span(traces: &[TraceData], row_offsets: &[usize]) -> usize {
+    traces
+        .iter()
+        .enumerate()
+        .map(|(i, trace)| row_offset(row_offsets, i) + trace.len())
+        .max()
+        .unwrap_or(0)
+}
+
+/// Offset of trace `index`, or zero when no offsets are given.
+#[inline]
+fn row_offset(row_offsets: &[usize], index: usize) -> usize {
+    row_offsets.get(index).copied().unwrap_or(0)
+}
+
+/// Color `columns` pixel columns from the samples `fill` writes for each.
+///
+/// `fill` returns the row (in samples) its first sample belongs on; rows
+/// above it keep the background, as do NaN samples. Every worker thread owns
+/// one scratch value and one sample buffer, reused across the columns it
+/// handles. Columns are colored in parallel batches of `COLUMN_BATCH` into a
+/// small column-major buffer, and each batch is copied into the image, so
+/// the image is the only panel-sized allocation. Columns past `columns` and
+/// rows past each column's samples keep the background.
+///
+/// With `sample_span`, row `y` sits at fractional sample
+/// `y * (sample_span - 1) / (height - 1)` and blends its two neighbours, so
+/// the first and last of `sample_span` samples land on the first and last row.
+#[allow(clippy::too_many_arguments)]
+fn paint_columns<S, F>(
+    width: u32,
+    height: u32,
+    sample_span: Option<usize>,
+    columns: usize,
+    colormap: &dyn Colormap,
+    background: Option<[u8; 3]>,
+    cancel: &RenderCancelToken,
+    fill: F,
+) -> Result<RgbImage, AppError>
+where
+    S: Default + Send,
+    F: Fn(usize, &mut S, &mut Vec<f32>) -> usize + Sync,
+{
+    let background = background.unwrap_or(DEFAULT_BACKGROUND);
+    if width == 0 || height == 0 {
+        return Ok(ImageBuffer::from_pixel(width, height, Rgb(background)));
+    }
+
+    let mut img = ImageBuffer::from_pixel(width, height, Rgb(background));
+    let columns = columns.min(width as usize);
+    let column_bytes = height as usize * 3;
+    let row_bytes = width as usize * 3;
+    let mut batch = Vec::new();
+    for batch_start in (0..columns).step_by(COLUMN_BATCH) {
+        let batch_columns = COLUMN_BATCH.min(columns - batch_start);
+        batch.clear();
+        batch.extend(background.repeat(batch_columns * height as usize));
+        batch
+            .par_chunks_mut(column_bytes)
+            .enumerate()
+            .try_for_each_init(
+                || (S::default(), Vec::new()),
+                |(scratch, samples), (i, column)| {
+                    cancel.check()?;
+                    let first_row = fill(batch_start + i, scratch, samples);
+                    let Some(span) = sample_span else {
+                        for (pixel, &amplitude) in column
+                            .chunks_exact_mut(3)
+                            .skip(first_row)
+                            .zip(samples.iter())
+                            .filter(|(_, amplitude)| !amplitude.is_nan())
+                        {
+                            pixel.copy_from_slice(&colormap.to_rgb(amplitude));
+                        }
+                        return Ok(());
+                    };
+                    let step = (span - 1) as f32 / (height - 1).max(1) as f32;
+                    for (y, pixel) in column.chunks_exact_mut(3).enumerate() {
+                        let position = y as f32 * step - first_row as f32;
+                        if position < 0.0 {
+                            continue;
+                        }
+                        let index = position as usize;
+                        let Some(&above) = samples.get(index) else {
+                            break;
+                        };
+                        if above.is_nan() {
+                            continue;
+                        }
+                        // Blending with NaN would blank the row; keep the sample above.
+                        let amplitude = match samples.get(index + 1) {
+                            Some(&below) if below.is_finite() && above.is_finite() => {
+                                above + (below - above) * (position - index as f32)
+                            }
+                            _ => above,
+                        };
+                        pixel.copy_from_slice(&colormap.to_rgb(amplitude));
+                    }
+                    Ok::<_, AppError>(())
+                },
+            )?;
+
+        let target = batch_start * 3..(batch_start + batch_columns) * 3;
+        img.par_chunks_mut(row_bytes)
+            .enumerate()
+            .for_each(|(y, row)| {
+                for (pixel, column) in row[target.clone()]
+                    .chunks_exact_mut(3)
+                    .zip(batch.chunks_exact(column_bytes))
+                {
+                    pixel.copy_from_slice(&column[y * 3..y * 3 + 3]);
+                }
+            });
+    }
+
+    Ok(img)
+}
+
+/// Colorize normalized traces into an image with one pixel column per trace.
+///
+/// Columns beyond the available traces or samples, and NaN samples, are
+/// filled with `background` (black by default). Columns are computed in
+/// parallel and the cancellation token is checked before each one.
+pub(super) fn rasterize_columns(
+    normalized: &[Vec<f32>],
+    width: u32,
+    colormap: &dyn Colormap,
+    background: Option<[u8; 3]>,
+    cancel: &RenderCancelToken,
+) -> Result<RgbImage, AppError> {
+    // Traces hold only the viewport's sample window, so this is the windowed
+    // sample count and the window is stretched over the full output height.
+    let height = normalized.iter().map(Vec::len).max().unwrap_or(0) as u32;
+
+    let columns = normalized
+        .par_iter()
+        .take(width as usize)
+        .map(|trace| {
+            cancel.check()?;
+            Ok(trace
+                .iter()
+                .take(height as usize)
+                .map(|&amplitude| (!amplitude.is_nan()).then(|| colormap.to_rgb(amplitude)))
+                .collect::<Vec<_>>())
+        })
+        .collect::<Result<Vec<_>, AppError>>()?;
+
+    let mut img: RgbImage =
+        ImageBuffer::from_pixel(width, height, Rgb(background.unwrap_or(DEFAULT_BACKGROUND)));
+    for (x, column) in columns.iter().enumerate() {
+        for (y, rgb) in column.iter().enumerate() {
+            if let Some(rgb) = rgb {
+                img.put_pixel(x as u32, y as u32, Rgb(*rgb));
+            }
+        }
+    }
+
+    Ok(img)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::segy::rendering::colormap::{create_colormap, ColormapContext};
+    use crate::segy::rendering::normalizer::normalize_traces;
+
+    #[test]
+    fn test_fused_render_matches_normalized_columns() {
+        // Uneven lengths and fewer traces than columns leave background gaps.
+        let traces: Vec<TraceData> = (0..5)
+            .map(|trace| {
+                TraceData::IeeeFloat32(
+                    (0..20 + trace * 3)
+                        .map(|i| ((i * 7 + trace * 13) % 17) as f32 - 8.0)
+                        .collect(),
+                )
+            })
+            .collect();
+        let colormap = create_colormap(ColormapType::Seismic, ColormapContext::default());
+        let cancel = RenderCancelToken::none();
+        let scalings = [
+            AmplitudeScaling::Global { max_amplitude: 4.0 },
+            AmplitudeScaling::PerTrace {
+                window_size: Some(5),
+                window_exclude: None,
+            },
+            AmplitudeScaling::Auto,
+            AmplitudeScaling::RmsBalance { reference_rms: 0.3 },
+        ];
+
+        for scaling in &scalings {
+            let expected = rasterize_columns(
+                &normalize_traces(&traces, scaling),
+                8,
+                colormap.as_ref(),
+                Some([1, 2, 3]),
+                &cancel,
+            )
+            .unwrap();
+            let fused = normalize_and_render(
+                &traces,
+                &TraceNormalizer::new(&traces, scaling),
+                8,
+                None,
+                &[],
+                colormap.as_ref(),
+                Some([1, 2, 3]),
+                &cancel,
+            )
+            .unwrap();
+            assert_eq!(fused, expected, "{:?}", scaling);
+        }
+    }
+
+    #[test]
+    fn test_interpolation_blends_between_samples() {
+        // A ramp from 0 to 1 over 10 samples, zoomed to 100 rows.
+        let traces = vec![TraceData::IeeeFloat32(
+            (0..10).map(|i| i as f32 / 9.0).collect(),
+        )];
+        let viewport = ViewportConfig {
+            start_trace: 0,
+            trace_count: 1,
+            width: 1,
+            height: 100,
+            flip_traces: false,
+            background_color: None,
+            start_sample: None,
+            end_sample: None,
+            fit: FitMode::Stretch,
+        };
+        let colormap = create_colormap(ColormapType::Grayscale, ColormapContext::default());
+        let render = |interpolate| {
+            render_variable_density(
+                traces.clone(),
+                &viewport,
+                colormap.as_ref(),
+                &AmplitudeScaling::Manual { scale: 1.0 },
+                None,
+                interpolate,
+                &[],
+                &RenderCancelToken::none(),
+            )
+            .unwrap()
+        };
+
+        let smooth = render(true);
+        assert_eq!(smooth.dimensions(), (1, 100));
+        for y in 0..100 {
+            let expected = colormap.to_rgb(y as f32 / 99.0);
+            let actual = smooth.get_pixel(0, y).0;
+            assert!(
+                (actual[0] as i32 - expected[0] as i32).abs() <= 1,
+                "row {}",
+                y
+            );
+        }
+        let levels = |img: &RgbImage| {
+            let mut levels: Vec<u8> = img.pixels().map(|pixel| pixel[0]).collect();
+            levels.dedup();
+            levels.len()
+        };
+        assert!(levels(&smooth) > 50);
+        assert!(smooth
+            .pixels()
+            .zip(smooth.pixels().skip(1))
+            .all(|(a, b)| a[0] <= b[0]));
+    }
+
+    #[test]
+    fn test_binning_averages_traces_per_column() {
+        // Every tenth trace is a full-scale spike, the rest are silent.
+        let traces: Vec<TraceData> = (0..1000)
+            .map(|trace| TraceData::IeeeFloat32(vec![if trace % 10 == 0 { 1.0 } else { 0.0 }; 6]))
+            .collect();
+        let viewport = ViewportConfig {
+            start_trace: 0,
+            trace_count: 1000,
+            width: 100,
+            height: 6,
+            flip_traces: false,
+            background_color: None,
+            start_sample: None,
+            end_sample: None,
+            fit: FitMode::Stretch,
+        };
+        let colormap = create_colormap(ColormapType::Grayscale, ColormapContext::default());
+        let render = |binning| {
+            render_variable_density(
+                traces.clone(),
+                &viewport,
+                colormap.as_ref(),
+                &AmplitudeScaling::Manual { scale: 1.0 },
+                Some(binning),
+                false,
+                &[],
+                &RenderCancelToken::none(),
+            )
+            .unwrap()
+        };
+
+        let mean = render(TraceBinning::Mean);
+        assert_eq!(mean.dimensions(), (100, 6));
+        let expected = Rgb(colormap.to_rgb(0.1));
+        assert!(mean.pixels().all(|&pixel| pixel == expected));
+
+        let peak = render(TraceBinning::MaxAbs);
+        let expected = Rgb(colormap.to_rgb(1.0));
+        assert!(peak.pixels().all(|&pixel| pixel == expected));
+    }
+
+    #[test]
+    fn test_max_abs_binning_keeps_strongest_sample_of_either_sign() {
+        // One column over a +1 event, a silent trace, and a -1 event.
+        let traces = vec![
+            TraceData::IeeeFloat32(vec![1.0, 0.0]),
+            TraceData::IeeeFloat32(vec![0.0, 0.0]),
+            TraceData::IeeeFloat32(vec![0.0, -1.0]),
+        ];
+        let viewport = ViewportConfig {
+            start_trace: 0,
+            trace_count: 3,
+            width: 1,
+            height: 2,
+            flip_traces: false,
+            background_color: None,
+            start_sample: None,
+            end_sample: None,
+            fit: FitMode::Stretch,
+        };
+        let colormap = create_colormap(ColormapType::Grayscale, ColormapContext::default());
+        let render = |binning| {
+            render_variable_density(
+                traces.clone(),
+                &viewport,
+                colormap.as_ref(),
+                &AmplitudeScaling::Manual { scale: 1.0 },
+                Some(binning),
+                false,
+                &[],
+                &RenderCancelToken::none(),
+            )
+            .unwrap()
+        };
+
+        let peak = render(TraceBinning::MaxAbs);
+        assert_eq!(*peak.get_pixel(0, 0), Rgb(colormap.to_rgb(1.0)));
+        assert_eq!(*peak.get_pixel(0, 1), Rgb(colormap.to_rgb(-1.0)));
+
+        let nearest = render(TraceBinning::Nearest);
+        assert!(nearest
+            .pixels()
+            .all(|&pixel| pixel == Rgb(colormap.to_rgb(0.0))));
+    }
+
+    #[test]
+    #[ignore = "benchmark; see segy::bench"]
+    fn bench_fused_render_peak_memory() {
+        use crate::segy::bench::{mib, peak_bytes};
+
+        let (width, height) = (5000u32, 3000u32);
+        let traces: Vec<TraceData> = (0..width as usize)
+            .map(|trace| {
+                TraceData::IeeeFloat32(
+                    (0..height as usize)
+                        .map(|i| ((i * 7 + trace * 13) % 17) as f32 - 8.0)
+                        .collect(),
+                )
+            })
+            .collect();
+        let viewport = ViewportConfig {
+            start_trace: 0,
+            trace_count: width as usize,
+            width,
+            height,
+            flip_traces: false,
+            background_color: None,
+            start_sample: None,
+            end_sample: None,
+            fit: FitMode::Stretch,
+        };
+        let colormap = create_colormap(ColormapType::Seismic, ColormapContext::default());
+        let scaling = AmplitudeScaling::Global { max_amplitude: 8.0 };
+        let cancel = RenderCancelToken::none();
+
+        let (_, nested) = peak_bytes(|| {
+            let normalized = normalize_traces(&traces, &scaling);
+            rasterize_columns(&normalized, width, colormap.as_ref(), None, &cancel).unwrap()
+        });
+        let input = traces.clone();
+        let (_, fused) = peak_bytes(move || {
+            render_variable_density(
+                input,
+                &viewport,
+                colormap.as_ref(),
+                &scaling,
+                None,
+                false,
+                &[],
+                &cancel,
+            )
+            .unwrap()
+        });
+
+        let image = width as usize * height as usize * 3;
+        println!(
+            "{}x{} VD peak heap: nested {}, fused {} (image {})",
+            width,
+            height,
+            mib(nested),
+            mib(fused),
+            mib(image)
+        );
+        assert!(fused < nested);
+        assert!(fused < image + image / 4);
+    }
 }
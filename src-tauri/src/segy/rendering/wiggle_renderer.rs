@@ -4,7 +4,10 @@
 //! and scanline polygon filling. Wiggle traces show amplitude variations as
 //! oscillations around a central axis, optionally filling positive/negative lobes.
 
+use super::cancel::RenderCancelToken;
 use super::types::*;
+use super::vd_renderer::rasterize_columns;
+use crate::error::AppError;
 use image::{Rgb, RgbImage};
 
 /// Render wiggle traces on a white background.
@@ -15,7 +18,7 @@ use image::{Rgb, RgbImage};
 /// * `normalized` - Normalized trace data in [-1.0, 1.0] range
 ///
 /// # Returns
-/// An RGB image with wiggle traces rendered, or an error message
+/// An RGB image with wiggle traces rendered, or an `AppError::RenderError`
 ///
 /// # Algorithm
 /// - Each trace is centered at `(trace_idx + 0.5) * trace_spacing`
@@ -26,10 +29,11 @@ pub fn render_wiggle(
     viewport: &ViewportConfig,
     wiggle_config: &WiggleConfig,
     normalized: &[Vec<f32>],
-) -> Result<RgbImage, String> {
+) -> Result<RgbImage, AppError> {
     let width = viewport.width;
     let height = viewport.height;
-    let mut img = RgbImage::from_pixel(width, height, Rgb([255, 255, 255])); // White background
+    let background = viewport.background_color.unwrap_or(DEFAULT_BACKGROUND);
+    let mut img = RgbImage::from_pixel(width, height, Rgb(background));
 
     let trace_count = normalized.len();
     if trace_count == 0 || normalized.is_empty() {
@@ -45,8 +49,8 @@ pub fn render_wiggle(
     let trace_spacing = width as f32 / trace_count as f32;
     let sample_spacing = height as f32 / samples_per_trace as f32;
 
-    // Maximum wiggle amplitude in pixels (half of trace spacing)
-    let max_wiggle_width = trace_spacing * 0.4;
+    // Maximum wiggle amplitude in pixels
+    let max_wiggle_width = wiggle_width(wiggle_config, trace_spacing, WIGGLE_WIDTH);
 
     // Render each trace
     for (trace_idx, trace_data) in normalized.iter().enumerate() {
@@ -57,49 +61,220 @@ pub fn render_wiggle(
             let y1 = sample_idx as f32 * sample_spacing;
             let y2 = (sample_idx + 1) as f32 * sample_spacing;
 
-            let amp1 = trace_data[sample_idx];
-            let amp2 = trace_data[sample_idx + 1];
-
-            let x1 = trace_center_x + amp1 * max_wiggle_width;
-            let x2 = trace_center_x + amp2 * max_wiggle_width;
-
-            // Draw line segment
-            draw_line(
+            draw_segment(
                 &mut img,
-                x1,
-                y1,
-                x2,
-                y2,
-                wiggle_config.line_color,
-                wiggle_config.line_width,
+                wiggle_config,
+                trace_center_x,
+                max_wiggle_width,
+                (y1, trace_data[sample_idx]),
+                (y2, trace_data[sample_idx + 1]),
             );
+        }
+    }
+
+    Ok(img)
+}
+
+/// Draw one wiggle segment between two `(y, amplitude)` samples: its line
+/// and the lobes `wiggle_config` asks to fill, or only the peaks beyond the
+/// threshold in variable-area mode.
+fn draw_segment(
+    img: &mut RgbImage,
+    wiggle_config: &WiggleConfig,
+    trace_center_x: f32,
+    max_wiggle_width: f32,
+    (y1, amp1): (f32, f32),
+    (y2, amp2): (f32, f32),
+) {
+    let amp1 = clip_amplitude(wiggle_config, amp1);
+    let amp2 = clip_amplitude(wiggle_config, amp2);
+    // Null (NaN) samples break the trace: no line or fill to them.
+    if amp1.is_nan() || amp2.is_nan() {
+        return;
+    }
+
+    let x1 = trace_center_x + amp1 * max_wiggle_width;
+    let x2 = trace_center_x + amp2 * max_wiggle_width;
+
+    // Variable area: no stroke, only peaks beyond the threshold filled
+    if wiggle_config.variable_area {
+        let threshold = wiggle_config.fill_threshold;
+        fill_lobe(
+            img,
+            trace_center_x + threshold * max_wiggle_width,
+            (y1, x1, amp1 - threshold),
+            (y2, x2, amp2 - threshold),
+            true,
+            wiggle_config.positive_fill_color,
+        );
+        return;
+    }
+
+    draw_line(
+        img,
+        x1,
+        y1,
+        x2,
+        y2,
+        wiggle_config.line_color,
+        wiggle_config.line_width,
+    );
+
+    if wiggle_config.fill_positive {
+        fill_lobe(
+            img,
+            trace_center_x,
+            (y1, x1, amp1),
+            (y2, x2, amp2),
+            true,
+            wiggle_config.positive_fill_color,
+        );
+    }
+    if wiggle_config.fill_negative {
+        fill_lobe(
+            img,
+            trace_center_x,
+            (y1, x1, amp1),
+            (y2, x2, amp2),
+            false,
+            wiggle_config.negative_fill_color,
+        );
+    }
+}
+
+/// Default full wiggle width in trace spacings for plain wiggles.
+const WIGGLE_WIDTH: f32 = 0.4;
+/// Default full wiggle width in trace spacings over variable density.
+const WIGGLE_VD_WIDTH: f32 = 0.3;
+
+/// Full-amplitude deflection in pixels, from `excursion` or `default_width`.
+fn wiggle_width(wiggle_config: &WiggleConfig, trace_spacing: f32, default_width: f32) -> f32 {
+    trace_spacing * wiggle_config.excursion.unwrap_or(default_width)
+}
+
+/// Clamp a normalized amplitude to `±clip`, keeping NaN.
+fn clip_amplitude(wiggle_config: &WiggleConfig, amplitude: f32) -> f32 {
+    if amplitude.is_nan() {
+        return amplitude;
+    }
+    let clip = wiggle_config.clip;
+    amplitude.max(-clip).min(clip)
+}
 
-            // Fill positive/negative areas
-            if wiggle_config.fill_positive && amp1 > 0.0 && amp2 > 0.0 {
-                fill_polygon(
+/// Minimum samples per output row before wiggle rendering switches to an envelope.
+const ENVELOPE_SAMPLES_PER_ROW: usize = 2;
+
+/// Wiggle background when the viewport does not set one.
+pub(super) const DEFAULT_BACKGROUND: [u8; 3] = [255, 255, 255];
+
+/// Check whether a trace is dense enough that plain wiggles would drop peaks.
+pub fn needs_envelope(samples_per_trace: usize, height: u32) -> bool {
+    height > 0 && samples_per_trace > height as usize * ENVELOPE_SAMPLES_PER_ROW
+}
+
+/// Render min/max envelopes on the viewport background (white by default).
+///
+/// Each trace in `normalized` holds alternating min and max values, one pair
+/// per output row (see `TraceData::downsample_minmax`). The min and max edges
+/// are drawn as lines, each row's span is filled in with the line color, and
+/// lobes beyond the trace axis are filled like regular wiggles.
+pub fn render_wiggle_envelope(
+    viewport: &ViewportConfig,
+    wiggle_config: &WiggleConfig,
+    normalized: &[Vec<f32>],
+) -> Result<RgbImage, AppError> {
+    let width = viewport.width;
+    let height = viewport.height;
+    let background = viewport.background_color.unwrap_or(DEFAULT_BACKGROUND);
+    let mut img = RgbImage::from_pixel(width, height, Rgb(background));
+
+    let trace_count = normalized.len();
+    if trace_count == 0 {
+        return Ok(img);
+    }
+
+    let rows = normalized[0].len() / 2;
+    if rows == 0 {
+        return Ok(img);
+    }
+
+    let trace_spacing = width as f32 / trace_count as f32;
+    let row_spacing = height as f32 / rows as f32;
+    let max_wiggle_width = wiggle_width(wiggle_config, trace_spacing, WIGGLE_WIDTH);
+    let variable_area = wiggle_config.variable_area;
+
+    for (trace_idx, envelope) in normalized.iter().enumerate() {
+        let trace_center_x = (trace_idx as f32 + 0.5) * trace_spacing;
+        let fill_axis = if variable_area {
+            trace_center_x + wiggle_config.fill_threshold * max_wiggle_width
+        } else {
+            trace_center_x
+        };
+        let edges: Vec<(f32, f32, f32)> = envelope
+            .chunks_exact(2)
+            .enumerate()
+            .map(|(row, pair)| {
+                (
+                    (row as f32 + 0.5) * row_spacing,
+                    trace_center_x + clip_amplitude(wiggle_config, pair[0]) * max_wiggle_width,
+                    trace_center_x + clip_amplitude(wiggle_config, pair[1]) * max_wiggle_width,
+                )
+            })
+            .collect();
+
+        for window in edges.windows(2) {
+            let (y1, min1, max1) = window[0];
+            let (y2, min2, max2) = window[1];
+            if [min1, max1, min2, max2].iter().any(|x| x.is_nan()) {
+                continue;
+            }
+
+            // Lobes are split at zero crossings as in `render_wiggle`; an
+            // edge's offset from the axis stands in for its amplitude.
+            if wiggle_config.fill_positive || variable_area {
+                fill_lobe(
                     &mut img,
-                    &[
-                        (trace_center_x, y1),
-                        (x1, y1),
-                        (x2, y2),
-                        (trace_center_x, y2),
-                    ],
+                    fill_axis,
+                    (y1, max1, max1 - fill_axis),
+                    (y2, max2, max2 - fill_axis),
+                    true,
                     wiggle_config.positive_fill_color,
                 );
             }
+            if variable_area {
+                continue;
+            }
 
-            if wiggle_config.fill_negative && amp1 < 0.0 && amp2 < 0.0 {
-                fill_polygon(
+            if wiggle_config.fill_negative {
+                fill_lobe(
                     &mut img,
-                    &[
-                        (trace_center_x, y1),
-                        (x1, y1),
-                        (x2, y2),
-                        (trace_center_x, y2),
-                    ],
+                    trace_center_x,
+                    (y1, min1, min1 - trace_center_x),
+                    (y2, min2, min2 - trace_center_x),
+                    false,
                     wiggle_config.negative_fill_color,
                 );
             }
+
+            let color = wiggle_config.line_color;
+            let line_width = wiggle_config.line_width;
+            draw_line(&mut img, min1, y1, min2, y2, color, line_width);
+            draw_line(&mut img, max1, y1, max2, y2, color, line_width);
+        }
+
+        if variable_area {
+            continue;
+        }
+        for &(y, min_x, max_x) in edges.iter().filter(|edge| !edge.1.is_nan()) {
+            draw_line(
+                &mut img,
+                min_x,
+                y,
+                max_x,
+                y,
+                wiggle_config.line_color,
+                wiggle_config.line_width,
+            );
         }
     }
 
@@ -107,14 +282,18 @@ pub fn render_wiggle(
 }
 
 /// Render combined wiggle + variable density.
+///
+/// Wiggles are drawn and filled over the colormap as in `render_wiggle`.
 pub fn render_wiggle_vd(
     viewport: &ViewportConfig,
     colormap: &dyn super::colormap::Colormap,
     wiggle_config: &WiggleConfig,
     normalized: &[Vec<f32>],
-) -> Result<RgbImage, String> {
+    cancel: &RenderCancelToken,
+) -> Result<RgbImage, AppError> {
     // First render VD as base
-    let mut img = render_vd_base(normalized, viewport, colormap)?;
+    let mut img = render_vd_base(normalized, viewport, colormap, cancel)?;
+    cancel.check()?;
 
     // Overlay wiggle traces
     let trace_count = normalized.len();
@@ -129,7 +308,7 @@ pub fn render_wiggle_vd(
 
     let trace_spacing = viewport.width as f32 / trace_count as f32;
     let sample_spacing = viewport.height as f32 / samples_per_trace as f32;
-    let max_wiggle_width = trace_spacing * 0.3;
+    let max_wiggle_width = wiggle_width(wiggle_config, trace_spacing, WIGGLE_VD_WIDTH);
 
     // Render wiggle overlay
     for (trace_idx, trace_data) in normalized.iter().enumerate() {
@@ -139,20 +318,13 @@ pub fn render_wiggle_vd(
             let y1 = sample_idx as f32 * sample_spacing;
             let y2 = (sample_idx + 1) as f32 * sample_spacing;
 
-            let amp1 = trace_data[sample_idx];
-            let amp2 = trace_data[sample_idx + 1];
-
-            let x1 = trace_center_x + amp1 * max_wiggle_width;
-            let x2 = trace_center_x + amp2 * max_wiggle_width;
-
-            draw_line(
+            draw_segment(
                 &mut img,
-                x1,
-                y1,
-                x2,
-                y2,
-                wiggle_config.line_color,
-                wiggle_config.line_width,
+                wiggle_config,
+                trace_center_x,
+                max_wiggle_width,
+                (y1, trace_data[sample_idx]),
+                (y2, trace_data[sample_idx + 1]),
             );
         }
     }
@@ -161,37 +333,22 @@ pub fn render_wiggle_vd(
 }
 
 /// Render variable density base image without encoding.
+///
+/// One row per windowed sample, resized to the viewport height.
 fn render_vd_base(
     normalized: &[Vec<f32>],
     viewport: &ViewportConfig,
     colormap: &dyn super::colormap::Colormap,
-) -> Result<RgbImage, String> {
-    use image::ImageBuffer;
-    use rayon::prelude::*;
-
-    let width = normalized.len() as u32;
-    let height = if !normalized.is_empty() {
-        normalized[0].len() as u32
-    } else {
-        0
-    };
-
-    let mut img: RgbImage = ImageBuffer::new(width, height);
-
-    img.enumerate_pixels_mut()
-        .par_bridge()
-        .for_each(|(x, y, pixel)| {
-            let trace_idx = x as usize;
-            let sample_idx = y as usize;
-
-            if trace_idx < normalized.len() && sample_idx < normalized[trace_idx].len() {
-                let amplitude = normalized[trace_idx][sample_idx];
-                let rgb = colormap.to_rgb(amplitude);
-                *pixel = Rgb(rgb);
-            } else {
-                *pixel = Rgb([0, 0, 0]);
-            }
-        });
+    cancel: &RenderCancelToken,
+) -> Result<RgbImage, AppError> {
+    let img = rasterize_columns(
+        normalized,
+        normalized.len() as u32,
+        colormap,
+        viewport.background_color,
+        cancel,
+    )?;
+    let (width, height) = img.dimensions();
 
     // Scale to output dimensions if needed
     if width != viewport.width || height != viewport.height {
@@ -293,6 +450,70 @@ fn draw_line(img: &mut RgbImage, x0: f32, y0: f32, x1: f32, y1: f32, color: [u8;
     }
 }
 
+/// Average each `factor`×`factor` block of `img` into one pixel.
+///
+/// Trailing rows or columns that do not fill a whole block are dropped.
+pub(super) fn box_downsample(img: &RgbImage, factor: u32) -> RgbImage {
+    let area = factor * factor;
+    RgbImage::from_fn(img.width() / factor, img.height() / factor, |x, y| {
+        let mut sum = [0u32; 3];
+        for dy in 0..factor {
+            for dx in 0..factor {
+                let pixel = img.get_pixel(x * factor + dx, y * factor + dy);
+                for (total, &channel) in sum.iter_mut().zip(pixel.0.iter()) {
+                    *total += u32::from(channel);
+                }
+            }
+        }
+        Rgb(sum.map(|total| ((total + area / 2) / area) as u8))
+    })
+}
+
+/// Fill the part of one wiggle segment between the centerline and the curve
+/// on the positive (or negative) side.
+///
+/// Each endpoint is `(y, x, amplitude)`. A segment that crosses zero is split
+/// at the interpolated crossing and only its lobe on the requested side is
+/// filled, so peak fill runs continuously up to every zero crossing.
+fn fill_lobe(
+    img: &mut RgbImage,
+    center_x: f32,
+    (y1, x1, amp1): (f32, f32, f32),
+    (y2, x2, amp2): (f32, f32, f32),
+    positive: bool,
+    color: [u8; 3],
+) {
+    let on_side = |amp: f32| if positive { amp > 0.0 } else { amp < 0.0 };
+    let off_side = |amp: f32| if positive { amp < 0.0 } else { amp > 0.0 };
+
+    if !on_side(amp1) && !on_side(amp2) {
+        return;
+    }
+    if !off_side(amp1) && !off_side(amp2) {
+        fill_polygon(
+            img,
+            &[(center_x, y1), (x1, y1), (x2, y2), (center_x, y2)],
+            color,
+        );
+        return;
+    }
+
+    let crossing_y = y1 + amp1 / (amp1 - amp2) * (y2 - y1);
+    if on_side(amp1) {
+        fill_polygon(
+            img,
+            &[(center_x, y1), (x1, y1), (center_x, crossing_y)],
+            color,
+        );
+    } else {
+        fill_polygon(
+            img,
+            &[(center_x, crossing_y), (x2, y2), (center_x, y2)],
+            color,
+        );
+    }
+}
+
 /// Fill a polygon using a scanline algorithm.
 ///
 /// # Arguments
@@ -362,3 +583,159 @@ fn fill_polygon(img: &mut RgbImage, points: &[(f32, f32)], color: [u8; 3]) {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::segy::rendering::colormap::{create_colormap, ColormapContext};
+
+    fn viewport(width: u32, height: u32) -> ViewportConfig {
+        ViewportConfig {
+            start_trace: 0,
+            trace_count: 2,
+            width,
+            height,
+            flip_traces: false,
+            background_color: None,
+            start_sample: None,
+            end_sample: None,
+            fit: FitMode::Stretch,
+        }
+    }
+
+    fn config(clip: f32, excursion: Option<f32>) -> WiggleConfig {
+        WiggleConfig {
+            line_width: 1.0,
+            line_color: [0, 0, 0],
+            fill_positive: true,
+            fill_negative: false,
+            positive_fill_color: [0, 0, 0],
+            negative_fill_color: [255, 0, 0],
+            clip,
+            excursion,
+            variable_area: false,
+            fill_threshold: 0.0,
+            supersample: 1,
+        }
+    }
+
+    /// Rightmost non-background column drawn in any row.
+    fn rightmost_ink(img: &RgbImage) -> u32 {
+        img.enumerate_pixels()
+            .filter(|(_, _, pixel)| pixel.0 != DEFAULT_BACKGROUND)
+            .map(|(x, _, _)| x)
+            .max()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_clip_keeps_large_excursions_in_their_trace() {
+        // Two 20 px lanes centered at x = 10 and x = 30; only trace 0 deflects.
+        let normalized = vec![vec![5.0; 8], vec![0.0; 8]];
+        let unclipped = render_wiggle(&viewport(40, 8), &config(f32::MAX, None), &normalized);
+        let clipped = render_wiggle(&viewport(40, 8), &config(1.0, None), &normalized);
+        let inked =
+            |img: &RgbImage, x: u32| (0..8).any(|y| img.get_pixel(x, y).0 != DEFAULT_BACKGROUND);
+
+        let (unclipped, clipped) = (unclipped.unwrap(), clipped.unwrap());
+        assert!(inked(&unclipped, 25));
+        assert!(inked(&clipped, 18));
+        assert!((19..30).all(|x| !inked(&clipped, x)));
+    }
+
+    #[test]
+    fn test_fill_reaches_zero_crossings() {
+        // One 20 px lane centered at x = 10 swinging from +1 to -1 over 20 rows.
+        // Lobes are the triangles (10, 0), (18, 0), (10, 10) and
+        // (10, 10), (2, 20), (10, 20); both touch the zero crossing at y = 10.
+        let mut wiggle = config(1.0, None);
+        wiggle.positive_fill_color = [0, 128, 0];
+        wiggle.negative_fill_color = [128, 0, 0];
+        wiggle.fill_negative = true;
+        let viewport = ViewportConfig {
+            trace_count: 1,
+            ..viewport(20, 40)
+        };
+        let img = render_wiggle(&viewport, &wiggle, &[vec![1.0, -1.0]]).unwrap();
+        let filled = |color: [u8; 3]| img.pixels().filter(|pixel| pixel.0 == color).count();
+
+        assert_eq!(filled([0, 128, 0]), 50);
+        assert_eq!(filled([128, 0, 0]), 42);
+
+        // The same lobes are filled over variable density.
+        let colormap = create_colormap(ColormapType::Grayscale, ColormapContext::default());
+        wiggle.excursion = Some(WIGGLE_WIDTH);
+        let img = render_wiggle_vd(
+            &viewport,
+            colormap.as_ref(),
+            &wiggle,
+            &[vec![1.0, -1.0]],
+            &RenderCancelToken::none(),
+        )
+        .unwrap();
+        let filled = |color: [u8; 3]| img.pixels().filter(|pixel| pixel.0 == color).count();
+        assert_eq!(filled([0, 128, 0]), 50);
+        assert_eq!(filled([128, 0, 0]), 42);
+    }
+
+    #[test]
+    fn test_envelope_fill_reaches_zero_crossings() {
+        // Two 20 px rows whose envelope swings from +1 to -1; row centers are
+        // y = 10 and y = 30, so both lobes meet at y = 20.
+        let mut wiggle = config(1.0, None);
+        wiggle.positive_fill_color = [0, 128, 0];
+        wiggle.negative_fill_color = [128, 0, 0];
+        wiggle.fill_negative = true;
+        let viewport = ViewportConfig {
+            trace_count: 1,
+            ..viewport(20, 40)
+        };
+        let img =
+            render_wiggle_envelope(&viewport, &wiggle, &[vec![1.0, 1.0, -1.0, -1.0]]).unwrap();
+        let rows = |color: [u8; 3]| {
+            img.enumerate_pixels()
+                .filter(move |p| p.2 .0 == color)
+                .map(|(_, y, _)| y)
+        };
+
+        assert!(rows([0, 128, 0]).count() > 0);
+        assert!(rows([0, 128, 0]).all(|y| (10..=20).contains(&y)));
+        assert!(rows([128, 0, 0]).count() > 0);
+        assert!(rows([128, 0, 0]).all(|y| (20..=30).contains(&y)));
+    }
+
+    #[test]
+    fn test_variable_area_fills_peaks_above_threshold_without_lines() {
+        // 20 px lane centered at x = 10 (full deflection 8 px): a flat 0.25
+        // stretch, then a full-scale peak.
+        let mut wiggle = config(1.0, None);
+        wiggle.line_color = [255, 0, 0];
+        wiggle.positive_fill_color = [0, 128, 0];
+        wiggle.variable_area = true;
+        wiggle.fill_threshold = 0.5;
+        let viewport = ViewportConfig {
+            trace_count: 1,
+            ..viewport(20, 40)
+        };
+        let samples = vec![0.25, 0.25, 1.0, 1.0];
+        let img = render_wiggle(&viewport, &wiggle, &[samples]).unwrap();
+        let colored = |color: [u8; 3]| img.enumerate_pixels().filter(move |p| p.2 .0 == color);
+
+        assert_eq!(colored([255, 0, 0]).count(), 0);
+        let (min_x, max_x) =
+            colored([0, 128, 0]).fold((u32::MAX, 0), |(lo, hi), (x, _, _)| (lo.min(x), hi.max(x)));
+        assert_eq!((min_x, max_x), (14, 18));
+        assert!(colored([0, 128, 0]).all(|(_, y, _)| y > 10));
+    }
+
+    #[test]
+    fn test_excursion_widens_wiggles() {
+        let normalized = vec![vec![1.0; 8]];
+        let narrow = render_wiggle(&viewport(40, 8), &config(1.0, None), &normalized).unwrap();
+        let wide = render_wiggle(&viewport(40, 8), &config(1.0, Some(0.45)), &normalized).unwrap();
+
+        // Centered at x = 20: default 0.4 reaches x = 36, 0.45 reaches x = 38.
+        assert_eq!(rightmost_ink(&narrow), 36);
+        assert_eq!(rightmost_ink(&wide), 38);
+    }
+}
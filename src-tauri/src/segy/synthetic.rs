@@ -0,0 +1,166 @@
+//! Synthetic traces and SEG-Y buffers for tests.
+//!
+//! Wavelets are centered in the trace so their full shape fits, and files
+//! are big-endian IEEE float (format 5) Rev 0 layouts that `SegyReader::from_bytes`
+//! parses like any file on disk. Test-only; nothing here is shipped.
+
+use crate::segy::{
+    BinaryHeader, ByteOrder, TraceData, TraceHeader, FILE_HEADER_SIZE, TEXTUAL_HEADER_SIZE,
+    TRACE_HEADER_SIZE,
+};
+use std::f32::consts::PI;
+
+/// Sample interval of traces in `make_synthetic_segy` (4 ms).
+pub const SYNTHETIC_SAMPLE_INTERVAL_US: i16 = 4000;
+
+/// Peak frequency of the Ricker wavelets in `make_synthetic_segy`.
+pub const SYNTHETIC_RICKER_HZ: f32 = 25.0;
+
+/// Zero-phase Ricker wavelet with unit peak at the center sample.
+pub fn ricker_trace(freq_hz: f32, sample_interval_us: i16, num_samples: usize) -> TraceData {
+    let samples = centered_times(sample_interval_us, num_samples)
+        .map(|t| {
+            let arg = (PI * freq_hz * t).powi(2);
+            (1.0 - 2.0 * arg) * (-arg).exp()
+        })
+        .collect();
+    TraceData::IeeeFloat32(samples)
+}
+
+/// Zero-phase Ormsby wavelet with a trapezoidal `[f1, f2, f3, f4]` Hz pass
+/// band, normalized to unit peak at the center sample.
+pub fn ormsby_trace(
+    corners_hz: [f32; 4],
+    sample_interval_us: i16,
+    num_samples: usize,
+) -> TraceData {
+    let [f1, f2, f3, f4] = corners_hz;
+    // pi * f^2 * sinc^2(pi f t), which tends to pi * f^2 at t = 0.
+    let term = |f: f32, t: f32| {
+        let x = PI * f * t;
+        let sinc = if x == 0.0 { 1.0 } else { x.sin() / x };
+        PI * f * f * sinc * sinc
+    };
+    let peak = PI * (f4 + f3 - f2 - f1);
+    let samples = centered_times(sample_interval_us, num_samples)
+        .map(|t| {
+            ((term(f4, t) - term(f3, t)) / (f4 - f3) - (term(f2, t) - term(f1, t)) / (f2 - f1))
+                / peak
+        })
+        .collect();
+    TraceData::IeeeFloat32(samples)
+}
+
+/// Build a SEG-Y file of `traces` Ricker traces with `samples` samples each.
+///
+/// Trace `i` holds the `SYNTHETIC_RICKER_HZ` wavelet scaled by `i + 1`, with
+/// sequence and CDP numbers `i + 1`, so tests can tell traces apart.
+pub fn make_synthetic_segy(traces: usize, samples: u16) -> Vec<u8> {
+    let mut bytes = vec![0x40u8; TEXTUAL_HEADER_SIZE];
+    let mut binary = vec![0u8; BinaryHeader::SIZE];
+    binary[16..18].copy_from_slice(&SYNTHETIC_SAMPLE_INTERVAL_US.to_be_bytes());
+    binary[20..22].copy_from_slice(&samples.to_be_bytes());
+    binary[24..26].copy_from_slice(&5i16.to_be_bytes());
+    bytes.extend_from_slice(&binary);
+
+    let wavelet = ricker_trace(
+        SYNTHETIC_RICKER_HZ,
+        SYNTHETIC_SAMPLE_INTERVAL_US,
+        usize::from(samples),
+    )
+    .to_f32_vec();
+    let trace_size = TRACE_HEADER_SIZE + usize::from(samples) * 4;
+    bytes.reserve(traces * trace_size);
+    for trace in 0..traces {
+        let number = trace as i32 + 1;
+        let mut header = [0u8; TRACE_HEADER_SIZE];
+        header[0..4].copy_from_slice(&number.to_be_bytes());
+        header[4..8].copy_from_slice(&number.to_be_bytes());
+        header[20..24].copy_from_slice(&number.to_be_bytes());
+        header[28..30].copy_from_slice(&1i16.to_be_bytes());
+        header[114..116].copy_from_slice(&samples.to_be_bytes());
+        header[116..118].copy_from_slice(&SYNTHETIC_SAMPLE_INTERVAL_US.to_be_bytes());
+        bytes.extend_from_slice(&header);
+        for &value in &wavelet {
+            bytes.extend_from_slice(&(value * number as f32).to_be_bytes());
+        }
+    }
+    debug_assert_eq!(bytes.len(), FILE_HEADER_SIZE + traces * trace_size);
+    bytes
+}
+
+/// Samples of trace `index` in a file from `make_synthetic_segy(_, samples)`.
+pub fn synthetic_trace_samples(index: usize, samples: u16) -> Vec<f32> {
+    let scale = (index + 1) as f32;
+    ricker_trace(
+        SYNTHETIC_RICKER_HZ,
+        SYNTHETIC_SAMPLE_INTERVAL_US,
+        usize::from(samples),
+    )
+    .to_f32_vec()
+    .into_iter()
+    .map(|value| value * scale)
+    .collect()
+}
+
+/// Parse a big-endian trace header after `edit` sets its bytes.
+///
+/// The bytes start zeroed with trace identification code 1 (seismic data),
+/// which `edit` may overwrite.
+pub fn make_trace_header(edit: impl FnOnce(&mut [u8])) -> TraceHeader {
+    let mut bytes = [0u8; TRACE_HEADER_SIZE];
+    bytes[28..30].copy_from_slice(&1i16.to_be_bytes());
+    edit(&mut bytes);
+    TraceHeader::from_reader(bytes.as_slice(), ByteOrder::BigEndian).unwrap()
+}
+
+/// Times in seconds of `num_samples` samples, zero at the center sample.
+fn centered_times(sample_interval_us: i16, num_samples: usize) -> impl Iterator<Item = f32> {
+    let dt = sample_interval_us as f32 * 1e-6;
+    let center = (num_samples / 2) as f32;
+    (0..num_samples).map(move |i| (i as f32 - center) * dt)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::segy::{analysis, SegyReader};
+
+    #[test]
+    fn test_wavelets_peak_at_center_and_expected_frequency() {
+        for trace in [
+            ricker_trace(30.0, 2000, 256),
+            ormsby_trace([5.0, 10.0, 40.0, 50.0], 2000, 256),
+        ] {
+            let samples = trace.to_f32_vec();
+            assert!((samples[128] - 1.0).abs() < 1e-6);
+            assert!(samples.iter().all(|v| v.abs() <= 1.0 + 1e-6));
+        }
+
+        // A Ricker wavelet's spectrum peaks at its nominal frequency.
+        let spectrum = analysis::amplitude_spectrum(&ricker_trace(30.0, 2000, 256), 2000);
+        let peak = spectrum
+            .magnitude
+            .iter()
+            .enumerate()
+            .max_by(|a, b| a.1.total_cmp(b.1))
+            .map(|(bin, _)| spectrum.freqs_hz[bin])
+            .unwrap();
+        assert!((peak - 30.0).abs() < 2.0, "peak at {} Hz", peak);
+    }
+
+    #[test]
+    fn test_synthetic_segy_parses() {
+        let reader = SegyReader::from_bytes(make_synthetic_segy(5, 100)).unwrap();
+        assert_eq!(reader.config().samples_per_trace, 100);
+        assert_eq!(reader.binary_header().sample_interval_us, 4000);
+
+        let blocks = reader.load_trace_range(0, 5, None, false).unwrap();
+        assert_eq!(blocks.len(), 5);
+        assert_eq!(blocks[4].header.cdp_ensemble_number, 5);
+        let first = blocks[0].data.to_f32_vec();
+        let last = blocks[4].data.to_f32_vec();
+        assert!((first[50] - 1.0).abs() < 1e-6);
+        assert!((last[50] - 5.0).abs() < 1e-6);
+    }
+}
@@ -0,0 +1,184 @@
+//! Bounded LRU cache of decoded trace blocks.
+//!
+//! Interactive panning tends to request overlapping trace windows, so decoded
+//! blocks are kept around keyed by trace index. The cache is bounded by the
+//! total number of samples it holds rather than the number of entries, which
+//! keeps memory use predictable across files with different trace lengths.
+//!
+//! Recency is tracked with generation stamps: every use appends the key with
+//! a fresh stamp to a queue, and stale queue entries are skipped on eviction
+//! and compacted away periodically, so hits cost amortized O(1).
+
+use crate::segy::TraceBlock;
+use std::collections::{HashMap, VecDeque};
+use std::sync::Arc;
+
+/// Default sample budget for a reader's trace cache (~64 MiB of f32 samples).
+pub const DEFAULT_TRACE_CACHE_SAMPLES: usize = 16 * 1024 * 1024;
+
+/// Least-recently-used cache of decoded trace blocks keyed by trace index.
+#[derive(Debug)]
+pub struct TraceCache {
+    sample_budget: usize,
+    cached_samples: usize,
+    entries: HashMap<usize, CacheEntry>,
+    // Uses in order, oldest at the front; an entry is live only while its
+    // stamp matches the key's current generation.
+    order: VecDeque<(usize, u64)>,
+    generation: u64,
+}
+
+#[derive(Debug)]
+struct CacheEntry {
+    block: Arc<TraceBlock>,
+    generation: u64,
+}
+
+impl TraceCache {
+    /// Create an empty cache bounded by `sample_budget` samples.
+    ///
+    /// A budget of zero disables caching entirely.
+    pub fn new(sample_budget: usize) -> Self {
+        Self {
+            sample_budget,
+            cached_samples: 0,
+            entries: HashMap::new(),
+            order: VecDeque::new(),
+            generation: 0,
+        }
+    }
+
+    /// Return the cached block for a trace, marking it as recently used.
+    pub fn get(&mut self, trace_index: usize) -> Option<Arc<TraceBlock>> {
+        let block = self.entries.get(&trace_index)?.block.clone();
+        self.touch(trace_index);
+        Some(block)
+    }
+
+    /// Insert a decoded block, evicting least-recently-used entries as needed.
+    ///
+    /// Blocks larger than the whole budget are not cached.
+    pub fn insert(&mut self, trace_index: usize, block: Arc<TraceBlock>) {
+        let samples = block.data.len();
+        if samples > self.sample_budget {
+            return;
+        }
+
+        let entry = CacheEntry {
+            block,
+            generation: 0,
+        };
+        if let Some(previous) = self.entries.insert(trace_index, entry) {
+            self.cached_samples -= previous.block.data.len();
+        }
+        self.cached_samples += samples;
+        self.touch(trace_index);
+
+        while self.cached_samples > self.sample_budget {
+            let Some((evicted, generation)) = self.order.pop_front() else {
+                break;
+            };
+            if self.is_live(evicted, generation) {
+                if let Some(entry) = self.entries.remove(&evicted) {
+                    self.cached_samples -= entry.block.data.len();
+                }
+            }
+        }
+    }
+
+    /// Drop every cached block.
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        self.order.clear();
+        self.cached_samples = 0;
+    }
+
+    /// Number of cached trace blocks.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Check whether the cache holds no blocks.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Move a key to the most-recently-used position.
+    fn touch(&mut self, trace_index: usize) {
+        let Some(entry) = self.entries.get_mut(&trace_index) else {
+            return;
+        };
+        self.generation += 1;
+        entry.generation = self.generation;
+        self.order.push_back((trace_index, self.generation));
+
+        // Drop stale stamps once they outnumber live ones; the queue has grown
+        // by at least as many uses since the last pass, so this is amortized.
+        if self.order.len() > 2 * self.entries.len() + 16 {
+            let entries = &self.entries;
+            self.order.retain(|&(index, generation)| {
+                entries
+                    .get(&index)
+                    .is_some_and(|entry| entry.generation == generation)
+            });
+        }
+    }
+
+    /// Check whether a queued use is the key's most recent one.
+    fn is_live(&self, trace_index: usize, generation: u64) -> bool {
+        self.entries
+            .get(&trace_index)
+            .is_some_and(|entry| entry.generation == generation)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::segy::synthetic::make_trace_header;
+    use crate::segy::TraceData;
+
+    fn block(samples: usize) -> Arc<TraceBlock> {
+        let header = make_trace_header(|_| {});
+        Arc::new(TraceBlock::new(header, TraceData::Int16(vec![0; samples])))
+    }
+
+    #[test]
+    fn test_evicts_least_recently_used() {
+        let mut cache = TraceCache::new(20);
+        cache.insert(0, block(10));
+        cache.insert(1, block(10));
+        assert!(cache.get(0).is_some());
+
+        cache.insert(2, block(10));
+        assert_eq!(cache.len(), 2);
+        assert!(cache.get(0).is_some());
+        assert!(cache.get(1).is_none());
+        assert!(cache.get(2).is_some());
+    }
+
+    #[test]
+    fn test_repeated_hits_keep_queue_bounded() {
+        let mut cache = TraceCache::new(30);
+        for index in 0..3 {
+            cache.insert(index, block(10));
+        }
+        for _ in 0..1000 {
+            assert!(cache.get(0).is_some());
+            assert!(cache.get(2).is_some());
+        }
+        assert!(cache.order.len() <= 2 * cache.len() + 16);
+
+        // Trace 1 went unused the longest, so it goes first.
+        cache.insert(3, block(10));
+        assert!(cache.get(1).is_none());
+        assert!(cache.get(0).is_some() && cache.get(2).is_some() && cache.get(3).is_some());
+    }
+
+    #[test]
+    fn test_zero_budget_disables_cache() {
+        let mut cache = TraceCache::new(0);
+        cache.insert(0, block(10));
+        assert!(cache.is_empty());
+    }
+}
@@ -0,0 +1,106 @@
+//! Byte storage that trace data is read from.
+//!
+//! Files opened from disk are memory-mapped, while `SegyReader::from_bytes`
+//! keeps the caller's buffer. Some network filesystems refuse to be mapped,
+//! so files can also be read with seek + `read_exact` on every access.
+//! `SegyReader` only goes through `slice`, so trace loading does not care
+//! which source backs it. Long scans can also pass access hints for byte
+//! ranges; only memory maps on Unix act on them.
+
+use crate::error::AppError;
+use std::borrow::Cow;
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
+use std::ops::Range;
+use std::sync::Mutex;
+
+/// Source bytes of a SEG-Y file.
+pub(crate) enum TraceStore {
+    /// Memory-mapped file contents.
+    Mmap(memmap2::Mmap),
+    /// Buffer owned by the reader.
+    Memory(Vec<u8>),
+    /// Open file read on demand, for files that cannot be memory-mapped.
+    File { file: Mutex<File>, len: usize },
+}
+
+impl TraceStore {
+    /// Read from `file` on demand; `len` is the file size at open time.
+    pub(crate) fn file(file: File, len: u64) -> Self {
+        Self::File {
+            file: Mutex::new(file),
+            len: len as usize,
+        }
+    }
+
+    /// Total number of bytes in the store.
+    pub(crate) fn len(&self) -> usize {
+        match self {
+            Self::Mmap(mmap) => mmap.len(),
+            Self::Memory(data) => data.len(),
+            Self::File { len, .. } => *len,
+        }
+    }
+
+    /// Bytes in `range`, borrowed when resident and read from disk otherwise.
+    ///
+    /// Panics if the range is out of bounds; callers check against `len` first.
+    pub(crate) fn slice(&self, range: Range<usize>) -> Result<Cow<'_, [u8]>, AppError> {
+        match self {
+            Self::Mmap(mmap) => Ok(Cow::Borrowed(&mmap[range])),
+            Self::Memory(data) => Ok(Cow::Borrowed(&data[range])),
+            Self::File { file, len } => {
+                assert!(range.end <= *len, "range exceeds file length");
+                let mut buffer = vec![0u8; range.len()];
+                let mut file = file.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+                file.seek(SeekFrom::Start(range.start as u64))
+                    .and_then(|_| file.read_exact(&mut buffer))
+                    .map_err(|e| AppError::IoError {
+                        message: format!(
+                            "Failed to read bytes {}..{}: {}",
+                            range.start, range.end, e
+                        ),
+                    })?;
+                Ok(Cow::Owned(buffer))
+            }
+        }
+    }
+
+    /// Hint that `range` is about to be read front to back, so the OS reads
+    /// ahead of the faults. Failures are logged and otherwise ignored.
+    #[cfg(unix)]
+    pub(crate) fn advise_sequential(&self, range: Range<usize>) {
+        if let Self::Mmap(mmap) = self {
+            let advised = mmap.advise_range(memmap2::Advice::Sequential, range.start, range.len());
+            if let Err(err) = advised {
+                log::debug!("MADV_SEQUENTIAL failed for bytes {:?}: {}", range, err);
+            }
+        }
+    }
+
+    #[cfg(not(unix))]
+    pub(crate) fn advise_sequential(&self, _range: Range<usize>) {}
+
+    /// Hint that `range` will not be read again soon, so its resident pages
+    /// can be dropped. Failures are logged and otherwise ignored.
+    #[cfg(unix)]
+    pub(crate) fn release(&self, range: Range<usize>) {
+        if let Self::Mmap(mmap) = self {
+            // Safety: the map is a read-only view of the file, so dropped
+            // pages are read back from disk on the next access, never lost.
+            let released = unsafe {
+                mmap.unchecked_advise_range(
+                    memmap2::UncheckedAdvice::DontNeed,
+                    range.start,
+                    range.len(),
+                )
+            };
+            if let Err(err) = released {
+                log::debug!("MADV_DONTNEED failed for bytes {:?}: {}", range, err);
+            }
+        }
+    }
+
+    #[cfg(not(unix))]
+    pub(crate) fn release(&self, _range: Range<usize>) {}
+}
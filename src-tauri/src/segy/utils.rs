@@ -14,15 +14,40 @@ pub enum TextEncoding {
 
 /// Detect the encoding of a textual header by analyzing character distribution
 ///
-/// Uses multiple heuristics to determine if data is ASCII or EBCDIC:
-/// 1. Check for EBCDIC space (0x40) which is very common in EBCDIC SEG-Y
-/// 2. Check for ASCII 'C' at line starts (standard in both encodings)
-/// 3. Look for patterns that indicate EBCDIC vs ASCII
+/// Counts how many bytes are printable ASCII when read directly and when
+/// mapped through the EBCDIC table, and picks the interpretation with more
+/// printable characters. Only on a tie does it fall back to the card-image
+/// heuristics in `detect_text_encoding_heuristic`.
 pub fn detect_text_encoding(data: &[u8]) -> TextEncoding {
     if data.is_empty() {
         return TextEncoding::Ebcdic;
     }
 
+    let ascii_printable = data.iter().filter(|&&b| is_printable_ascii(b)).count();
+    let ebcdic_printable = data
+        .iter()
+        .filter(|&&b| is_printable_ascii(EBCDIC_TO_ASCII_TABLE[b as usize]))
+        .count();
+
+    match ascii_printable.cmp(&ebcdic_printable) {
+        std::cmp::Ordering::Greater => TextEncoding::Ascii,
+        std::cmp::Ordering::Less => TextEncoding::Ebcdic,
+        std::cmp::Ordering::Equal => detect_text_encoding_heuristic(data),
+    }
+}
+
+/// Check whether a byte is a printable ASCII character (space through tilde).
+fn is_printable_ascii(byte: u8) -> bool {
+    (0x20..=0x7E).contains(&byte)
+}
+
+/// Detect the encoding from card-image conventions and space bytes
+///
+/// Uses multiple heuristics to determine if data is ASCII or EBCDIC:
+/// 1. Check for EBCDIC space (0x40) which is very common in EBCDIC SEG-Y
+/// 2. Check for ASCII 'C' at line starts (standard in both encodings)
+/// 3. Look for patterns that indicate EBCDIC vs ASCII
+fn detect_text_encoding_heuristic(data: &[u8]) -> TextEncoding {
     // EBCDIC space is 0x40, ASCII space is 0x20
     // EBCDIC 'C' is 0xC3, ASCII 'C' is 0x43
 
@@ -149,21 +174,64 @@ pub fn ebcdic_to_ascii(ebcdic: &[u8]) -> String {
 pub fn text_to_ascii(data: &[u8]) -> String {
     match detect_text_encoding(data) {
         TextEncoding::Ascii => {
-            // Already ASCII, just clean up non-printable characters
+            // Already ASCII; drop carriage returns and clean up non-printable characters
             data.iter()
-                .map(|&byte| {
-                    if byte == b'\n' || (0x20..=0x7E).contains(&byte) {
-                        byte as char
-                    } else {
-                        ' '
-                    }
-                })
+                .filter(|&&byte| byte != b'\r')
+                .map(|&byte| clean_ascii_byte(byte))
                 .collect()
         }
         TextEncoding::Ebcdic => ebcdic_to_ascii(data),
     }
 }
 
+/// Split ASCII text with embedded line terminators into logical lines
+///
+/// Some ASCII-converted files store `\r\n`-terminated lines instead of fixed
+/// 80-byte card images. Each `\n` ends a line, `\r` is dropped, lines longer
+/// than `line_width` wrap, and the result is padded or truncated to
+/// `line_count` lines.
+///
+/// # Returns
+///
+/// `None` when the data contains no `\n`, meaning fixed-width cards apply
+pub fn ascii_lines(data: &[u8], line_count: usize, line_width: usize) -> Option<Vec<String>> {
+    if !data.contains(&b'\n') {
+        return None;
+    }
+
+    let mut lines: Vec<String> = data
+        .split(|&byte| byte == b'\n')
+        .flat_map(|line| {
+            let cleaned: Vec<char> = line
+                .iter()
+                .filter(|&&byte| byte != b'\r')
+                .map(|&byte| clean_ascii_byte(byte))
+                .collect();
+            if cleaned.is_empty() {
+                vec![String::new()]
+            } else {
+                cleaned
+                    .chunks(line_width)
+                    .map(|chunk| chunk.iter().collect())
+                    .collect()
+            }
+        })
+        .take(line_count)
+        .collect();
+    lines.resize(line_count, String::new());
+
+    Some(lines)
+}
+
+/// Map a byte to a printable ASCII character, replacing anything else with a space.
+fn clean_ascii_byte(byte: u8) -> char {
+    if byte == b'\n' || (0x20..=0x7E).contains(&byte) {
+        byte as char
+    } else {
+        ' '
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -183,6 +251,46 @@ mod tests {
         assert_eq!(result, "ABC");
     }
 
+    /// Encode ASCII text as EBCDIC using the inverse of the conversion table.
+    fn to_ebcdic(text: &[u8]) -> Vec<u8> {
+        text.iter()
+            .map(|&ascii| {
+                EBCDIC_TO_ASCII_TABLE
+                    .iter()
+                    .position(|&mapped| mapped == ascii)
+                    .unwrap() as u8
+            })
+            .collect()
+    }
+
+    /// A 3200-byte header of 80-column cards without the leading 'C' convention.
+    fn c_less_header() -> Vec<u8> {
+        (0..40)
+            .flat_map(|card| {
+                format!("{:<80}", format!("SURVEY NORTH FIELD LINE {}", card)).into_bytes()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_detect_c_less_ascii_header() {
+        assert_eq!(detect_text_encoding(&c_less_header()), TextEncoding::Ascii);
+    }
+
+    #[test]
+    fn test_detect_c_less_ebcdic_header() {
+        let ebcdic = to_ebcdic(&c_less_header());
+        assert_eq!(detect_text_encoding(&ebcdic), TextEncoding::Ebcdic);
+    }
+
+    #[test]
+    fn test_ascii_lines_handles_crlf() {
+        let data = b"C 1 CLIENT: ACME\r\nC 2 LINE: 42\r\n\0\0";
+        let lines = ascii_lines(data, 4, 80).unwrap();
+        assert_eq!(lines, vec!["C 1 CLIENT: ACME", "C 2 LINE: 42", "  ", ""]);
+        assert!(ascii_lines(b"C 1 no terminators", 40, 80).is_none());
+    }
+
     #[test]
     fn test_ebcdic_to_ascii_digits() {
         // EBCDIC 0xF0-0xF9 = 0-9
@@ -0,0 +1,283 @@
+//! Structural validation of SEG-Y files against their header specification.
+//!
+//! Validation works on the raw header bytes rather than the parsed
+//! `BinaryHeader` so that malformed files (unknown format codes, zero sample
+//! counts) still produce a list of findings instead of a single parse error.
+
+use crate::error::AppError;
+use crate::segy::io;
+use crate::segy::parser::binary_header::detect_byte_order;
+use crate::segy::trace_store::TraceStore;
+use crate::segy::{
+    constants, BinaryHeader, ByteOrder, DataSampleFormat, SegyFormatSpec, TEXTUAL_HEADER_SIZE,
+};
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::io::Read;
+
+/// 1-based byte position of the first binary header byte in spec coordinates.
+const BINARY_HEADER_BASE: usize = TEXTUAL_HEADER_SIZE + 1;
+
+/// Severity of a validation finding.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ValidationSeverity {
+    /// File is readable but deviates from the spec.
+    Warning,
+    /// File cannot be read correctly as declared.
+    Error,
+}
+
+/// A single spec deviation found while validating a file.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ValidationFinding {
+    /// Spec field key the finding refers to, or a structural key like `trace_data`.
+    pub field_key: String,
+    /// Whether the finding is a warning or an error.
+    pub severity: ValidationSeverity,
+    /// Human-readable description of the problem.
+    pub message: String,
+}
+
+impl ValidationFinding {
+    fn new(field_key: &str, severity: ValidationSeverity, message: String) -> Self {
+        Self {
+            field_key: field_key.to_string(),
+            severity,
+            message,
+        }
+    }
+}
+
+/// Disagreement between a binary header value and a trace header value.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ConsistencyWarning {
+    /// Zero-based index of the trace whose header disagrees.
+    pub trace_index: usize,
+    /// Trace header field key (`num_samples` or `sample_interval_us`).
+    pub field_key: String,
+    /// Value declared in the binary header.
+    pub binary_value: i64,
+    /// Value found in the trace header.
+    pub trace_value: i64,
+    /// Human-readable description of the mismatch.
+    pub message: String,
+}
+
+/// Validate a SEG-Y file against the spec for its declared revision.
+///
+/// Returns an empty list for a well-formed file. IO failures are returned as
+/// errors; everything else is reported as a finding.
+pub fn validate_file(file_path: &str) -> Result<Vec<ValidationFinding>, AppError> {
+    let mut file = File::open(file_path).map_err(|e| AppError::IoError {
+        message: format!("Failed to open file '{}': {}", file_path, e),
+    })?;
+    let file_size = file.metadata()?.len();
+
+    if file_size < constants::FILE_HEADER_SIZE as u64 {
+        return Ok(vec![ValidationFinding::new(
+            "file_size",
+            ValidationSeverity::Error,
+            format!(
+                "File is {} bytes, smaller than the {}-byte file header",
+                file_size,
+                constants::FILE_HEADER_SIZE
+            ),
+        )]);
+    }
+
+    let mut headers = vec![0u8; constants::FILE_HEADER_SIZE];
+    file.read_exact(&mut headers)?;
+    let binary = &headers[TEXTUAL_HEADER_SIZE..];
+
+    // Trace data starts after any extended textual headers, and only
+    // fixed-length traces can be checked against the file size. A binary
+    // header too malformed to parse is assumed fixed-length from byte 3600.
+    let data_start = match BinaryHeader::from_reader(binary) {
+        Ok(binary_header) if !binary_header.has_fixed_length_traces() => None,
+        Ok(binary_header) => {
+            let store = TraceStore::file(file, file_size);
+            Some(io::first_trace_offset(&store, &binary_header)? as u64)
+        }
+        Err(_) => Some(constants::FILE_HEADER_SIZE as u64),
+    };
+
+    Ok(validate_headers(binary, file_size, data_start))
+}
+
+/// Validate raw binary header bytes for a file of the given size.
+///
+/// `data_start` is the byte offset of the first trace, or `None` for
+/// variable-length traces, whose blocks cannot be checked against the file
+/// size.
+fn validate_headers(
+    binary: &[u8],
+    file_size: u64,
+    data_start: Option<u64>,
+) -> Vec<ValidationFinding> {
+    let byte_order = detect_byte_order(binary);
+    // SEG-Y revision number lives at bytes 3501-3502.
+    let revision_bytes = [binary[300], binary[301]];
+    let revision = match byte_order {
+        ByteOrder::BigEndian => u16::from_be_bytes(revision_bytes),
+        ByteOrder::LittleEndian => u16::from_le_bytes(revision_bytes),
+    };
+
+    let mut findings = Vec::new();
+    let spec = match SegyFormatSpec::load_for_revision(revision) {
+        Ok(spec) => spec,
+        Err(message) => {
+            findings.push(ValidationFinding::new(
+                "segy_revision",
+                ValidationSeverity::Error,
+                message,
+            ));
+            return findings;
+        }
+    };
+
+    let mut samples_per_trace = None;
+    let mut format = None;
+    let mut extended_headers = 0u64;
+
+    for field in &spec.binary_header.fields {
+        let Some(value) = field.read_value(binary, BINARY_HEADER_BASE, byte_order) else {
+            continue;
+        };
+
+        match field.field_key.as_str() {
+            "samples_per_trace" => samples_per_trace = Some(value),
+            "max_additional_trace_headers" => extended_headers = value.max(0) as u64,
+            "data_sample_format" => {
+                let parsed = i16::try_from(value)
+                    .ok()
+                    .and_then(|code| DataSampleFormat::from_code(code).ok());
+                match parsed {
+                    Some(parsed) => format = Some(parsed),
+                    None => findings.push(ValidationFinding::new(
+                        &field.field_key,
+                        ValidationSeverity::Error,
+                        format!("Unsupported data sample format code: {}", value),
+                    )),
+                }
+                continue;
+            }
+            _ => {}
+        }
+
+        if field.required && value == 0 {
+            findings.push(ValidationFinding::new(
+                &field.field_key,
+                ValidationSeverity::Warning,
+                format!("Required field '{}' is not set", field.name),
+            ));
+            continue;
+        }
+
+        if field.code_mapping.is_some() && field.label_for_code(value).is_none() {
+            findings.push(ValidationFinding::new(
+                &field.field_key,
+                ValidationSeverity::Warning,
+                format!("'{}' has unknown code {}", field.name, value),
+            ));
+        }
+    }
+
+    if let (Some(samples), Some(format), Some(data_start)) = (samples_per_trace, format, data_start)
+    {
+        if samples > 0 {
+            let block_size = constants::TRACE_HEADER_SIZE as u64 * (1 + extended_headers)
+                + samples as u64 * format.bytes_per_sample() as u64;
+            let data_size = file_size.saturating_sub(data_start);
+            let remainder = data_size % block_size;
+            if remainder != 0 {
+                findings.push(ValidationFinding::new(
+                    "trace_data",
+                    ValidationSeverity::Warning,
+                    format!(
+                        "Data region is not a whole number of {}-byte traces ({} trailing bytes)",
+                        block_size, remainder
+                    ),
+                ));
+            }
+        }
+    }
+
+    findings
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::segy::synthetic::make_synthetic_segy;
+
+    fn binary_header(samples: u16, format: i16) -> Vec<u8> {
+        let mut bytes = vec![0u8; 400];
+        bytes[16..18].copy_from_slice(&4000i16.to_be_bytes());
+        bytes[20..22].copy_from_slice(&samples.to_be_bytes());
+        bytes[24..26].copy_from_slice(&format.to_be_bytes());
+        bytes
+    }
+
+    #[test]
+    fn test_trailing_partial_trace_warns() {
+        let binary = binary_header(100, 1);
+        let block = 240 + 100 * 4;
+        let file_size = (constants::FILE_HEADER_SIZE + block * 3 + 17) as u64;
+
+        let findings =
+            validate_headers(&binary, file_size, Some(constants::FILE_HEADER_SIZE as u64));
+        let partial = findings
+            .iter()
+            .find(|f| f.field_key == "trace_data")
+            .expect("expected trailing trace finding");
+        assert_eq!(partial.severity, ValidationSeverity::Warning);
+    }
+
+    #[test]
+    fn test_trailing_check_follows_trace_layout() {
+        let trailing = |bytes: &[u8], name: &str| {
+            let path = std::env::temp_dir().join(name);
+            std::fs::write(&path, bytes).unwrap();
+            let findings = validate_file(path.to_str().unwrap()).unwrap();
+            std::fs::remove_file(&path).ok();
+            findings.iter().any(|f| f.field_key == "trace_data")
+        };
+        let binary = TEXTUAL_HEADER_SIZE;
+        let mut rev1 = make_synthetic_segy(3, 10);
+        rev1[binary + 300..binary + 302].copy_from_slice(&0x0100u16.to_be_bytes());
+        rev1[binary + 302..binary + 304].copy_from_slice(&1i16.to_be_bytes());
+
+        // One extended textual header shifts the traces by 3200 bytes.
+        let mut extended = rev1.clone();
+        extended[binary + 304..binary + 306].copy_from_slice(&1i16.to_be_bytes());
+        let stanza = vec![0x40u8; TEXTUAL_HEADER_SIZE];
+        extended.splice(
+            constants::FILE_HEADER_SIZE..constants::FILE_HEADER_SIZE,
+            stanza,
+        );
+        assert!(!trailing(&extended, "tracelens_validate_extended_test.sgy"));
+
+        // Variable-length traces are not multiples of the binary header length.
+        let mut variable = rev1.clone();
+        variable[binary + 302..binary + 304].copy_from_slice(&0i16.to_be_bytes());
+        variable.extend_from_slice(&make_synthetic_segy(1, 3)[constants::FILE_HEADER_SIZE..]);
+        assert!(!trailing(&variable, "tracelens_validate_variable_test.sgy"));
+
+        rev1.extend_from_slice(&[0; 17]);
+        assert!(trailing(&rev1, "tracelens_validate_fixed_test.sgy"));
+    }
+
+    #[test]
+    fn test_unsupported_format_is_error() {
+        let binary = binary_header(100, 7);
+        let findings = validate_headers(
+            &binary,
+            constants::FILE_HEADER_SIZE as u64,
+            Some(constants::FILE_HEADER_SIZE as u64),
+        );
+        assert!(findings.iter().any(
+            |f| f.field_key == "data_sample_format" && f.severity == ValidationSeverity::Error
+        ));
+    }
+}
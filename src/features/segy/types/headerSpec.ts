@@ -9,3 +9,21 @@ export interface HeaderFieldSpec {
   data_type: string;
   description?: string;
 }
+
+/**
+ * Header field decoded from one trace, with its spec and resolved code label.
+ */
+export interface DecodedField {
+  spec: HeaderFieldSpec;
+  value: number | null; // null when the field's data type is not supported
+  label: string | null;
+}
+
+/**
+ * Trace header field whose value differs between two traces.
+ */
+export interface FieldDiff {
+  field_key: string;
+  value_a: number;
+  value_b: number;
+}
@@ -15,6 +15,22 @@ export interface TextualHeader {
 export type BinaryHeader = Record<string, unknown>;
 export type TraceHeader = Record<string, unknown>;
 
+export type TimeBasis = 'Local' | 'Gmt' | 'Other' | 'Utc' | 'Gps' | 'Unknown';
+
+/**
+ * Trace recording time, found under `recorded_at` in trace headers when set.
+ */
+export interface RecordingTime {
+  year: number;
+  month: number;
+  day: number;
+  day_of_year: number;
+  hour: number;
+  minute: number;
+  second: number;
+  time_basis: TimeBasis;
+}
+
 /**
  * Aggregate SEG-Y metadata loaded from the backend.
  */
@@ -22,9 +38,131 @@ export interface SegyData {
   textual_header: TextualHeader;
   binary_header: BinaryHeader;
   total_traces: number | null;
+  trailing_bytes: number;
   file_size: number;
   text_encoding: TextEncoding;
   byte_order: ByteOrder;
+  samples_per_trace_inferred: boolean;
+}
+
+/**
+ * Trace geometry used to read a file; overrides the binary header when passed to `loadSegyFile`.
+ */
+export interface SegyFileConfig {
+  samplesPerTrace: number;
+  dataSampleFormat: number; // SEG-Y format code, e.g. 1 = IBM float, 5 = IEEE float
+  byteOrder: ByteOrder;
+  samplesPerTraceInferred?: boolean;
+  fixedLengthTraces?: boolean; // defaults to true when omitted
+  dataOffset?: number; // byte position of the first trace; detected on open
+  extendedTraceHeaders?: number; // Rev 2 extra 240-byte header blocks per trace; detected on open
+}
+
+/**
+ * Original textual header bytes with the encoding detected for them.
+ */
+export interface RawTextualHeader {
+  bytes: number[];
+  encoding: TextEncoding;
+}
+
+/**
+ * Original binary header bytes with the byte order detected for them.
+ */
+export interface RawBinaryHeader {
+  bytes: number[];
+  byte_order: ByteOrder;
+}
+
+/**
+ * Trace samples as a flat row-major buffer; short traces are padded with `null` (NaN).
+ */
+export interface SamplesPayload {
+  trace_count: number;
+  samples_per_trace: number;
+  data: (number | null)[];
+}
+
+/**
+ * Single-sided amplitude spectrum from 0 Hz up to Nyquist.
+ */
+export interface SpectrumResult {
+  freqs_hz: number[];
+  magnitude: number[];
+}
+
+/**
+ * Equal-width amplitude histogram; `edges` has one more entry than `counts`.
+ */
+export interface HistogramResult {
+  edges: number[];
+  counts: number[];
+}
+
+/**
+ * Distance between consecutive traces, in survey units (meters for arcsecond coordinates).
+ */
+export interface SpacingStats {
+  median: number;
+  mean: number;
+  min: number;
+  max: number;
+  coordinate_units: 'Unknown' | 'Length' | 'SecondsOfArc'; // geographic vs projected coordinates
+}
+
+/**
+ * Stored source-to-group distance of a trace next to the one computed from its coordinates.
+ */
+export interface TraceOffset {
+  stored: number;
+  computed: number | null; // null when all coordinates are zero
+}
+
+/**
+ * File overview for the info sidebar, with codes resolved to display names.
+ */
+export interface FileSummary {
+  revision: string; // spec name, e.g. "SEG-Y Rev 1 (2002)"
+  byte_order: ByteOrder;
+  sample_format: string;
+  samples_per_trace: number;
+  sample_interval_ms: number;
+  total_traces: number | null;
+  trailing_bytes: number;
+  sort: string;
+  measurement_system: string;
+}
+
+/**
+ * Sample format suggested by decoding a few traces as both IBM and IEEE float.
+ *
+ * `suggested` equals `declared` when no override is needed or the data can't tell.
+ */
+export interface SampleFormatSuggestion {
+  declared: number;
+  suggested: number;
+  ibm_implausible: number;
+  ieee_implausible: number;
+  samples_checked: number;
+}
+
+/**
+ * Progress of a long trace scan, emitted as `segy://scan-progress` events.
+ */
+export interface ScanProgress {
+  processed: number;
+  total: number;
+}
+
+/**
+ * Mismatch between a binary header value and one trace header's value.
+ */
+export interface ConsistencyWarning {
+  trace_index: number;
+  field_key: 'num_samples' | 'sample_interval_us';
+  binary_value: number;
+  trace_value: number;
+  message: string;
 }
 
 /**
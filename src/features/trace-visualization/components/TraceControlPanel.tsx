@@ -80,10 +80,16 @@ export const TraceControlPanel = () => {
         return 'Per-Trace AGC';
       case 'percentile':
         return `Percentile (${(amplitudeScaling.percentile * 100).toFixed(0)}%)`;
+      case 'global-percentile':
+        return `Global Percentile (${(amplitudeScaling.percentile * 100).toFixed(0)}%)`;
       case 'manual':
         return `Manual (${amplitudeScaling.scale}x)`;
       case 'global':
         return 'Global';
+      case 'rms-balance':
+        return 'RMS Balance';
+      case 'auto':
+        return 'Auto';
       default:
         return 'Unknown';
     }
@@ -41,11 +41,13 @@ export function useTraceLoader() {
       // Use viewport dimensions directly - don't adjust for zoom to avoid infinite loops
       const rendered = await renderVariableDensity({
         filePath,
-        viewport,
-        colormapType: colormap,
-        scaling: amplitudeScaling,
-        renderMode,
-        wiggleConfig,
+        renderConfig: {
+          viewport,
+          colormapType: colormap,
+          scaling: amplitudeScaling,
+          renderMode,
+          wiggleConfig,
+        },
       });
 
       // Convert to displayable format
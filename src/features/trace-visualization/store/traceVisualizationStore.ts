@@ -65,6 +65,7 @@ const DEFAULT_WIGGLE_CONFIG: WiggleConfig = {
   fillNegative: false,
   positiveFillColor: [0, 0, 0],
   negativeFillColor: [255, 0, 0],
+  clip: 1.0,
 };
 
 /**
@@ -10,8 +10,19 @@ export interface ViewportConfig {
   traceCount: number;
   width: number;
   height: number;
+  flipTraces?: boolean; // display traces right-to-left
+  backgroundColor?: [number, number, number]; // RGB; white for wiggles, black for VD by default
+  startSample?: number; // first sample to render (0-based)
+  endSample?: number; // end of the sample window (exclusive)
+  fit?: FitMode; // variable density only; defaults to 'stretch'
 }
 
+/**
+ * How a variable-density render with fewer traces than pixels fills the width:
+ * stretch the traces, or draw one column per trace and pad with background.
+ */
+export type FitMode = 'stretch' | 'pad';
+
 /**
  * Supported colormaps for variable-density rendering.
  */
@@ -22,20 +33,55 @@ export type ColormapType = 'seismic' | 'grayscale' | 'grayscale-inverted' | 'vir
  */
 export type RenderMode = 'variable-density' | 'wiggle' | 'wiggle-variable-density';
 
+/**
+ * Trace attribute rendered instead of raw amplitude; `envelope` is never negative.
+ */
+export type TraceAttribute = 'amplitude' | 'envelope';
+
+/**
+ * How adjacent traces sharing an output column are combined when a
+ * variable-density render has more traces than pixels.
+ */
+export type TraceBinning = 'mean' | 'max-abs' | 'nearest';
+
+/**
+ * How columns for traces missing from the CDP numbering are drawn.
+ */
+export type MissingTraceFill = 'blank' | 'interpolate';
+
+/**
+ * Zero-phase band-pass corners in Hz, applied per trace before normalization.
+ */
+export interface BandpassConfig {
+  lowHz: number;
+  highHz: number;
+}
+
 /**
  * Amplitude scaling strategies used prior to rendering.
+ *
+ * `windowExclude` is a `[start, end)` sample range per-trace AGC ignores when measuring gain,
+ * counted from the trace start even when the viewport shows a sample window.
  */
 export type AmplitudeScaling =
   | { type: 'global'; maxAmplitude: number }
-  | { type: 'per-trace'; windowSize?: number }
+  | { type: 'per-trace'; windowSize?: number; windowExclude?: [number, number] }
   | { type: 'percentile'; percentile: number }
-  | { type: 'manual'; scale: number };
+  | { type: 'global-percentile'; percentile: number; cachedClip?: number }
+  | { type: 'manual'; scale: number }
+  | { type: 'rms-balance'; referenceRms: number }
+  | { type: 'auto' };
 
 /**
  * Backend image encoding format.
  */
 export type ImageFormat = 'png';
 
+/**
+ * PNG compression level: `fast` for interactive renders, `best` for exports.
+ */
+export type PngCompression = 'default' | 'fast' | 'best';
+
 /**
  * Rendered image payload returned from the backend.
  */
@@ -44,6 +90,9 @@ export interface RenderedImage {
   height: number;
   data: number[]; // u8 array
   format: ImageFormat;
+  applied_scale: number | null; // amplitude at full color; pass back as cachedClip for global-percentile
+  scaling_mode: AmplitudeScaling['type'];
+  data_trace_count?: number | null; // traces drawn when aux or excluded-id traces were skipped
 }
 
 /**
@@ -56,4 +105,41 @@ export interface WiggleConfig {
   fillNegative: boolean;
   positiveFillColor: [number, number, number]; // RGB
   negativeFillColor: [number, number, number]; // RGB
+  clip?: number; // clamp normalized amplitudes to ±clip (default 1, not negative)
+  excursion?: number; // full wiggle width in trace spacings (0-20); > 0.5 overlaps neighbours
+  variableArea?: boolean; // fill peaks only, without the line stroke
+  fillThreshold?: number; // fraction of full scale a peak must exceed in variable-area mode
+  supersample?: number; // render at this factor and box-filter down for anti-aliased export (1-8)
+}
+
+/**
+ * Blank gap inserted between traces wherever a trace header field changes.
+ */
+export interface GatherGapConfig {
+  headerField: string;
+  width: number;
+  color?: [number, number, number]; // RGB, defaults to white
+}
+
+/**
+ * Complete render settings, used for interactive renders and exports to disk.
+ */
+export interface RenderConfig {
+  viewport: ViewportConfig;
+  colormapType: ColormapType;
+  scaling: AmplitudeScaling;
+  renderMode: RenderMode;
+  wiggleConfig: WiggleConfig | null;
+  gatherGap?: GatherGapConfig | null;
+  attribute?: TraceAttribute;
+  filter?: BandpassConfig | null;
+  hideDeadTraces?: boolean;
+  traceBinning?: TraceBinning;
+  excludedTraceIds?: number[]; // trace identification codes to leave out, e.g. [3, 7]
+  interpolateSamples?: boolean; // blend between samples when zoomed in (variable density)
+  alignDelays?: boolean; // shift traces by delay recording time (variable density)
+  nullValue?: number; // sample value marking missing data, e.g. -999.25; left blank
+  missingTraces?: MissingTraceFill; // add columns for CDP numbers the line skips
+  colormapCenter?: number; // normalized amplitude shown with the midpoint color; 0 = unchanged
+  phaseRotationDeg?: number; // constant phase rotation of every trace, e.g. 90
 }
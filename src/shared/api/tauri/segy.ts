@@ -1,17 +1,47 @@
 /**
  * Tauri command wrappers for SEG-Y parsing and rendering services.
  */
-import type { HeaderFieldSpec } from '@/features/segy/types/headerSpec';
-import type { SegyData } from '@/features/segy/types/segy';
+import type {
+  DecodedField,
+  FieldDiff,
+  HeaderFieldSpec,
+} from '@/features/segy/types/headerSpec';
+import type {
+  ConsistencyWarning,
+  FileSummary,
+  HistogramResult,
+  RawBinaryHeader,
+  RawTextualHeader,
+  SampleFormatSuggestion,
+  SamplesPayload,
+  ScanProgress,
+  SegyData,
+  SegyFileConfig,
+  SpacingStats,
+  SpectrumResult,
+  TraceHeader,
+  TraceOffset,
+} from '@/features/segy/types/segy';
 import type {
   AmplitudeScaling,
   ColormapType,
+  PngCompression,
+  RenderConfig,
   RenderedImage,
-  RenderMode,
   ViewportConfig,
   WiggleConfig,
 } from '@/features/trace-visualization/types/rendering';
 import { invoke } from '@tauri-apps/api/core';
+import { listen, type UnlistenFn } from '@tauri-apps/api/event';
+
+/**
+ * Subscribe to progress of long scans (header ranges and exports).
+ */
+export async function onScanProgress(
+  handler: (progress: ScanProgress) => void
+): Promise<UnlistenFn> {
+  return listen<ScanProgress>('segy://scan-progress', event => handler(event.payload));
+}
 
 /**
  * Header-only payload for an individual trace.
@@ -22,9 +52,69 @@ export interface SingleTrace {
 
 /**
  * Load SEG-Y metadata and headers from disk.
+ *
+ * Pass `overrideConfig` to read traces from a file whose binary header
+ * declares the wrong geometry.
+ */
+export async function loadSegyFile(
+  filePath: string,
+  overrideConfig?: SegyFileConfig
+): Promise<SegyData> {
+  return invoke<SegyData>('load_segy_file', {
+    filePath,
+    overrideConfig: overrideConfig ?? null,
+  });
+}
+
+/**
+ * Close a file and release its memory map so it can be deleted or replaced.
+ *
+ * Safe to call for files that are not open. Up to four recently used files
+ * stay open otherwise.
+ */
+export async function closeSegyFile(filePath: string): Promise<void> {
+  return invoke<void>('close_segy_file', { filePath });
+}
+
+/**
+ * Count traces from the file headers alone, without caching a reader.
+ *
+ * Resolves to `null` when the trace block size can't be determined.
+ */
+export async function getTraceCount(filePath: string): Promise<number | null> {
+  return invoke<number | null>('get_trace_count', { filePath });
+}
+
+/**
+ * Summarize a file's format, revision, geometry, and sorting in one call.
+ */
+export async function getFileSummary(filePath: string): Promise<FileSummary> {
+  return invoke<FileSummary>('get_file_summary', { filePath });
+}
+
+/**
+ * Fetch the untouched 3200-byte textual header and its detected encoding.
+ */
+export async function getRawTextualHeader(filePath: string): Promise<RawTextualHeader> {
+  return invoke<RawTextualHeader>('get_raw_textual_header', { filePath });
+}
+
+/**
+ * Fetch the untouched 400-byte binary header and its detected byte order.
  */
-export async function loadSegyFile(filePath: string): Promise<SegyData> {
-  return invoke<SegyData>('load_segy_file', { filePath });
+export async function getBinaryHeaderRaw(filePath: string): Promise<RawBinaryHeader> {
+  return invoke<RawBinaryHeader>('get_binary_header_raw', { filePath });
+}
+
+/**
+ * Read up to 64 KiB of raw bytes at `offset` for hex inspection.
+ */
+export async function readBytes(
+  filePath: string,
+  offset: number,
+  length: number
+): Promise<number[]> {
+  return invoke<number[]>('read_bytes', { filePath, offset, length });
 }
 
 /**
@@ -43,36 +133,411 @@ export async function loadSingleTrace(params: {
 }
 
 /**
- * Fetch backend spec for binary header fields.
+ * Load trace samples for a range as a flat row-major f32 buffer.
+ */
+export async function loadTraceSamplesF32(params: {
+  filePath: string;
+  startIndex: number;
+  count: number;
+  maxSamples?: number;
+}): Promise<SamplesPayload> {
+  return invoke<SamplesPayload>('load_trace_samples_f32', {
+    filePath: params.filePath,
+    startIndex: params.startIndex,
+    count: params.count,
+    maxSamples: params.maxSamples ?? null,
+  });
+}
+
+/**
+ * Load one trace's samples as f32 for the trace inspector plot.
+ */
+export async function getTraceSamples(params: {
+  filePath: string;
+  traceIndex: number;
+  maxSamples?: number;
+  applyWeighting?: boolean;
+}): Promise<number[]> {
+  return invoke<number[]>('get_trace_samples', {
+    filePath: params.filePath,
+    traceIndex: params.traceIndex,
+    maxSamples: params.maxSamples ?? null,
+    applyWeighting: params.applyWeighting ?? null,
+  });
+}
+
+/**
+ * Get `[min, max]` of an integer trace header field across every trace.
+ */
+export async function headerFieldRange(params: {
+  filePath: string;
+  fieldKey: string;
+}): Promise<[number, number]> {
+  return invoke<[number, number]>('header_field_range', {
+    filePath: params.filePath,
+    fieldKey: params.fieldKey,
+  });
+}
+
+/**
+ * Extract one integer trace header field for a range of traces, in trace order.
+ */
+export async function extractHeaderField(params: {
+  filePath: string;
+  fieldKey: string;
+  startIndex: number;
+  count: number;
+}): Promise<number[]> {
+  return invoke<number[]>('extract_header_field', {
+    filePath: params.filePath,
+    fieldKey: params.fieldKey,
+    startIndex: params.startIndex,
+    count: params.count,
+  });
+}
+
+/**
+ * Find the first trace whose integer header field equals `target`, or null.
+ * Pass `sorted` for fields monotonic across the file to binary search. Unsorted
+ * searches emit scan progress (see `onScanProgress`).
+ */
+export async function findTraceByField(params: {
+  filePath: string;
+  fieldKey: string;
+  target: number;
+  sorted?: boolean;
+}): Promise<number | null> {
+  return invoke<number | null>('find_trace_by_field', {
+    filePath: params.filePath,
+    fieldKey: params.fieldKey,
+    target: params.target,
+    sorted: params.sorted ?? null,
+  });
+}
+
+/**
+ * Write integer trace header fields for a range of traces to a CSV file.
+ */
+export async function exportHeadersCsv(params: {
+  filePath: string;
+  outputPath: string;
+  fieldKeys: string[];
+  start: number;
+  count: number;
+}): Promise<void> {
+  return invoke<void>('export_headers_csv', {
+    filePath: params.filePath,
+    outputPath: params.outputPath,
+    fieldKeys: params.fieldKeys,
+    start: params.start,
+    count: params.count,
+  });
+}
+
+/**
+ * Write trace samples to a NumPy `.npy` file as a float32 (traces × samples) array.
+ */
+export async function exportSamplesNpy(params: {
+  filePath: string;
+  outputPath: string;
+  start: number;
+  count: number;
+}): Promise<void> {
+  return invoke<void>('export_samples_npy', {
+    filePath: params.filePath,
+    outputPath: params.outputPath,
+    start: params.start,
+    count: params.count,
+  });
+}
+
+/**
+ * List the integer trace header fields that differ between two traces.
+ */
+export async function diffTraceHeaders(params: {
+  filePath: string;
+  indexA: number;
+  indexB: number;
+}): Promise<FieldDiff[]> {
+  return invoke<FieldDiff[]>('diff_trace_headers', {
+    filePath: params.filePath,
+    indexA: params.indexA,
+    indexB: params.indexB,
+  });
+}
+
+/**
+ * Decode one trace header into fields with spec metadata and code labels.
+ */
+export async function getTraceHeaderDecoded(
+  filePath: string,
+  traceIndex: number
+): Promise<DecodedField[]> {
+  return invoke<DecodedField[]>('get_trace_header_decoded', { filePath, traceIndex });
+}
+
+/**
+ * Load trace headers only (no samples), taking every `stride`-th trace.
+ */
+export async function loadTraceHeaders(params: {
+  filePath: string;
+  startIndex: number;
+  count: number;
+  stride?: number;
+}): Promise<TraceHeader[]> {
+  return invoke<TraceHeader[]>('load_trace_headers', {
+    filePath: params.filePath,
+    startIndex: params.startIndex,
+    count: params.count,
+    stride: params.stride ?? null,
+  });
+}
+
+/**
+ * Compare binary header sample count/interval against the first trace headers.
  */
-export async function getBinaryHeaderSpec(): Promise<HeaderFieldSpec[]> {
-  return invoke<HeaderFieldSpec[]>('get_binary_header_spec');
+export async function checkConsistency(filePath: string): Promise<ConsistencyWarning[]> {
+  return invoke<ConsistencyWarning[]>('check_consistency', { filePath });
 }
 
 /**
- * Fetch backend spec for trace header fields.
+ * Compute the amplitude spectrum of a single trace.
  */
-export async function getTraceHeaderSpec(): Promise<HeaderFieldSpec[]> {
-  return invoke<HeaderFieldSpec[]>('get_trace_header_spec');
+export async function computeSpectrum(
+  filePath: string,
+  traceIndex: number
+): Promise<SpectrumResult> {
+  return invoke<SpectrumResult>('compute_spectrum', { filePath, traceIndex });
+}
+
+/**
+ * Compute the amplitude spectrum averaged over a trace range.
+ */
+export async function computeAverageSpectrum(params: {
+  filePath: string;
+  startIndex: number;
+  count: number;
+}): Promise<SpectrumResult> {
+  return invoke<SpectrumResult>('compute_average_spectrum', {
+    filePath: params.filePath,
+    startIndex: params.startIndex,
+    count: params.count,
+  });
+}
+
+/**
+ * Histogram sample amplitudes over a trace range; all-constant data yields one bin.
+ * Emits scan progress (see `onScanProgress`) over two passes, so `total` is `2 * count`.
+ */
+export async function amplitudeHistogram(params: {
+  filePath: string;
+  start: number;
+  count: number;
+  bins: number;
+}): Promise<HistogramResult> {
+  return invoke<HistogramResult>('amplitude_histogram', {
+    filePath: params.filePath,
+    start: params.start,
+    count: params.count,
+    bins: params.bins,
+  });
+}
+
+/**
+ * Compute receiver group spacing statistics over a range of traces.
+ */
+export async function computeTraceSpacing(params: {
+  filePath: string;
+  startIndex: number;
+  count: number;
+}): Promise<SpacingStats> {
+  return invoke<SpacingStats>('compute_trace_spacing', {
+    filePath: params.filePath,
+    startIndex: params.startIndex,
+    count: params.count,
+  });
+}
+
+/**
+ * Stored vs coordinate-derived source-to-group offset of each trace in a range.
+ */
+export async function computeTraceOffsets(params: {
+  filePath: string;
+  startIndex: number;
+  count: number;
+}): Promise<TraceOffset[]> {
+  return invoke<TraceOffset[]>('compute_trace_offsets', {
+    filePath: params.filePath,
+    startIndex: params.startIndex,
+    count: params.count,
+  });
+}
+
+/**
+ * Fetch backend spec for binary header fields, matching the file's revision when given.
+ */
+export async function getBinaryHeaderSpec(filePath?: string): Promise<HeaderFieldSpec[]> {
+  return invoke<HeaderFieldSpec[]>('get_binary_header_spec', { filePath: filePath ?? null });
+}
+
+/**
+ * Fingerprint a file (size, mtime, binary header) as 16 hex digits for tile cache keys.
+ *
+ * A change-detection heuristic, not a cryptographic hash.
+ */
+export async function getContentHash(filePath: string): Promise<string> {
+  return invoke<string>('get_content_hash', { filePath });
+}
+
+/**
+ * Check whether a file's float samples look like the other float format.
+ *
+ * Only suggests a code; pass it as `dataSampleFormat` in an override config to apply.
+ */
+export async function suggestSampleFormat(filePath: string): Promise<SampleFormatSuggestion> {
+  return invoke<SampleFormatSuggestion>('suggest_sample_format', { filePath });
+}
+
+/**
+ * Fetch backend spec for trace header fields, matching the file's revision when given.
+ */
+export async function getTraceHeaderSpec(filePath?: string): Promise<HeaderFieldSpec[]> {
+  return invoke<HeaderFieldSpec[]>('get_trace_header_spec', { filePath: filePath ?? null });
+}
+
+/**
+ * Remap trace header fields (e.g. inlines at byte 9) for the rest of the session.
+ *
+ * Overrides replace fields by key and drop fields whose bytes they take over;
+ * pass an empty list to restore the standard layout.
+ */
+export async function setTraceHeaderOverrides(
+  filePath: string,
+  overrides: HeaderFieldSpec[]
+): Promise<void> {
+  return invoke<void>('set_trace_header_overrides', { filePath, overrides });
+}
+
+/**
+ * Fetch the trace header fields in effect for a file, including overrides.
+ */
+export async function getEffectiveTraceSpec(filePath: string): Promise<HeaderFieldSpec[]> {
+  return invoke<HeaderFieldSpec[]>('get_effective_trace_spec', { filePath });
 }
 
 /**
  * Render trace visualization with the selected render mode and scaling.
+ *
+ * `renderConfig` is the same structure `saveRenderToFile` takes; its wiggle settings are
+ * dropped for variable-density renders.
  */
 export async function renderVariableDensity(params: {
   filePath: string;
-  viewport: ViewportConfig;
-  colormapType: ColormapType;
-  scaling: AmplitudeScaling;
-  renderMode: RenderMode;
-  wiggleConfig: WiggleConfig;
+  renderConfig: RenderConfig;
+  compression?: PngCompression;
+  skipAuxTraces?: boolean;
 }): Promise<RenderedImage> {
+  const { renderConfig } = params;
   return invoke<RenderedImage>('render_variable_density', {
     filePath: params.filePath,
-    viewport: params.viewport,
+    renderConfig: {
+      ...renderConfig,
+      wiggleConfig:
+        renderConfig.renderMode !== 'variable-density' ? renderConfig.wiggleConfig : null,
+    },
+    compression: params.compression ?? null,
+    skipAuxTraces: params.skipAuxTraces ?? null,
+  });
+}
+
+/**
+ * Render a small variable density thumbnail spanning the whole file, for previews.
+ */
+export async function renderOverview(params: {
+  filePath: string;
+  width: number;
+  height: number;
+  colormapType: ColormapType;
+}): Promise<RenderedImage> {
+  return invoke<RenderedImage>('render_overview', {
+    filePath: params.filePath,
+    width: params.width,
+    height: params.height,
     colormapType: params.colormapType,
+  });
+}
+
+/**
+ * Render a trace axis strip; resolves to the image and `[label, x]` of each tick.
+ *
+ * Pass `labelField` (one value per trace) to label ticks with a header
+ * attribute such as CDP instead of trace numbers.
+ */
+export async function renderTraceAxis(params: {
+  width: number;
+  startTrace: number;
+  traceCount: number;
+  labelField?: number[];
+}): Promise<[RenderedImage, Array<[number, number]>]> {
+  return invoke<[RenderedImage, Array<[number, number]>]>('render_trace_axis', {
+    width: params.width,
+    startTrace: params.startTrace,
+    traceCount: params.traceCount,
+    labelField: params.labelField ?? null,
+  });
+}
+
+/**
+ * Render a section and write it to `outputPath` (.png, .jpg, or .webp) on disk.
+ */
+export async function saveRenderToFile(params: {
+  renderConfig: RenderConfig;
+  filePath: string;
+  outputPath: string;
+}): Promise<void> {
+  return invoke<void>('save_render_to_file', {
+    renderConfig: params.renderConfig,
+    filePath: params.filePath,
+    outputPath: params.outputPath,
+  });
+}
+
+/**
+ * Write the viewport's traces as a 16-bit grayscale PNG, one pixel per sample.
+ * Amplitudes are normalized with `scaling`; the viewport's width and height are ignored.
+ */
+export async function saveAmplitudePng16(params: {
+  filePath: string;
+  outputPath: string;
+  viewport: ViewportConfig;
+  scaling: AmplitudeScaling;
+}): Promise<void> {
+  return invoke<void>('save_amplitude_png16', {
+    filePath: params.filePath,
+    outputPath: params.outputPath,
+    viewport: params.viewport,
     scaling: params.scaling,
-    renderMode: params.renderMode,
-    wiggleConfig: params.renderMode !== 'variable-density' ? params.wiggleConfig : null,
   });
 }
+
+/**
+ * Render a wiggle settings swatch from synthetic wavelets; no file is needed.
+ */
+export async function renderWigglePreview(params: {
+  wiggleConfig: WiggleConfig;
+  width: number;
+  height: number;
+}): Promise<RenderedImage> {
+  return invoke<RenderedImage>('render_wiggle_preview', {
+    wiggleConfig: params.wiggleConfig,
+    width: params.width,
+    height: params.height,
+  });
+}
+
+/**
+ * Cancel in-flight renders; they reject with a `RenderError` whose message is `cancelled`.
+ */
+export async function cancelRender(): Promise<void> {
+  return invoke<void>('cancel_render');
+}